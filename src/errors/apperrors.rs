@@ -9,6 +9,19 @@ pub enum MiniSQLError {
     Generic(String),
 }
 
+impl MiniSQLError {
+    /// The same error, telling the number of the register it happened on, the first one after the headers is 1
+    pub fn on_row(self, row: usize) -> MiniSQLError {
+        let located = |msg: String| format!("{} on row {} ", msg.trim_end(), row);
+        match self {
+            MiniSQLError::InvalidTable(msg) => MiniSQLError::InvalidTable(located(msg)),
+            MiniSQLError::InvalidColumn(msg) => MiniSQLError::InvalidColumn(located(msg)),
+            MiniSQLError::InvalidSyntax(msg) => MiniSQLError::InvalidSyntax(located(msg)),
+            MiniSQLError::Generic(msg) => MiniSQLError::Generic(located(msg)),
+        }
+    }
+}
+
 impl fmt::Display for MiniSQLError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -25,3 +38,9 @@ impl From<io::Error> for MiniSQLError {
         MiniSQLError::Generic(error.to_string())
     }
 }
+
+impl From<csv::Error> for MiniSQLError {
+    fn from(error: csv::Error) -> Self {
+        MiniSQLError::Generic(error.to_string())
+    }
+}
@@ -1,17 +1,18 @@
 use crate::errors::apperrors::MiniSQLError;
+use csv::{Reader, ReaderBuilder, Writer, WriterBuilder};
 use std::fs;
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{self, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-pub fn new_file_iterator(
-    dir: &String,
-    file_name: &String,
-) -> Result<BufReader<File>, MiniSQLError> {
+pub fn new_file_iterator(dir: &String, file_name: &String) -> Result<Reader<File>, MiniSQLError> {
     let route: String = format!("{}/{}{}", dir, file_name, ".csv");
     let file = File::open(route);
     match file {
         Ok(file) => {
-            let reader = BufReader::new(file);
+            // flexible para no rechazar tablas con filas incompletas
+            let reader = ReaderBuilder::new().flexible(true).from_reader(file);
             Ok(reader)
         }
         Err(_) => Err(MiniSQLError::InvalidTable(format!(
@@ -21,11 +22,11 @@ pub fn new_file_iterator(
     }
 }
 
-pub fn create_file(route: &String, name: &String) -> Result<File, MiniSQLError> {
+pub fn create_file(route: &String, name: &String) -> Result<Writer<File>, MiniSQLError> {
     let route: String = format!("{}/{}{}", route, name, ".temp");
     let file = File::create(route);
     match file {
-        Ok(file) => Ok(file),
+        Ok(file) => Ok(new_writer(file)),
         Err(err) => Err(MiniSQLError::Generic(format!(
             "there was a problem updating the table: {} ",
             err
@@ -46,14 +47,156 @@ pub fn rename_file(route: &String, name: &String) -> Result<(), MiniSQLError> {
     }
 }
 
-pub fn create_file_append(route: &String, name: &String) -> Result<File, MiniSQLError> {
+pub fn create_file_append(route: &String, name: &String) -> Result<Writer<File>, MiniSQLError> {
     let route: String = format!("{}/{}{}", route, name, ".csv");
-    let file = File::options().append(true).open(route);
+    let file = File::options().read(true).append(true).open(&route);
     match file {
-        Ok(file) => Ok(file),
+        Ok(mut file) => {
+            // si la ultima fila no termina en salto de linea, la nueva quedaria pegada a ella
+            if !ends_with_newline(&mut file)? {
+                file.write_all(b"\n")?;
+            }
+            Ok(new_writer(file))
+        }
         Err(err) => Err(MiniSQLError::Generic(format!(
             "there was a problem inserting into the table: {} ",
             err
         ))),
     }
 }
+
+/// Reads only the last byte of the file, an empty file counts as ending in a line break
+fn ends_with_newline(file: &mut File) -> io::Result<bool> {
+    if file.metadata()?.len() == 0 {
+        return Ok(true);
+    }
+    let mut last = [0u8; 1];
+    file.seek(SeekFrom::End(-1))?;
+    file.read_exact(&mut last)?;
+    Ok(last[0] == b'\n')
+}
+
+pub fn table_exists(route: &String, name: &String) -> bool {
+    Path::new(&format!("{}/{}{}", route, name, ".csv")).exists()
+}
+
+/// Creates a new table with only its header, never overwriting an existing one
+pub fn create_table(route: &String, name: &String, headers: &[String]) -> Result<(), MiniSQLError> {
+    let path: String = format!("{}/{}{}", route, name, ".csv");
+    let file = File::options().write(true).create_new(true).open(path);
+    match file {
+        Ok(file) => {
+            let mut writer = new_writer(file);
+            writer.write_record(headers)?;
+            writer.flush()?;
+            Ok(())
+        }
+        Err(err) if err.kind() == ErrorKind::AlreadyExists => Err(MiniSQLError::InvalidTable(
+            format!("table {} already exists ", name),
+        )),
+        Err(err) => Err(MiniSQLError::Generic(format!(
+            "there was a problem creating the table: {} ",
+            err
+        ))),
+    }
+}
+
+/// Removes a table and its schema.
+///
+/// The `.csv` is first renamed to the temporary file, so the table is gone at once
+/// even if the program stops before the files are deleted.
+pub fn drop_table(route: &String, name: &String) -> Result<(), MiniSQLError> {
+    let table_path: String = format!("{}/{}{}", route, name, ".csv");
+    let temp_path: String = format!("{}/{}{}", route, name, ".temp");
+    let schema_path: String = format!("{}/{}{}", route, name, ".schema");
+
+    if let Err(err) = fs::rename(table_path, &temp_path) {
+        return Err(MiniSQLError::Generic(format!(
+            "there was a problem dropping the table: {} ",
+            err
+        )));
+    }
+    match fs::remove_file(schema_path) {
+        Ok(()) => (),
+        Err(err) if err.kind() == ErrorKind::NotFound => (),
+        Err(err) => return Err(err.into()),
+    }
+    fs::remove_file(temp_path)?;
+    Ok(())
+}
+
+/// Moves a table and its schema to a new name, failing if a table already has it
+pub fn rename_table(route: &String, name: &String, new_name: &String) -> Result<(), MiniSQLError> {
+    if table_exists(route, new_name) {
+        return Err(MiniSQLError::InvalidTable(format!(
+            "table {} already exists ",
+            new_name
+        )));
+    }
+
+    let schema_path: String = format!("{}/{}{}", route, name, ".schema");
+    let rename = fs::rename(
+        format!("{}/{}{}", route, name, ".csv"),
+        format!("{}/{}{}", route, new_name, ".csv"),
+    )
+    .and_then(|_| {
+        match fs::rename(&schema_path, format!("{}/{}{}", route, new_name, ".schema")) {
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    });
+    match rename {
+        Ok(()) => Ok(()),
+        Err(err) => Err(MiniSQLError::Generic(format!(
+            "there was a problem renaming the table: {} ",
+            err
+        ))),
+    }
+}
+
+/// Creates an empty temporary directory for the files a query writes when its data does not fit in memory
+pub fn create_spill_dir() -> Result<PathBuf, MiniSQLError> {
+    static CREATED: AtomicUsize = AtomicUsize::new(0);
+    let dir = std::env::temp_dir().join(format!(
+        "mini_sql-{}-{}",
+        std::process::id(),
+        CREATED.fetch_add(1, Ordering::Relaxed)
+    ));
+    match fs::create_dir_all(&dir) {
+        Ok(()) => Ok(dir),
+        Err(err) => Err(MiniSQLError::Generic(format!(
+            "there was a problem creating a temporary directory: {} ",
+            err
+        ))),
+    }
+}
+
+/// Creates a temporary file without header, its registers may have any number of fields
+pub fn create_spill_file(path: &Path) -> Result<Writer<File>, MiniSQLError> {
+    match File::create(path) {
+        Ok(file) => Ok(new_writer(file)),
+        Err(err) => Err(MiniSQLError::Generic(format!(
+            "there was a problem writing a temporary file: {} ",
+            err
+        ))),
+    }
+}
+
+/// Reads back a temporary file written with `create_spill_file`
+pub fn new_spill_iterator(path: &Path) -> Result<Reader<File>, MiniSQLError> {
+    match File::open(path) {
+        Ok(file) => Ok(ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(file)),
+        Err(err) => Err(MiniSQLError::Generic(format!(
+            "there was a problem reading a temporary file: {} ",
+            err
+        ))),
+    }
+}
+
+/// Writer that quotes values only when needed ( commas, quotes or line breaks )
+fn new_writer(file: File) -> Writer<File> {
+    WriterBuilder::new().flexible(true).from_writer(file)
+}
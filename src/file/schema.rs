@@ -0,0 +1,163 @@
+use crate::errors::apperrors::MiniSQLError;
+use crate::parser::ast::ColumnDefinition;
+use crate::parser::grammar::parse_column_definition;
+use crate::types::value::ColumnType;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+/// Declared columns of a table, read from the `<table>.schema` file next to its `.csv`.
+///
+/// The file has one column definition per line, such as `id_cliente INTEGER`.
+/// Columns that are not declared keep being read as plain text.
+#[derive(Debug, Default, PartialEq)]
+pub struct Schema {
+    columns: Vec<ColumnDefinition>,
+}
+
+/// Reads the schema of a table, a table without a schema file has every column untyped.
+///
+/// # Examples
+///
+/// ```
+/// let schema = read_schema(&"data/tables".to_string(), &"clientes".to_string())?;
+/// let types = schema.column_types(&headers)?;
+/// ```
+///
+/// # Errors
+///
+/// - `MiniSQLError::InvalidTable` if a line of the schema is not a valid column definition.
+pub fn read_schema(dir: &String, table: &String) -> Result<Schema, MiniSQLError> {
+    let route: String = format!("{}/{}{}", dir, table, ".schema");
+    let content = match fs::read_to_string(&route) {
+        Ok(content) => content,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Schema::default()),
+        Err(err) => {
+            return Err(MiniSQLError::Generic(format!(
+                "there was a problem reading the schema of the table: {} ",
+                err
+            )))
+        }
+    };
+
+    let mut columns: Vec<ColumnDefinition> = vec![];
+    for (number, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match parse_column_definition(line) {
+            Ok(column) => columns.push(column),
+            Err(MiniSQLError::InvalidSyntax(message)) => {
+                return Err(MiniSQLError::InvalidTable(format!(
+                    "invalid schema for table {} on line {}: {}",
+                    table,
+                    number + 1,
+                    message
+                )))
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(Schema { columns })
+}
+
+pub fn has_schema(dir: &String, table: &String) -> bool {
+    Path::new(&format!("{}/{}{}", dir, table, ".schema")).exists()
+}
+
+/// Persists the column definitions of a table, one per line
+pub fn write_schema(
+    dir: &String,
+    table: &String,
+    columns: &[ColumnDefinition],
+) -> Result<(), MiniSQLError> {
+    write_schema_file(format!("{}/{}{}", dir, table, ".schema"), columns)
+}
+
+/// Writes the column definitions on a temporary file, so the schema only changes once
+/// `replace_schema` moves it in place
+pub fn write_temp_schema(
+    dir: &String,
+    table: &String,
+    columns: &[ColumnDefinition],
+) -> Result<(), MiniSQLError> {
+    write_schema_file(format!("{}/{}{}", dir, table, ".schema.temp"), columns)
+}
+
+/// Replaces the schema of a table with the one written by `write_temp_schema`
+pub fn replace_schema(dir: &String, table: &String) -> Result<(), MiniSQLError> {
+    let previous_path: String = format!("{}/{}{}", dir, table, ".schema.temp");
+    let new_path: String = format!("{}/{}{}", dir, table, ".schema");
+    match fs::rename(previous_path, new_path) {
+        Ok(()) => Ok(()),
+        Err(err) => Err(MiniSQLError::Generic(format!(
+            "there was a problem writing the schema of the table: {} ",
+            err
+        ))),
+    }
+}
+
+fn write_schema_file(route: String, columns: &[ColumnDefinition]) -> Result<(), MiniSQLError> {
+    let content: String = columns
+        .iter()
+        .map(|column| format!("{}\n", column))
+        .collect();
+    match fs::write(route, content) {
+        Ok(()) => Ok(()),
+        Err(err) => Err(MiniSQLError::Generic(format!(
+            "there was a problem writing the schema of the table: {} ",
+            err
+        ))),
+    }
+}
+
+impl Schema {
+    pub fn columns(&self) -> &[ColumnDefinition] {
+        &self.columns
+    }
+
+    pub fn add_column(&mut self, column: ColumnDefinition) {
+        self.columns.push(column);
+    }
+
+    /// Removes the definition of a field, if it was declared
+    pub fn drop_column(&mut self, name: &str) {
+        self.columns.retain(|column| column.name != name);
+    }
+
+    /// Renames the definition of a field, if it was declared
+    pub fn rename_column(&mut self, from: &str, to: &str) {
+        if let Some(column) = self.columns.iter_mut().find(|column| column.name == from) {
+            column.name = to.to_string();
+        }
+    }
+
+    /// Definition of a field, if the schema declares it
+    pub fn column(&self, name: &str) -> Option<&ColumnDefinition> {
+        self.columns.iter().find(|column| column.name == name)
+    }
+
+    /// Declared type of each header, in the same order
+    ///
+    /// # Errors
+    ///
+    /// - `MiniSQLError::InvalidTable` if the schema declares a column that is not on the table.
+    pub fn column_types(
+        &self,
+        headers: &[String],
+    ) -> Result<Vec<Option<ColumnType>>, MiniSQLError> {
+        let mut types: Vec<Option<ColumnType>> = vec![None; headers.len()];
+        for column in &self.columns {
+            match headers.iter().position(|header| *header == column.name) {
+                Some(index) => types[index] = Some(column.column_type),
+                None => {
+                    return Err(MiniSQLError::InvalidTable(format!(
+                        "schema declares field [ {} ] but the table does not have it ",
+                        column.name
+                    )))
+                }
+            }
+        }
+        Ok(types)
+    }
+}
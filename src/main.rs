@@ -1,21 +1,29 @@
 use std::env;
 mod errors;
 use crate::errors::apperrors::MiniSQLError;
+use crate::parser::ast::Statement;
+use crate::sentences::Coercion;
 pub mod file;
+pub mod parser;
 pub mod sentences;
+pub mod types;
 fn main() {
     let args: Vec<String> = env::args().collect();
 
     match get_args(args) {
-        Ok((route, sentence)) => match execute_query(route.to_string(), sentence.to_string()) {
-            Ok(()) => (),
-            Err(error) => eprintln!("{}", error),
-        },
+        Ok((route, sentence, coercion)) => {
+            match execute_query(route.to_string(), sentence.to_string(), coercion) {
+                Ok(()) => (),
+                Err(error) => eprintln!("{}", error),
+            }
+        }
         Err(error) => eprintln!("{}", error),
     }
 }
 
-fn get_args(mut args: Vec<String>) -> Result<(String, String), MiniSQLError> {
+/// Reads `[--strict] <route> <query>`. With `--strict` comparing values of different
+/// types is an error instead of comparing them as text.
+fn get_args(mut args: Vec<String>) -> Result<(String, String, Coercion), MiniSQLError> {
     let arg2 = args.pop().ok_or_else(|| {
         MiniSQLError::InvalidSyntax("Missing second parameter: SQL query.".to_string())
     })?;
@@ -24,58 +32,42 @@ fn get_args(mut args: Vec<String>) -> Result<(String, String), MiniSQLError> {
         MiniSQLError::InvalidSyntax("Missing first parameter: path to dir.".to_string())
     })?;
 
-    Ok((arg1, arg2))
-}
-
-fn execute_query(route: String, sentence: String) -> Result<(), MiniSQLError> {
-    let sententence_vec: Vec<String> = standardize_sentence(sentence);
-
-    if let Some(sentence_type) = sententence_vec.first() {
-        match sentence_type.to_uppercase().as_str() {
-            "SELECT" => sentences::select::execute_select_statement(sententence_vec, &route),
-            "DELETE" => sentences::delete::execute_delete_statement(sententence_vec, &route),
-            "INSERT" => sentences::insert::execute_insert_statement(sententence_vec, &route),
-            "UPDATE" => sentences::update::execute_update_statement(sententence_vec, &route),
-            _ => Err(MiniSQLError::InvalidSyntax(format!(
-                "Unable recognize statement: {}",
-                sentence_type.to_uppercase().as_str()
-            ))),
+    // las opciones van antes de la ruta, el primer argumento es el programa
+    let mut coercion = Coercion::Implicit;
+    for option in args.iter().skip(1) {
+        match option.as_str() {
+            "--strict" => coercion = Coercion::Strict,
+            option => {
+                return Err(MiniSQLError::InvalidSyntax(format!(
+                    "Unknown option {}.",
+                    option
+                )))
+            }
         }
-    } else {
-        Err(MiniSQLError::InvalidTable("Empty statement".to_string()))
     }
-}
-
-fn standardize_sentence(sentence: String) -> Vec<String> {
-    let sentence_vec: Vec<String> = sentence.split("'").map(|s| s.to_string()).collect();
-    let mut result: Vec<String> = Vec::new();
-
-    for (i, part) in sentence_vec.iter().enumerate() {
-        if i % 2 == 0 {
-            let mut modified_part = String::new();
 
-            for c in part.chars() {
-                match c {
-                    '(' | ')' | ',' => {
-                        modified_part.push(' ');
-                        modified_part.push(c);
-                        modified_part.push(' ');
-                    }
-                    ';' | '\n' | '\t' => modified_part.push(' '),
-                    _ => modified_part.push(c),
-                }
-            }
-
-            let replaced: Vec<String> = modified_part
-                .split_ascii_whitespace()
-                .map(|s| s.to_string())
-                .collect();
+    Ok((arg1, arg2, coercion))
+}
 
-            result.extend(replaced);
-        } else {
-            result.push(part.to_string());
+fn execute_query(route: String, sentence: String, coercion: Coercion) -> Result<(), MiniSQLError> {
+    match parser::grammar::parse(&sentence)? {
+        Statement::Select(select) => {
+            sentences::select::execute_select_statement(select, &route, coercion)
+        }
+        Statement::Delete(delete) => {
+            sentences::delete::execute_delete_statement(delete, &route, coercion)
         }
+        Statement::Insert(insert) => sentences::insert::execute_insert_statement(insert, &route),
+        Statement::Update(update) => {
+            sentences::update::execute_update_statement(update, &route, coercion)
+        }
+        Statement::CreateTable(create) => {
+            sentences::create::execute_create_statement(create, &route)
+        }
+        Statement::DropTable(drop) => sentences::drop::execute_drop_statement(drop, &route),
+        Statement::Truncate(truncate) => {
+            sentences::truncate::execute_truncate_statement(truncate, &route)
+        }
+        Statement::AlterTable(alter) => sentences::alter::execute_alter_statement(alter, &route),
     }
-
-    result
 }
@@ -0,0 +1,573 @@
+use crate::types::date::DateUnit;
+use crate::types::value::{ColumnType, NULL};
+use std::fmt;
+
+/// A parsed sentence, ready to be executed
+#[derive(Debug, Clone, PartialEq)]
+pub enum Statement {
+    Select(SelectStatement),
+    Insert(InsertStatement),
+    Update(UpdateStatement),
+    Delete(DeleteStatement),
+    CreateTable(CreateTableStatement),
+    DropTable(DropTableStatement),
+    Truncate(TruncateStatement),
+    AlterTable(AlterTableStatement),
+}
+
+/// SELECT [DISTINCT [ON (keys)]] projection FROM table [joins] [WHERE condition] [GROUP BY fields [HAVING condition]]
+/// [ORDER BY key [ASC|DESC] [NULLS {FIRST|LAST}], ...] [LIMIT n [OFFSET m] | [OFFSET m ROWS] FETCH FIRST n ROWS ONLY]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectStatement {
+    pub distinct: Option<Distinct>,
+    pub projection: Vec<SelectItem>,
+    pub from: TableReference,
+    pub joins: Vec<Join>,
+    pub condition: Option<Expr>,
+    /// fields that make up each group, empty when the query is not grouped
+    pub group_by: Vec<String>,
+    /// condition applied to each group once its aggregates are computed
+    pub having: Option<Expr>,
+    /// sort keys, in order of precedence
+    pub order_by: Vec<OrderBy>,
+    /// maximum number of registers to print, all of them when missing
+    pub limit: Option<usize>,
+    /// registers skipped before the first one printed
+    pub offset: usize,
+}
+
+/// INSERT INTO table (columns) VALUES (values), (values)
+#[derive(Debug, Clone, PartialEq)]
+pub struct InsertStatement {
+    pub table: String,
+    pub columns: Vec<String>,
+    /// one vector per inserted register, each with the same length as `columns`
+    pub values: Vec<Vec<Literal>>,
+}
+
+/// UPDATE table SET column = expression, ... [WHERE condition]
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpdateStatement {
+    pub table: String,
+    /// each expression is evaluated on the register as it was before the update
+    pub assignments: Vec<(String, Expr)>,
+    pub condition: Option<Expr>,
+}
+
+/// DELETE FROM table [WHERE condition]
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeleteStatement {
+    pub table: String,
+    pub condition: Option<Expr>,
+}
+
+/// CREATE TABLE [IF NOT EXISTS] table (column TYPE [constraints], ...)
+#[derive(Debug, Clone, PartialEq)]
+pub struct CreateTableStatement {
+    pub table: String,
+    pub if_not_exists: bool,
+    pub columns: Vec<ColumnDefinition>,
+}
+
+/// DROP TABLE [IF EXISTS] table
+#[derive(Debug, Clone, PartialEq)]
+pub struct DropTableStatement {
+    pub table: String,
+    pub if_exists: bool,
+}
+
+/// TRUNCATE TABLE table
+#[derive(Debug, Clone, PartialEq)]
+pub struct TruncateStatement {
+    pub table: String,
+}
+
+/// ALTER TABLE table operation
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlterTableStatement {
+    pub table: String,
+    pub operation: AlterOperation,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlterOperation {
+    /// ADD [COLUMN] column [TYPE] [DEFAULT value]
+    AddColumn {
+        name: String,
+        column_type: Option<ColumnType>,
+        default: Option<Literal>,
+    },
+    /// DROP [COLUMN] column
+    DropColumn(String),
+    /// RENAME [COLUMN] column TO new_name
+    RenameColumn { from: String, to: String },
+    /// RENAME TO new_name
+    RenameTable(String),
+}
+
+/// name TYPE [constraints], as declared on the schema of a table
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnDefinition {
+    pub name: String,
+    pub column_type: ColumnType,
+    pub constraints: Vec<ColumnConstraint>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnConstraint {
+    NotNull,
+    PrimaryKey,
+    Unique,
+    /// value written when an INSERT does not give one
+    Default(Literal),
+}
+
+impl ColumnDefinition {
+    /// The field can not be left NULL
+    pub fn is_required(&self) -> bool {
+        self.constraints
+            .iter()
+            .any(|c| matches!(c, ColumnConstraint::NotNull | ColumnConstraint::PrimaryKey))
+    }
+
+    /// Two registers can not share a value on the field
+    pub fn is_unique(&self) -> bool {
+        self.constraints
+            .iter()
+            .any(|c| matches!(c, ColumnConstraint::Unique | ColumnConstraint::PrimaryKey))
+    }
+
+    pub fn default_value(&self) -> Option<&Literal> {
+        self.constraints.iter().find_map(|c| match c {
+            ColumnConstraint::Default(literal) => Some(literal),
+            _ => None,
+        })
+    }
+}
+
+/// table [[AS] alias]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableReference {
+    pub name: String,
+    pub alias: Option<String>,
+}
+
+impl TableReference {
+    /// Name used to qualify the fields of the table, `alias.field` or `table.field`
+    pub fn qualifier(&self) -> &str {
+        self.alias.as_deref().unwrap_or(&self.name)
+    }
+}
+
+/// [INNER | LEFT | CROSS] JOIN table [ON condition], a comma on the FROM is a cross join
+#[derive(Debug, Clone, PartialEq)]
+pub struct Join {
+    pub kind: JoinKind,
+    pub table: TableReference,
+    pub condition: Option<Expr>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JoinKind {
+    Inner,
+    /// keeps the registers of the left side without a match, with NULL fields
+    Left,
+    Cross,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Distinct {
+    /// DISTINCT, equal registers are printed once
+    All,
+    /// DISTINCT ON (keys), only the first register of each key after ORDER BY
+    On(Vec<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SelectItem {
+    /// SELECT *
+    Wildcard,
+    /// field [AS alias]
+    Column { name: String, alias: Option<String> },
+    /// FUNCTION(field) [AS alias]
+    Aggregate {
+        aggregate: Aggregate,
+        alias: Option<String>,
+    },
+    /// expression computed for each register, `precio * cantidad [AS alias]`
+    Expr { expr: Expr, alias: Option<String> },
+}
+
+impl SelectItem {
+    pub fn alias(&self) -> Option<&str> {
+        match self {
+            SelectItem::Column { alias, .. }
+            | SelectItem::Aggregate { alias, .. }
+            | SelectItem::Expr { alias, .. } => alias.as_deref(),
+            SelectItem::Wildcard => None,
+        }
+    }
+}
+
+/// FUNCTION([DISTINCT] field), or COUNT(*)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Aggregate {
+    pub function: AggregateFunction,
+    pub distinct: bool,
+    /// `None` for COUNT(*)
+    pub argument: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AggregateFunction {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl AggregateFunction {
+    /// Function names are not reserved words, they are matched ignoring case
+    pub fn from_name(name: &str) -> Option<AggregateFunction> {
+        match name.to_uppercase().as_str() {
+            "COUNT" => Some(AggregateFunction::Count),
+            "SUM" => Some(AggregateFunction::Sum),
+            "AVG" => Some(AggregateFunction::Avg),
+            "MIN" => Some(AggregateFunction::Min),
+            "MAX" => Some(AggregateFunction::Max),
+            _ => None,
+        }
+    }
+}
+
+/// A sort key: a field, a selected alias, a position on the projection ( `ORDER BY 2` )
+/// or an aggregate function of a grouped query
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderBy {
+    pub expr: Expr,
+    pub ascending: bool,
+    /// `None` keeps the default: NULLs first when ascending, last when descending
+    pub nulls_first: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    String(String),
+    /// kept as written, each executor decides how to read it
+    Number(String),
+    Boolean(bool),
+    Null,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    /// field name, `table.field` when qualified
+    Column(String),
+    Literal(Literal),
+    /// only valid on the projection and HAVING, evaluated over the registers of each group
+    Aggregate(Aggregate),
+    Unary {
+        operator: UnaryOperator,
+        expr: Box<Expr>,
+    },
+    Binary {
+        left: Box<Expr>,
+        operator: BinaryOperator,
+        right: Box<Expr>,
+    },
+    /// expr [NOT] {LIKE | ILIKE} pattern [ESCAPE 'c'], `%` matches any text and `_` one character
+    Like {
+        expr: Box<Expr>,
+        pattern: Box<Expr>,
+        escape: Option<String>,
+        negated: bool,
+        /// ILIKE, ignores case
+        case_insensitive: bool,
+    },
+    /// expr [NOT] IN (values)
+    InList {
+        expr: Box<Expr>,
+        list: Vec<Expr>,
+        negated: bool,
+    },
+    /// expr [NOT] BETWEEN low AND high, both ends included
+    Between {
+        expr: Box<Expr>,
+        low: Box<Expr>,
+        high: Box<Expr>,
+        negated: bool,
+    },
+    /// expr IS [NOT] NULL
+    IsNull {
+        expr: Box<Expr>,
+        negated: bool,
+    },
+    Function(Function),
+}
+
+/// Date and time functions. Their names are not reserved words, they are matched ignoring case.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Function {
+    /// NOW() or CURRENT_TIMESTAMP, the date and time in UTC when the sentence started
+    Now,
+    /// CURRENT_DATE, in UTC
+    CurrentDate,
+    /// DATE_ADD(value, INTERVAL amount unit), a negative amount goes back
+    DateAdd {
+        value: Box<Expr>,
+        amount: Box<Expr>,
+        unit: DateUnit,
+    },
+    /// EXTRACT(unit FROM value)
+    Extract { unit: DateUnit, value: Box<Expr> },
+    /// DATE_TRUNC('unit', value)
+    DateTrunc { unit: DateUnit, value: Box<Expr> },
+}
+
+impl Function {
+    /// Looks up a function written with parenthesis by its name
+    pub fn is_function(name: &str) -> bool {
+        ["NOW", "DATE_ADD", "EXTRACT", "DATE_TRUNC"].contains(&name.to_uppercase().as_str())
+    }
+}
+
+impl Expr {
+    /// Expressions directly inside this one
+    pub fn children(&self) -> Vec<&Expr> {
+        match self {
+            Expr::Column(_) | Expr::Literal(_) | Expr::Aggregate(_) => vec![],
+            Expr::Function(Function::Now | Function::CurrentDate) => vec![],
+            Expr::Function(Function::DateAdd { value, amount, .. }) => vec![value, amount],
+            Expr::Function(Function::Extract { value, .. } | Function::DateTrunc { value, .. }) => {
+                vec![value]
+            }
+            Expr::Unary { expr, .. } | Expr::IsNull { expr, .. } => vec![expr],
+            Expr::Binary { left, right, .. } => vec![left, right],
+            Expr::Like { expr, pattern, .. } => vec![expr, pattern],
+            Expr::InList { expr, list, .. } => {
+                let mut children: Vec<&Expr> = vec![expr];
+                children.extend(list.iter());
+                children
+            }
+            Expr::Between {
+                expr, low, high, ..
+            } => vec![expr, low, high],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnaryOperator {
+    Not,
+    /// -x
+    Minus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOperator {
+    And,
+    Or,
+    Equal,
+    NotEqual,
+    Greater,
+    GreaterEqual,
+    Less,
+    LessEqual,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    /// `||`, joins the text of both sides
+    Concat,
+}
+
+impl BinaryOperator {
+    pub fn is_comparison(&self) -> bool {
+        matches!(
+            self,
+            BinaryOperator::Equal
+                | BinaryOperator::NotEqual
+                | BinaryOperator::Greater
+                | BinaryOperator::GreaterEqual
+                | BinaryOperator::Less
+                | BinaryOperator::LessEqual
+        )
+    }
+}
+
+/// Raw value of the literal, as it is stored on a table
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Literal::String(value) | Literal::Number(value) => write!(f, "{}", value),
+            Literal::Boolean(value) => write!(f, "{}", value),
+            Literal::Null => write!(f, "{}", NULL),
+        }
+    }
+}
+
+/// Definition as it is written on the schema file
+impl fmt::Display for ColumnDefinition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.name, self.column_type)?;
+        for constraint in &self.constraints {
+            write!(f, " {}", constraint)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ColumnConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColumnConstraint::NotNull => write!(f, "NOT NULL"),
+            ColumnConstraint::PrimaryKey => write!(f, "PRIMARY KEY"),
+            ColumnConstraint::Unique => write!(f, "UNIQUE"),
+            ColumnConstraint::Default(Literal::String(value)) => {
+                write!(f, "DEFAULT '{}'", value.replace('\'', "''"))
+            }
+            ColumnConstraint::Default(Literal::Null) => write!(f, "DEFAULT NULL"),
+            ColumnConstraint::Default(literal) => write!(f, "DEFAULT {}", literal),
+        }
+    }
+}
+
+impl fmt::Display for AggregateFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            AggregateFunction::Count => "COUNT",
+            AggregateFunction::Sum => "SUM",
+            AggregateFunction::Avg => "AVG",
+            AggregateFunction::Min => "MIN",
+            AggregateFunction::Max => "MAX",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl fmt::Display for Aggregate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let distinct = if self.distinct { "DISTINCT " } else { "" };
+        match &self.argument {
+            Some(argument) => write!(f, "{}({}{})", self.function, distinct, argument),
+            None => write!(f, "{}(*)", self.function),
+        }
+    }
+}
+
+impl fmt::Display for UnaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnaryOperator::Not => write!(f, "NOT"),
+            UnaryOperator::Minus => write!(f, "-"),
+        }
+    }
+}
+
+impl fmt::Display for BinaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            BinaryOperator::And => "AND",
+            BinaryOperator::Or => "OR",
+            BinaryOperator::Equal => "=",
+            BinaryOperator::NotEqual => "!=",
+            BinaryOperator::Greater => ">",
+            BinaryOperator::GreaterEqual => ">=",
+            BinaryOperator::Less => "<",
+            BinaryOperator::LessEqual => "<=",
+            BinaryOperator::Add => "+",
+            BinaryOperator::Subtract => "-",
+            BinaryOperator::Multiply => "*",
+            BinaryOperator::Divide => "/",
+            BinaryOperator::Modulo => "%",
+            BinaryOperator::Concat => "||",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+/// SQL representation of the expression, used on error messages
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Column(name) => write!(f, "{}", name),
+            Expr::Literal(Literal::String(value)) => write!(f, "'{}'", value),
+            Expr::Literal(Literal::Null) => write!(f, "NULL"),
+            Expr::Literal(literal) => write!(f, "{}", literal),
+            Expr::Aggregate(aggregate) => write!(f, "{}", aggregate),
+            Expr::Unary {
+                operator: UnaryOperator::Minus,
+                expr,
+            } => write!(f, "-{}", expr),
+            Expr::Unary { operator, expr } => write!(f, "{} {}", operator, expr),
+            Expr::Binary {
+                left,
+                operator,
+                right,
+            } => write!(f, "( {} {} {} )", left, operator, right),
+            Expr::Like {
+                expr,
+                pattern,
+                escape,
+                negated,
+                case_insensitive,
+            } => {
+                let not = if *negated { "NOT " } else { "" };
+                let like = if *case_insensitive { "ILIKE" } else { "LIKE" };
+                write!(f, "( {} {}{} {}", expr, not, like, pattern)?;
+                if let Some(escape) = escape {
+                    write!(f, " ESCAPE '{}'", escape)?;
+                }
+                write!(f, " )")
+            }
+            Expr::InList {
+                expr,
+                list,
+                negated,
+            } => {
+                let not = if *negated { "NOT " } else { "" };
+                let list: Vec<String> = list.iter().map(|value| value.to_string()).collect();
+                write!(f, "( {} {}IN ({}) )", expr, not, list.join(", "))
+            }
+            Expr::Between {
+                expr,
+                low,
+                high,
+                negated,
+            } => {
+                let not = if *negated { "NOT " } else { "" };
+                write!(f, "( {} {}BETWEEN {} AND {} )", expr, not, low, high)
+            }
+            Expr::IsNull { expr, negated } => {
+                let not = if *negated { "NOT " } else { "" };
+                write!(f, "( {} IS {}NULL )", expr, not)
+            }
+            Expr::Function(function) => write!(f, "{}", function),
+        }
+    }
+}
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Function::Now => write!(f, "NOW()"),
+            Function::CurrentDate => write!(f, "CURRENT_DATE"),
+            Function::DateAdd {
+                value,
+                amount,
+                unit,
+            } => write!(f, "DATE_ADD({}, INTERVAL {} {})", value, amount, unit),
+            Function::Extract { unit, value } => write!(f, "EXTRACT({} FROM {})", unit, value),
+            Function::DateTrunc { unit, value } => {
+                write!(
+                    f,
+                    "DATE_TRUNC('{}', {})",
+                    unit.to_string().to_lowercase(),
+                    value
+                )
+            }
+        }
+    }
+}
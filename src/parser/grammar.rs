@@ -0,0 +1,1815 @@
+use super::ast::{
+    Aggregate, AggregateFunction, AlterOperation, AlterTableStatement, BinaryOperator,
+    ColumnConstraint, ColumnDefinition, CreateTableStatement, DeleteStatement, Distinct,
+    DropTableStatement, Expr, Function, InsertStatement, Join, JoinKind, Literal, OrderBy,
+    SelectItem, SelectStatement, Statement, TableReference, TruncateStatement, UnaryOperator,
+    UpdateStatement,
+};
+use super::tokenizer::{syntax_error, tokenize, Token, TokenKind};
+use crate::errors::apperrors::MiniSQLError;
+use crate::types::date::DateUnit;
+use crate::types::value::ColumnType;
+
+/// Parses a full SQL sentence into a `Statement`.
+///
+/// A single trailing `;` is accepted. Conditions are parsed with the usual precedence,
+/// from lowest to highest: `OR`, `AND`, `NOT`, comparisons and the `LIKE`, `IN` and `BETWEEN`
+/// predicates, `||`, `+ -`, `* / %`,
+/// the negative sign and parenthesised groups.
+/// The date functions `NOW()`, `CURRENT_DATE`, `DATE_ADD`, `EXTRACT` and `DATE_TRUNC`
+/// can be used wherever a value can.
+///
+/// # Examples
+///
+/// ```
+/// parse("SELECT nombre FROM clientes WHERE id = 5 OR ( apellido = 'diaz' AND NOT id > 3 )");
+/// parse("INSERT INTO clientes (id, nombre) VALUES (1, 'pepe'), (2, 'carlos');");
+/// ```
+///
+/// # Errors
+///
+/// - `MiniSQLError::InvalidSyntax` with the line and column where the sentence stopped making sense.
+/// - `MiniSQLError::InvalidTable` if the statement has no target table, or its name has a path separator.
+pub fn parse(sentence: &str) -> Result<Statement, MiniSQLError> {
+    let tokens = tokenize(sentence)?;
+    if tokens.is_empty() {
+        return Err(MiniSQLError::InvalidTable("Empty statement".to_string()));
+    }
+
+    let mut parser = Parser {
+        tokens,
+        position: 0,
+        sentence,
+    };
+    let statement = parser.parse_statement()?;
+    parser.expect_end()?;
+    Ok(statement)
+}
+
+/// Parses a single column definition, such as a line of a schema file.
+///
+/// # Examples
+///
+/// ```
+/// parse_column_definition("id_cliente INTEGER");
+/// ```
+///
+/// # Errors
+///
+/// - `MiniSQLError::InvalidSyntax` if the definition is incomplete or the type is unknown.
+pub fn parse_column_definition(definition: &str) -> Result<ColumnDefinition, MiniSQLError> {
+    let mut parser = Parser {
+        tokens: tokenize(definition)?,
+        position: 0,
+        sentence: definition,
+    };
+    let column = parser.parse_column_definition()?;
+    parser.expect_end()?;
+    Ok(column)
+}
+
+/// A selected field, literal, function, parenthesis or negative sign can start an expression
+fn starts_value(token: &Token) -> bool {
+    match token.kind {
+        TokenKind::Identifier | TokenKind::StringLiteral | TokenKind::NumericLiteral => true,
+        TokenKind::Keyword => {
+            ["TRUE", "FALSE", "NULL"].contains(&token.value.as_str()) || token.is_name()
+        }
+        TokenKind::Operator => token.value == "-",
+        TokenKind::Punctuation => token.value == "(",
+    }
+}
+
+/// Keywords that can follow `NOT` after a value
+fn is_predicate(token: &Token) -> bool {
+    ["LIKE", "ILIKE", "IN", "BETWEEN"]
+        .iter()
+        .any(|keyword| token.is_keyword(keyword))
+}
+
+fn binary(left: Expr, operator: BinaryOperator, right: Expr) -> Expr {
+    Expr::Binary {
+        left: Box::new(left),
+        operator,
+        right: Box::new(right),
+    }
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    position: usize,
+    sentence: &'a str,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn check_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(token) if token.is_keyword(keyword))
+    }
+
+    fn check_punctuation(&self, symbol: &str) -> bool {
+        matches!(self.peek(), Some(token) if token.is_punctuation(symbol))
+    }
+
+    fn consume_keyword(&mut self, keyword: &str) -> bool {
+        if self.check_keyword(keyword) {
+            self.position += 1;
+            return true;
+        }
+        false
+    }
+
+    fn consume_punctuation(&mut self, symbol: &str) -> bool {
+        if self.check_punctuation(symbol) {
+            self.position += 1;
+            return true;
+        }
+        false
+    }
+
+    fn consume_operator(&mut self, symbol: &str) -> bool {
+        if matches!(self.peek(), Some(token) if token.is_operator(symbol)) {
+            self.position += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), MiniSQLError> {
+        if self.consume_keyword(keyword) {
+            return Ok(());
+        }
+        Err(self.error(&format!("expected {}", keyword)))
+    }
+
+    fn expect_punctuation(&mut self, symbol: &str) -> Result<(), MiniSQLError> {
+        if self.consume_punctuation(symbol) {
+            return Ok(());
+        }
+        Err(self.error(&format!("expected '{}'", symbol)))
+    }
+
+    /// A keyword keeps the case it was written with when it is read as a name
+    fn name_of(&self, token: &Token) -> String {
+        match token.kind {
+            TokenKind::Keyword => self.sentence[token.offset..]
+                .chars()
+                .take_while(|c| c.is_alphanumeric() || *c == '_')
+                .collect(),
+            _ => token.value.to_string(),
+        }
+    }
+
+    fn expect_identifier(&mut self, description: &str) -> Result<String, MiniSQLError> {
+        match self.peek() {
+            Some(token) if token.is_name() => {
+                let name = self.name_of(token);
+                self.position += 1;
+                Ok(name)
+            }
+            _ => Err(self.error(&format!("expected {}", description))),
+        }
+    }
+
+    fn expect_end(&mut self) -> Result<(), MiniSQLError> {
+        self.consume_punctuation(";");
+        if self.peek().is_some() {
+            return Err(self.error("unexpected content after the end of the statement"));
+        }
+        Ok(())
+    }
+
+    /// Syntax error pointing at the current token
+    fn error(&self, message: &str) -> MiniSQLError {
+        let offset = match self.peek() {
+            Some(token) => token.offset,
+            None => self.sentence.len(),
+        };
+        syntax_error(self.sentence, offset, message)
+    }
+
+    fn parse_statement(&mut self) -> Result<Statement, MiniSQLError> {
+        let first = match self.peek() {
+            Some(token) => token.clone(),
+            None => return Err(MiniSQLError::InvalidTable("Empty statement".to_string())),
+        };
+
+        if first.kind != TokenKind::Keyword {
+            return Err(MiniSQLError::InvalidSyntax(format!(
+                "Unable recognize statement: {}",
+                first.value
+            )));
+        }
+
+        match first.value.as_str() {
+            "SELECT" => Ok(Statement::Select(self.parse_select()?)),
+            "INSERT" => Ok(Statement::Insert(self.parse_insert()?)),
+            "UPDATE" => Ok(Statement::Update(self.parse_update()?)),
+            "DELETE" => Ok(Statement::Delete(self.parse_delete()?)),
+            "CREATE" => Ok(Statement::CreateTable(self.parse_create_table()?)),
+            "DROP" => Ok(Statement::DropTable(self.parse_drop_table()?)),
+            "TRUNCATE" => Ok(Statement::Truncate(self.parse_truncate()?)),
+            "ALTER" => Ok(Statement::AlterTable(self.parse_alter_table()?)),
+            _ => Err(MiniSQLError::InvalidSyntax(format!(
+                "Unable recognize statement: {}",
+                first.value
+            ))),
+        }
+    }
+
+    /// A missing table name is reported as an invalid table instead of a syntax error
+    fn parse_table_name(&mut self) -> Result<String, MiniSQLError> {
+        match self.peek() {
+            Some(token) if token.is_name() => {
+                let name = self.name_of(token);
+                // el nombre termina en la ruta del archivo, no puede salir de la carpeta de tablas
+                if name.contains(['/', '\\', '\0']) {
+                    return Err(MiniSQLError::InvalidTable(format!(
+                        "table name [ {} ] can not contain path separators ",
+                        name
+                    )));
+                }
+                self.position += 1;
+                Ok(name)
+            }
+            _ => Err(MiniSQLError::InvalidTable(
+                "no table was given ".to_string(),
+            )),
+        }
+    }
+
+    /// Type names are not reserved words, so they are read as identifiers.
+    /// Returns `None` without moving if the next token is not a known type.
+    fn parse_column_type(&mut self) -> Option<ColumnType> {
+        let column_type = match self.peek() {
+            Some(token) if token.kind == TokenKind::Identifier => {
+                ColumnType::from_name(&token.value)
+            }
+            _ => None,
+        };
+        if column_type.is_some() {
+            self.position += 1;
+        }
+        column_type
+    }
+
+    fn parse_column_definition(&mut self) -> Result<ColumnDefinition, MiniSQLError> {
+        let name = self.expect_identifier("a field name")?;
+        let column_type = match self.parse_column_type() {
+            Some(column_type) => column_type,
+            None => {
+                return Err(self.error(
+                    "expected a type: INTEGER, BIGINT, REAL, DECIMAL, TEXT, BOOLEAN, DATE, TIME or TIMESTAMP",
+                ))
+            }
+        };
+
+        let mut constraints: Vec<ColumnConstraint> = vec![];
+        loop {
+            let constraint = if self.consume_keyword("NOT") {
+                self.expect_keyword("NULL")?;
+                ColumnConstraint::NotNull
+            } else if self.consume_keyword("PRIMARY") {
+                self.expect_keyword("KEY")?;
+                ColumnConstraint::PrimaryKey
+            } else if self.consume_keyword("UNIQUE") {
+                ColumnConstraint::Unique
+            } else if self.consume_keyword("DEFAULT") {
+                ColumnConstraint::Default(self.parse_literal()?)
+            } else {
+                break;
+            };
+            constraints.push(constraint);
+        }
+
+        Ok(ColumnDefinition {
+            name,
+            column_type,
+            constraints,
+        })
+    }
+
+    fn parse_create_table(&mut self) -> Result<CreateTableStatement, MiniSQLError> {
+        self.expect_keyword("CREATE")?;
+        self.expect_keyword("TABLE")?;
+        let if_not_exists = if self.consume_keyword("IF") {
+            self.expect_keyword("NOT")?;
+            self.expect_keyword("EXISTS")?;
+            true
+        } else {
+            false
+        };
+        let table = self.parse_table_name()?;
+
+        self.expect_punctuation("(")?;
+        let mut columns: Vec<ColumnDefinition> = vec![];
+        loop {
+            columns.push(self.parse_column_definition()?);
+            if !self.consume_punctuation(",") {
+                break;
+            }
+        }
+        self.expect_punctuation(")")?;
+
+        Ok(CreateTableStatement {
+            table,
+            if_not_exists,
+            columns,
+        })
+    }
+
+    fn parse_drop_table(&mut self) -> Result<DropTableStatement, MiniSQLError> {
+        self.expect_keyword("DROP")?;
+        self.expect_keyword("TABLE")?;
+        let if_exists = if self.consume_keyword("IF") {
+            self.expect_keyword("EXISTS")?;
+            true
+        } else {
+            false
+        };
+        let table = self.parse_table_name()?;
+        Ok(DropTableStatement { table, if_exists })
+    }
+
+    fn parse_truncate(&mut self) -> Result<TruncateStatement, MiniSQLError> {
+        self.expect_keyword("TRUNCATE")?;
+        self.expect_keyword("TABLE")?;
+        let table = self.parse_table_name()?;
+        Ok(TruncateStatement { table })
+    }
+
+    fn parse_alter_table(&mut self) -> Result<AlterTableStatement, MiniSQLError> {
+        self.expect_keyword("ALTER")?;
+        self.expect_keyword("TABLE")?;
+        let table = self.parse_table_name()?;
+
+        let operation = if self.consume_keyword("ADD") {
+            self.consume_keyword("COLUMN");
+            let name = self.expect_identifier("a field name")?;
+            let column_type = self.parse_column_type();
+            let default = if self.consume_keyword("DEFAULT") {
+                Some(self.parse_literal()?)
+            } else {
+                None
+            };
+            AlterOperation::AddColumn {
+                name,
+                column_type,
+                default,
+            }
+        } else if self.consume_keyword("DROP") {
+            self.consume_keyword("COLUMN");
+            AlterOperation::DropColumn(self.expect_identifier("a field name")?)
+        } else if self.consume_keyword("RENAME") {
+            if self.consume_keyword("TO") {
+                AlterOperation::RenameTable(self.parse_table_name()?)
+            } else {
+                self.consume_keyword("COLUMN");
+                let from = self.expect_identifier("a field name")?;
+                self.expect_keyword("TO")?;
+                let to = self.expect_identifier("a new field name")?;
+                AlterOperation::RenameColumn { from, to }
+            }
+        } else {
+            return Err(self.error("expected ADD, DROP or RENAME"));
+        };
+
+        Ok(AlterTableStatement { table, operation })
+    }
+
+    fn parse_select(&mut self) -> Result<SelectStatement, MiniSQLError> {
+        self.expect_keyword("SELECT")?;
+
+        let distinct = if self.consume_keyword("DISTINCT") {
+            if self.consume_keyword("ON") {
+                self.expect_punctuation("(")?;
+                let mut keys: Vec<Expr> = vec![];
+                loop {
+                    keys.push(self.parse_primary()?);
+                    if !self.consume_punctuation(",") {
+                        break;
+                    }
+                }
+                self.expect_punctuation(")")?;
+                Some(Distinct::On(keys))
+            } else {
+                Some(Distinct::All)
+            }
+        } else {
+            None
+        };
+
+        let mut projection: Vec<SelectItem> = vec![];
+        loop {
+            match self.peek() {
+                Some(token) if token.is_operator("*") => {
+                    self.position += 1;
+                    projection.push(SelectItem::Wildcard);
+                }
+                Some(token) if starts_value(token) => {
+                    let expr = self.parse_concat()?;
+                    let alias = self.parse_alias("an alias for the field")?;
+                    projection.push(match expr {
+                        Expr::Column(name) => SelectItem::Column { name, alias },
+                        Expr::Aggregate(aggregate) => SelectItem::Aggregate { aggregate, alias },
+                        expr => SelectItem::Expr { expr, alias },
+                    });
+                }
+                _ => return Err(self.error("expected a field name")),
+            }
+            if !self.consume_punctuation(",") {
+                break;
+            }
+        }
+
+        if !self.consume_keyword("FROM") {
+            return Err(MiniSQLError::InvalidTable(
+                "no table was given ".to_string(),
+            ));
+        }
+        let from = self.parse_table_reference()?;
+        let joins = self.parse_joins()?;
+        let condition = self.parse_where()?;
+
+        let mut group_by: Vec<String> = vec![];
+        if self.consume_keyword("GROUP") {
+            self.expect_keyword("BY")?;
+            loop {
+                group_by.push(self.parse_column_name("a field to group by")?);
+                if !self.consume_punctuation(",") {
+                    break;
+                }
+            }
+        }
+        let having = if self.consume_keyword("HAVING") {
+            Some(self.parse_expr()?)
+        } else {
+            None
+        };
+
+        let mut order_by: Vec<OrderBy> = vec![];
+        if self.consume_keyword("ORDER") {
+            self.expect_keyword("BY")?;
+            loop {
+                order_by.push(self.parse_order_key()?);
+                if !self.consume_punctuation(",") {
+                    break;
+                }
+            }
+        }
+        let (limit, offset) = self.parse_limit()?;
+
+        Ok(SelectStatement {
+            distinct,
+            projection,
+            from,
+            joins,
+            condition,
+            group_by,
+            having,
+            order_by,
+            limit,
+            offset,
+        })
+    }
+
+    /// field, alias, position or aggregate [ASC|DESC] [NULLS {FIRST|LAST}]
+    fn parse_order_key(&mut self) -> Result<OrderBy, MiniSQLError> {
+        let expr = match self.peek() {
+            Some(token) if token.is_name() || token.kind == TokenKind::NumericLiteral => {
+                self.parse_primary()?
+            }
+            _ => return Err(self.error("expected a field to order by")),
+        };
+        let ascending = if self.consume_keyword("DESC") {
+            false
+        } else {
+            self.consume_keyword("ASC");
+            true
+        };
+        let nulls_first = if self.consume_keyword("NULLS") {
+            if self.consume_keyword("FIRST") {
+                Some(true)
+            } else {
+                self.expect_keyword("LAST")?;
+                Some(false)
+            }
+        } else {
+            None
+        };
+        Ok(OrderBy {
+            expr,
+            ascending,
+            nulls_first,
+        })
+    }
+
+    /// LIMIT n [OFFSET m], or the standard [OFFSET m {ROW|ROWS}] FETCH {FIRST|NEXT} n {ROW|ROWS} ONLY
+    fn parse_limit(&mut self) -> Result<(Option<usize>, usize), MiniSQLError> {
+        if self.consume_keyword("LIMIT") {
+            let limit = self.parse_count("a number of registers")?;
+            let offset = if self.consume_keyword("OFFSET") {
+                self.parse_count("a number of registers to skip")?
+            } else {
+                0
+            };
+            return Ok((Some(limit), offset));
+        }
+
+        let mut offset = 0;
+        if self.consume_keyword("OFFSET") {
+            offset = self.parse_count("a number of registers to skip")?;
+            if !self.consume_keyword("ROWS") {
+                self.consume_keyword("ROW");
+            }
+        }
+        if !self.consume_keyword("FETCH") {
+            return Ok((None, offset));
+        }
+        if !self.consume_keyword("FIRST") {
+            self.expect_keyword("NEXT")?;
+        }
+        let limit = self.parse_count("a number of registers")?;
+        if !self.consume_keyword("ROWS") {
+            self.expect_keyword("ROW")?;
+        }
+        self.expect_keyword("ONLY")?;
+        Ok((Some(limit), offset))
+    }
+
+    fn parse_count(&mut self, description: &str) -> Result<usize, MiniSQLError> {
+        let count = match self.peek() {
+            Some(token) if token.kind == TokenKind::NumericLiteral => {
+                token.value.parse::<usize>().ok()
+            }
+            _ => None,
+        };
+        match count {
+            Some(count) => {
+                self.position += 1;
+                Ok(count)
+            }
+            None => Err(self.error(&format!("expected {}", description))),
+        }
+    }
+
+    /// An identifier followed by '(' is a function call
+    fn next_is_call(&self) -> bool {
+        matches!(self.tokens.get(self.position + 1), Some(token) if token.is_punctuation("("))
+    }
+
+    fn parse_aggregate(&mut self) -> Result<Aggregate, MiniSQLError> {
+        let function = match self.peek() {
+            Some(token) => match AggregateFunction::from_name(&token.value) {
+                Some(function) => function,
+                None => return Err(self.error(&format!("unknown function {}", token.value))),
+            },
+            None => return Err(self.error("expected a function")),
+        };
+        self.position += 1;
+        self.expect_punctuation("(")?;
+
+        if function == AggregateFunction::Count {
+            if let Some(token) = self.peek() {
+                if token.is_operator("*") {
+                    self.position += 1;
+                    self.expect_punctuation(")")?;
+                    return Ok(Aggregate {
+                        function,
+                        distinct: false,
+                        argument: None,
+                    });
+                }
+            }
+        }
+
+        let distinct = self.consume_keyword("DISTINCT");
+        let argument = self.parse_column_name("a field to aggregate")?;
+        self.expect_punctuation(")")?;
+        Ok(Aggregate {
+            function,
+            distinct,
+            argument: Some(argument),
+        })
+    }
+
+    /// NOW(), DATE_ADD(value, INTERVAL amount unit), EXTRACT(unit FROM value) or DATE_TRUNC('unit', value)
+    fn parse_function(&mut self) -> Result<Function, MiniSQLError> {
+        let name = self.expect_identifier("a function")?.to_uppercase();
+        self.expect_punctuation("(")?;
+        let function = match name.as_str() {
+            "DATE_ADD" => {
+                let value = self.parse_concat()?;
+                self.expect_punctuation(",")?;
+                match self.peek() {
+                    Some(token)
+                        if token.kind == TokenKind::Identifier
+                            && token.value.eq_ignore_ascii_case("INTERVAL") =>
+                    {
+                        self.position += 1;
+                    }
+                    _ => return Err(self.error("expected INTERVAL")),
+                }
+                let amount = self.parse_additive()?;
+                let unit = self.parse_date_unit()?;
+                Function::DateAdd {
+                    value: Box::new(value),
+                    amount: Box::new(amount),
+                    unit,
+                }
+            }
+            "EXTRACT" => {
+                let unit = self.parse_date_unit()?;
+                self.expect_keyword("FROM")?;
+                Function::Extract {
+                    unit,
+                    value: Box::new(self.parse_concat()?),
+                }
+            }
+            "DATE_TRUNC" => {
+                let unit = match self.peek() {
+                    Some(token) if token.kind == TokenKind::StringLiteral => {
+                        DateUnit::from_name(&token.value)
+                    }
+                    _ => None,
+                };
+                let unit = match unit {
+                    Some(unit) => unit,
+                    None => return Err(self.error("expected a quoted unit to truncate to")),
+                };
+                self.position += 1;
+                self.expect_punctuation(",")?;
+                Function::DateTrunc {
+                    unit,
+                    value: Box::new(self.parse_concat()?),
+                }
+            }
+            "NOW" => Function::Now,
+            _ => return Err(self.error(&format!("unknown function {}", name))),
+        };
+        self.expect_punctuation(")")?;
+        Ok(function)
+    }
+
+    /// YEAR, MONTH, DAY, HOUR, MINUTE or SECOND
+    fn parse_date_unit(&mut self) -> Result<DateUnit, MiniSQLError> {
+        let unit = match self.peek() {
+            Some(token) if token.kind == TokenKind::Identifier => DateUnit::from_name(&token.value),
+            _ => None,
+        };
+        match unit {
+            Some(unit) => {
+                self.position += 1;
+                Ok(unit)
+            }
+            None => Err(self.error("expected a unit: YEAR, MONTH, DAY, HOUR, MINUTE or SECOND")),
+        }
+    }
+
+    /// field or table.field
+    fn parse_column_name(&mut self, description: &str) -> Result<String, MiniSQLError> {
+        let name = self.expect_identifier(description)?;
+        if self.consume_punctuation(".") {
+            let field = self.expect_identifier("a field name after '.'")?;
+            return Ok(format!("{}.{}", name, field));
+        }
+        Ok(name)
+    }
+
+    fn parse_table_reference(&mut self) -> Result<TableReference, MiniSQLError> {
+        let name = self.parse_table_name()?;
+        let alias = self.parse_alias("an alias for the table")?;
+        Ok(TableReference { name, alias })
+    }
+
+    /// [AS] alias, an identifier right after the aliased element is also an alias.
+    /// Without AS a non reserved keyword is not an alias: `FROM t LEFT JOIN` is a join.
+    fn parse_alias(&mut self, description: &str) -> Result<Option<String>, MiniSQLError> {
+        if self.consume_keyword("AS") {
+            return Ok(Some(self.expect_identifier(description)?));
+        }
+        match self.peek() {
+            Some(token) if token.kind == TokenKind::Identifier => {
+                Ok(Some(self.expect_identifier(description)?))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn parse_joins(&mut self) -> Result<Vec<Join>, MiniSQLError> {
+        let mut joins: Vec<Join> = vec![];
+        loop {
+            let kind = if self.consume_punctuation(",") {
+                joins.push(Join {
+                    kind: JoinKind::Cross,
+                    table: self.parse_table_reference()?,
+                    condition: None,
+                });
+                continue;
+            } else if self.consume_keyword("CROSS") {
+                self.expect_keyword("JOIN")?;
+                joins.push(Join {
+                    kind: JoinKind::Cross,
+                    table: self.parse_table_reference()?,
+                    condition: None,
+                });
+                continue;
+            } else if self.consume_keyword("LEFT") {
+                self.consume_keyword("OUTER");
+                self.expect_keyword("JOIN")?;
+                JoinKind::Left
+            } else if self.consume_keyword("INNER") {
+                self.expect_keyword("JOIN")?;
+                JoinKind::Inner
+            } else if self.consume_keyword("JOIN") {
+                JoinKind::Inner
+            } else {
+                break;
+            };
+
+            let table = self.parse_table_reference()?;
+            self.expect_keyword("ON")?;
+            let condition = Some(self.parse_expr()?);
+            joins.push(Join {
+                kind,
+                table,
+                condition,
+            });
+        }
+        Ok(joins)
+    }
+
+    fn parse_insert(&mut self) -> Result<InsertStatement, MiniSQLError> {
+        self.expect_keyword("INSERT")?;
+        self.expect_keyword("INTO")?;
+        let table = self.parse_table_name()?;
+
+        self.expect_punctuation("(")?;
+        let mut columns: Vec<String> = vec![];
+        loop {
+            columns.push(self.expect_identifier("a field name")?);
+            if !self.consume_punctuation(",") {
+                break;
+            }
+        }
+        self.expect_punctuation(")")?;
+
+        self.expect_keyword("VALUES")?;
+        let mut values: Vec<Vec<Literal>> = vec![];
+        loop {
+            let start = self.position;
+            self.expect_punctuation("(")?;
+            let mut register: Vec<Literal> = vec![];
+            loop {
+                register.push(self.parse_literal()?);
+                if !self.consume_punctuation(",") {
+                    break;
+                }
+            }
+            self.expect_punctuation(")")?;
+
+            if register.len() != columns.len() {
+                self.position = start;
+                return Err(self.error(&format!(
+                    "{} values were given but {} fields were requiered",
+                    register.len(),
+                    columns.len()
+                )));
+            }
+            values.push(register);
+
+            if !self.consume_punctuation(",") {
+                break;
+            }
+        }
+
+        Ok(InsertStatement {
+            table,
+            columns,
+            values,
+        })
+    }
+
+    fn parse_update(&mut self) -> Result<UpdateStatement, MiniSQLError> {
+        self.expect_keyword("UPDATE")?;
+        let table = self.parse_table_name()?;
+        self.expect_keyword("SET")?;
+
+        let mut assignments: Vec<(String, Expr)> = vec![];
+        loop {
+            let column = self.expect_identifier("a field name")?;
+            match self.peek() {
+                Some(token) if token.is_operator("=") => self.position += 1,
+                _ => return Err(self.error("expected '=' on assignment")),
+            }
+            assignments.push((column, self.parse_concat()?));
+            if !self.consume_punctuation(",") {
+                break;
+            }
+        }
+
+        let condition = self.parse_where()?;
+        Ok(UpdateStatement {
+            table,
+            assignments,
+            condition,
+        })
+    }
+
+    fn parse_delete(&mut self) -> Result<DeleteStatement, MiniSQLError> {
+        self.expect_keyword("DELETE")?;
+        self.expect_keyword("FROM")?;
+        let table = self.parse_table_name()?;
+        let condition = self.parse_where()?;
+        Ok(DeleteStatement { table, condition })
+    }
+
+    fn parse_where(&mut self) -> Result<Option<Expr>, MiniSQLError> {
+        if self.consume_keyword("WHERE") {
+            return Ok(Some(self.parse_expr()?));
+        }
+        Ok(None)
+    }
+
+    fn parse_literal(&mut self) -> Result<Literal, MiniSQLError> {
+        let literal = match self.peek() {
+            Some(token) => match token.kind {
+                TokenKind::StringLiteral => Literal::String(token.value.to_string()),
+                TokenKind::NumericLiteral => Literal::Number(token.value.to_string()),
+                TokenKind::Keyword if token.value == "TRUE" => Literal::Boolean(true),
+                TokenKind::Keyword if token.value == "FALSE" => Literal::Boolean(false),
+                TokenKind::Keyword if token.value == "NULL" => Literal::Null,
+                _ => return Err(self.error("expected a literal value")),
+            },
+            None => return Err(self.error("expected a literal value")),
+        };
+        self.position += 1;
+        Ok(literal)
+    }
+
+    fn parse_expr(&mut self) -> Result<Expr, MiniSQLError> {
+        self.parse_or()
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, MiniSQLError> {
+        let mut left = self.parse_and()?;
+        while self.consume_keyword("OR") {
+            let right = self.parse_and()?;
+            left = Expr::Binary {
+                left: Box::new(left),
+                operator: BinaryOperator::Or,
+                right: Box::new(right),
+            };
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, MiniSQLError> {
+        let mut left = self.parse_not()?;
+        while self.consume_keyword("AND") {
+            let right = self.parse_not()?;
+            left = Expr::Binary {
+                left: Box::new(left),
+                operator: BinaryOperator::And,
+                right: Box::new(right),
+            };
+        }
+        Ok(left)
+    }
+
+    fn parse_not(&mut self) -> Result<Expr, MiniSQLError> {
+        if self.consume_keyword("NOT") {
+            let expr = self.parse_not()?;
+            return Ok(Expr::Unary {
+                operator: UnaryOperator::Not,
+                expr: Box::new(expr),
+            });
+        }
+        self.parse_comparison()
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr, MiniSQLError> {
+        let left = self.parse_concat()?;
+
+        if self.consume_keyword("IS") {
+            let negated = self.consume_keyword("NOT");
+            self.expect_keyword("NULL")?;
+            return Ok(Expr::IsNull {
+                expr: Box::new(left),
+                negated,
+            });
+        }
+
+        // NOT despues de un valor solo puede negar el predicado que le sigue
+        let negated = self.check_keyword("NOT")
+            && matches!(self.tokens.get(self.position + 1), Some(token) if is_predicate(token));
+        if negated {
+            self.position += 1;
+        }
+        if self.check_keyword("LIKE") || self.check_keyword("ILIKE") {
+            return self.parse_like(left, negated);
+        }
+        if self.consume_keyword("IN") {
+            self.expect_punctuation("(")?;
+            let mut list: Vec<Expr> = vec![];
+            loop {
+                list.push(self.parse_concat()?);
+                if !self.consume_punctuation(",") {
+                    break;
+                }
+            }
+            self.expect_punctuation(")")?;
+            return Ok(Expr::InList {
+                expr: Box::new(left),
+                list,
+                negated,
+            });
+        }
+        if self.consume_keyword("BETWEEN") {
+            // los extremos no pueden tener AND, asi el AND que sigue es siempre el de BETWEEN
+            let low = self.parse_concat()?;
+            self.expect_keyword("AND")?;
+            let high = self.parse_concat()?;
+            return Ok(Expr::Between {
+                expr: Box::new(left),
+                low: Box::new(low),
+                high: Box::new(high),
+                negated,
+            });
+        }
+
+        let operator = match self.peek() {
+            Some(token) if token.kind == TokenKind::Operator => match token.value.as_str() {
+                "=" => BinaryOperator::Equal,
+                "!=" | "<>" => BinaryOperator::NotEqual,
+                ">" => BinaryOperator::Greater,
+                ">=" => BinaryOperator::GreaterEqual,
+                "<" => BinaryOperator::Less,
+                "<=" => BinaryOperator::LessEqual,
+                _ => return Err(self.error("invalid operand for comparison")),
+            },
+            _ => return Ok(left),
+        };
+        self.position += 1;
+
+        let right = self.parse_concat()?;
+        Ok(Expr::Binary {
+            left: Box::new(left),
+            operator,
+            right: Box::new(right),
+        })
+    }
+
+    /// {LIKE | ILIKE} pattern [ESCAPE 'c'], after the value it matches
+    fn parse_like(&mut self, expr: Expr, negated: bool) -> Result<Expr, MiniSQLError> {
+        let case_insensitive = self.consume_keyword("ILIKE");
+        if !case_insensitive {
+            self.expect_keyword("LIKE")?;
+        }
+        let pattern = self.parse_concat()?;
+        let escape = if self.consume_keyword("ESCAPE") {
+            match self.peek() {
+                Some(token)
+                    if token.kind == TokenKind::StringLiteral
+                        && token.value.chars().count() == 1 =>
+                {
+                    let escape = token.value.to_string();
+                    self.position += 1;
+                    Some(escape)
+                }
+                _ => return Err(self.error("expected a single character to ESCAPE")),
+            }
+        } else {
+            None
+        };
+        Ok(Expr::Like {
+            expr: Box::new(expr),
+            pattern: Box::new(pattern),
+            escape,
+            negated,
+            case_insensitive,
+        })
+    }
+
+    /// a || b, below the arithmetic operators as on the SQL standard
+    fn parse_concat(&mut self) -> Result<Expr, MiniSQLError> {
+        let mut left = self.parse_additive()?;
+        while self.consume_operator("||") {
+            let right = self.parse_additive()?;
+            left = binary(left, BinaryOperator::Concat, right);
+        }
+        Ok(left)
+    }
+
+    fn parse_additive(&mut self) -> Result<Expr, MiniSQLError> {
+        let mut left = self.parse_multiplicative()?;
+        loop {
+            let operator = if self.consume_operator("+") {
+                BinaryOperator::Add
+            } else if self.consume_operator("-") {
+                BinaryOperator::Subtract
+            } else {
+                return Ok(left);
+            };
+            let right = self.parse_multiplicative()?;
+            left = binary(left, operator, right);
+        }
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<Expr, MiniSQLError> {
+        let mut left = self.parse_unary()?;
+        loop {
+            let operator = if self.consume_operator("*") {
+                BinaryOperator::Multiply
+            } else if self.consume_operator("/") {
+                BinaryOperator::Divide
+            } else if self.consume_operator("%") {
+                BinaryOperator::Modulo
+            } else {
+                return Ok(left);
+            };
+            let right = self.parse_unary()?;
+            left = binary(left, operator, right);
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, MiniSQLError> {
+        if self.consume_operator("-") {
+            let expr = self.parse_unary()?;
+            return Ok(Expr::Unary {
+                operator: UnaryOperator::Minus,
+                expr: Box::new(expr),
+            });
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, MiniSQLError> {
+        if self.consume_punctuation("(") {
+            let expr = self.parse_expr()?;
+            self.expect_punctuation(")")?;
+            return Ok(expr);
+        }
+
+        match self.peek() {
+            Some(token)
+                if token.kind == TokenKind::Identifier
+                    && self.next_is_call()
+                    && Function::is_function(&token.value) =>
+            {
+                Ok(Expr::Function(self.parse_function()?))
+            }
+            Some(token) if token.kind == TokenKind::Identifier && self.next_is_call() => {
+                Ok(Expr::Aggregate(self.parse_aggregate()?))
+            }
+            // las funciones sin parentesis del estandar
+            Some(token)
+                if token.kind == TokenKind::Identifier
+                    && ["CURRENT_DATE", "CURRENT_TIMESTAMP"]
+                        .contains(&token.value.to_uppercase().as_str()) =>
+            {
+                let function = if token.value.eq_ignore_ascii_case("CURRENT_DATE") {
+                    Function::CurrentDate
+                } else {
+                    Function::Now
+                };
+                self.position += 1;
+                Ok(Expr::Function(function))
+            }
+            Some(token) if token.is_name() => {
+                Ok(Expr::Column(self.parse_column_name("a field name")?))
+            }
+            _ => Ok(Expr::Literal(self.parse_literal()?)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_grammar {
+    use super::*;
+
+    fn column(name: &str) -> Box<Expr> {
+        Box::new(Expr::Column(name.to_string()))
+    }
+
+    fn number(value: &str) -> Box<Expr> {
+        Box::new(Expr::Literal(Literal::Number(value.to_string())))
+    }
+
+    #[test]
+    fn test_parse_select() {
+        let statement = parse("SELECT nombre, * FROM clientes WHERE id = 5 ORDER BY nombre DESC;");
+        assert_eq!(
+            statement,
+            Ok(Statement::Select(SelectStatement {
+                distinct: None,
+                projection: vec![
+                    SelectItem::Column {
+                        name: "nombre".to_string(),
+                        alias: None
+                    },
+                    SelectItem::Wildcard
+                ],
+                from: TableReference {
+                    name: "clientes".to_string(),
+                    alias: None
+                },
+                joins: vec![],
+                condition: Some(Expr::Binary {
+                    left: column("id"),
+                    operator: BinaryOperator::Equal,
+                    right: number("5"),
+                }),
+                group_by: vec![],
+                having: None,
+                order_by: vec![OrderBy {
+                    expr: Expr::Column("nombre".to_string()),
+                    ascending: false,
+                    nulls_first: None
+                }],
+                limit: None,
+                offset: 0,
+            }))
+        );
+    }
+
+    #[test]
+    fn test_parse_precedence() {
+        let statement = parse("DELETE FROM t WHERE ( a = 1 ) OR b = 2 AND NOT c = 3");
+        let expected = Expr::Binary {
+            left: Box::new(Expr::Binary {
+                left: column("a"),
+                operator: BinaryOperator::Equal,
+                right: number("1"),
+            }),
+            operator: BinaryOperator::Or,
+            right: Box::new(Expr::Binary {
+                left: Box::new(Expr::Binary {
+                    left: column("b"),
+                    operator: BinaryOperator::Equal,
+                    right: number("2"),
+                }),
+                operator: BinaryOperator::And,
+                right: Box::new(Expr::Unary {
+                    operator: UnaryOperator::Not,
+                    expr: Box::new(Expr::Binary {
+                        left: column("c"),
+                        operator: BinaryOperator::Equal,
+                        right: number("3"),
+                    }),
+                }),
+            }),
+        };
+        assert_eq!(
+            statement,
+            Ok(Statement::Delete(DeleteStatement {
+                table: "t".to_string(),
+                condition: Some(expected),
+            }))
+        );
+    }
+
+    #[test]
+    fn test_parse_insert() {
+        let statement = parse("INSERT INTO t (a, b) VALUES (1, 'x'), (-2, 'a, b')");
+        assert_eq!(
+            statement,
+            Ok(Statement::Insert(InsertStatement {
+                table: "t".to_string(),
+                columns: vec!["a".to_string(), "b".to_string()],
+                values: vec![
+                    vec![
+                        Literal::Number("1".to_string()),
+                        Literal::String("x".to_string())
+                    ],
+                    vec![
+                        Literal::Number("-2".to_string()),
+                        Literal::String("a, b".to_string())
+                    ],
+                ],
+            }))
+        );
+    }
+
+    #[test]
+    fn test_parse_update() {
+        let statement = parse("UPDATE t SET a = 'OR', b = 2, c = c - 1");
+        assert_eq!(
+            statement,
+            Ok(Statement::Update(UpdateStatement {
+                table: "t".to_string(),
+                assignments: vec![
+                    (
+                        "a".to_string(),
+                        Expr::Literal(Literal::String("OR".to_string()))
+                    ),
+                    ("b".to_string(), *number("2")),
+                    (
+                        "c".to_string(),
+                        Expr::Binary {
+                            left: column("c"),
+                            operator: BinaryOperator::Subtract,
+                            right: number("1"),
+                        }
+                    ),
+                ],
+                condition: None,
+            }))
+        );
+    }
+
+    #[test]
+    fn test_parse_error_reports_position() {
+        let statement = parse("SELECT * FROM t\nWHERE ( a = 1");
+        assert_eq!(
+            statement,
+            Err(MiniSQLError::InvalidSyntax(
+                "expected ')' at line 2, column 14 ".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_missing_table() {
+        let statement = parse("UPDATE SET a = 1");
+        assert_eq!(
+            statement,
+            Err(MiniSQLError::InvalidTable(
+                "no table was given ".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_table_name_with_path_separators() {
+        assert_eq!(
+            parse("DROP TABLE \"../x\""),
+            Err(MiniSQLError::InvalidTable(
+                "table name [ ../x ] can not contain path separators ".to_string()
+            ))
+        );
+        assert!(parse("SELECT * FROM \"a\\b\"").is_err());
+        assert!(parse("ALTER TABLE t RENAME TO \"/tmp/t\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_values_count_mismatch() {
+        let statement = parse("INSERT INTO t (a, b) VALUES (1, 2), (3)");
+        assert_eq!(
+            statement,
+            Err(MiniSQLError::InvalidSyntax(
+                "1 values were given but 2 fields were requiered at line 1, column 37 ".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_column_definition() {
+        assert_eq!(
+            parse_column_definition("fecha date"),
+            Ok(ColumnDefinition {
+                name: "fecha".to_string(),
+                column_type: ColumnType::Date,
+                constraints: vec![]
+            })
+        );
+        assert_eq!(
+            parse_column_definition("fecha VARCHAR"),
+            Err(MiniSQLError::InvalidSyntax(
+                "expected a type: INTEGER, BIGINT, REAL, DECIMAL, TEXT, BOOLEAN, DATE, TIME or TIMESTAMP at line 1, column 7 "
+                    .to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_create_table() {
+        let statement = parse(
+            "CREATE TABLE IF NOT EXISTS cuentas (id INTEGER PRIMARY KEY, titular TEXT NOT NULL, saldo REAL DEFAULT -1.5)",
+        );
+        assert_eq!(
+            statement,
+            Ok(Statement::CreateTable(CreateTableStatement {
+                table: "cuentas".to_string(),
+                if_not_exists: true,
+                columns: vec![
+                    ColumnDefinition {
+                        name: "id".to_string(),
+                        column_type: ColumnType::Integer,
+                        constraints: vec![ColumnConstraint::PrimaryKey]
+                    },
+                    ColumnDefinition {
+                        name: "titular".to_string(),
+                        column_type: ColumnType::Text,
+                        constraints: vec![ColumnConstraint::NotNull]
+                    },
+                    ColumnDefinition {
+                        name: "saldo".to_string(),
+                        column_type: ColumnType::Real,
+                        constraints: vec![ColumnConstraint::Default(Literal::Number(
+                            "-1.5".to_string()
+                        ))]
+                    },
+                ]
+            }))
+        );
+    }
+
+    #[test]
+    fn test_column_definition_round_trip() {
+        let text = "nombre TEXT NOT NULL UNIQUE DEFAULT 'o''neil'";
+        let column = parse_column_definition(text).unwrap();
+        assert_eq!(column.to_string(), text);
+    }
+
+    #[test]
+    fn test_parse_drop_and_truncate() {
+        assert_eq!(
+            parse("DROP TABLE IF EXISTS cuentas;"),
+            Ok(Statement::DropTable(DropTableStatement {
+                table: "cuentas".to_string(),
+                if_exists: true
+            }))
+        );
+        assert_eq!(
+            parse("TRUNCATE TABLE cuentas"),
+            Ok(Statement::Truncate(TruncateStatement {
+                table: "cuentas".to_string()
+            }))
+        );
+        assert_eq!(
+            parse("DROP TABLE"),
+            Err(MiniSQLError::InvalidTable(
+                "no table was given ".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_alter_table() {
+        let alter = |sentence: &str| match parse(sentence) {
+            Ok(Statement::AlterTable(alter)) => alter.operation,
+            other => panic!("unexpected statement: {:?}", other),
+        };
+        assert_eq!(
+            alter("ALTER TABLE t ADD COLUMN pais TEXT DEFAULT 'AR'"),
+            AlterOperation::AddColumn {
+                name: "pais".to_string(),
+                column_type: Some(ColumnType::Text),
+                default: Some(Literal::String("AR".to_string()))
+            }
+        );
+        assert_eq!(
+            alter("ALTER TABLE t ADD pais"),
+            AlterOperation::AddColumn {
+                name: "pais".to_string(),
+                column_type: None,
+                default: None
+            }
+        );
+        assert_eq!(
+            alter("ALTER TABLE t DROP COLUMN pais"),
+            AlterOperation::DropColumn("pais".to_string())
+        );
+        assert_eq!(
+            alter("ALTER TABLE t RENAME COLUMN pais TO nacion"),
+            AlterOperation::RenameColumn {
+                from: "pais".to_string(),
+                to: "nacion".to_string()
+            }
+        );
+        assert_eq!(
+            alter("ALTER TABLE t RENAME TO u"),
+            AlterOperation::RenameTable("u".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_joins() {
+        let statement = match parse(
+            "SELECT c.nombre, o.producto FROM clientes AS c JOIN ordenes o ON c.id_cliente = o.id_cliente LEFT JOIN x ON x.a = 1 CROSS JOIN y, z",
+        ) {
+            Ok(Statement::Select(select)) => select,
+            other => panic!("unexpected statement: {:?}", other),
+        };
+        assert_eq!(
+            statement.projection,
+            vec![
+                SelectItem::Column {
+                    name: "c.nombre".to_string(),
+                    alias: None
+                },
+                SelectItem::Column {
+                    name: "o.producto".to_string(),
+                    alias: None
+                }
+            ]
+        );
+        assert_eq!(statement.from.qualifier(), "c");
+        let kinds: Vec<(JoinKind, &str)> = statement
+            .joins
+            .iter()
+            .map(|join| (join.kind, join.table.qualifier()))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (JoinKind::Inner, "o"),
+                (JoinKind::Left, "x"),
+                (JoinKind::Cross, "y"),
+                (JoinKind::Cross, "z")
+            ]
+        );
+        assert_eq!(
+            statement.joins[0].condition,
+            Some(Expr::Binary {
+                left: column("c.id_cliente"),
+                operator: BinaryOperator::Equal,
+                right: column("o.id_cliente")
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_arithmetic_expressions() {
+        let statement = match parse(
+            "SELECT precio * cantidad AS total, nombre || ' ' || apellido, -x FROM t WHERE a + 1 > b",
+        ) {
+            Ok(Statement::Select(select)) => select,
+            other => panic!("unexpected statement: {:?}", other),
+        };
+        let rendered: Vec<(String, Option<&str>)> = statement
+            .projection
+            .iter()
+            .map(|item| match item {
+                SelectItem::Expr { expr, alias } => (expr.to_string(), alias.as_deref()),
+                other => panic!("unexpected item: {:?}", other),
+            })
+            .collect();
+        assert_eq!(
+            rendered,
+            vec![
+                ("( precio * cantidad )".to_string(), Some("total")),
+                ("( ( nombre || ' ' ) || apellido )".to_string(), None),
+                ("-x".to_string(), None)
+            ]
+        );
+        assert_eq!(
+            statement.condition.map(|condition| condition.to_string()),
+            Some("( ( a + 1 ) > b )".to_string())
+        );
+
+        let rendered = |sentence: &str| match parse(sentence) {
+            Ok(Statement::Select(select)) => match &select.projection[0] {
+                SelectItem::Expr { expr, .. } => expr.to_string(),
+                other => panic!("unexpected item: {:?}", other),
+            },
+            other => panic!("unexpected statement: {:?}", other),
+        };
+        assert_eq!(
+            rendered("SELECT a + b * -c % 2 - d FROM t"),
+            "( ( a + ( ( b * -c ) % 2 ) ) - d )"
+        );
+        assert_eq!(
+            rendered("SELECT (a + b) * c || d FROM t"),
+            "( ( ( a + b ) * c ) || d )"
+        );
+    }
+
+    #[test]
+    fn test_parse_like() {
+        let condition = |sentence: &str| match parse(sentence) {
+            Ok(Statement::Delete(delete)) => delete.condition.map(|c| c.to_string()),
+            other => panic!("unexpected statement: {:?}", other),
+        };
+        assert_eq!(
+            condition("DELETE FROM t WHERE a LIKE '%x' AND b NOT ILIKE 'y!_%' ESCAPE '!'"),
+            Some("( ( a LIKE '%x' ) AND ( b NOT ILIKE 'y!_%' ESCAPE '!' ) )".to_string())
+        );
+        assert_eq!(
+            condition("DELETE FROM t WHERE NOT a || b LIKE 'x%'"),
+            Some("NOT ( ( a || b ) LIKE 'x%' )".to_string())
+        );
+        assert_eq!(
+            parse("DELETE FROM t WHERE a LIKE 'x' ESCAPE '!!'"),
+            Err(MiniSQLError::InvalidSyntax(
+                "expected a single character to ESCAPE at line 1, column 39 ".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_in_and_between() {
+        let condition = |sentence: &str| match parse(sentence) {
+            Ok(Statement::Delete(delete)) => delete.condition.map(|c| c.to_string()),
+            other => panic!("unexpected statement: {:?}", other),
+        };
+        assert_eq!(
+            condition("DELETE FROM t WHERE a IN (1, 'x') AND b NOT IN (c + 1)"),
+            Some("( ( a IN (1, 'x') ) AND ( b NOT IN (( c + 1 )) ) )".to_string())
+        );
+        assert_eq!(
+            condition("DELETE FROM t WHERE a BETWEEN 1 AND b + 2 AND c NOT BETWEEN 'a' AND 'b' OR d = 1"),
+            Some(
+                "( ( ( a BETWEEN 1 AND ( b + 2 ) ) AND ( c NOT BETWEEN 'a' AND 'b' ) ) OR ( d = 1 ) )"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            parse("DELETE FROM t WHERE a BETWEEN 1 OR 2"),
+            Err(MiniSQLError::InvalidSyntax(
+                "expected AND at line 1, column 33 ".to_string()
+            ))
+        );
+        assert_eq!(
+            parse("DELETE FROM t WHERE a IN ()"),
+            Err(MiniSQLError::InvalidSyntax(
+                "expected a literal value at line 1, column 27 ".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_null() {
+        let condition = |sentence: &str| match parse(sentence) {
+            Ok(Statement::Delete(delete)) => delete.condition.map(|c| c.to_string()),
+            other => panic!("unexpected statement: {:?}", other),
+        };
+        assert_eq!(
+            condition("DELETE FROM t WHERE a IS NULL OR b + 1 IS NOT NULL AND c = NULL"),
+            Some("( ( a IS NULL ) OR ( ( ( b + 1 ) IS NOT NULL ) AND ( c = NULL ) ) )".to_string())
+        );
+        assert_eq!(
+            parse("INSERT INTO t (a, b) VALUES (NULL, 1)"),
+            Ok(Statement::Insert(InsertStatement {
+                table: "t".to_string(),
+                columns: vec!["a".to_string(), "b".to_string()],
+                values: vec![vec![Literal::Null, Literal::Number("1".to_string())]],
+            }))
+        );
+        assert_eq!(
+            parse("DELETE FROM t WHERE a IS 1"),
+            Err(MiniSQLError::InvalidSyntax(
+                "expected NULL at line 1, column 26 ".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_date_functions() {
+        let condition = |sentence: &str| match parse(sentence) {
+            Ok(Statement::Delete(delete)) => delete.condition.map(|c| c.to_string()),
+            other => panic!("unexpected statement: {:?}", other),
+        };
+        assert_eq!(
+            condition("DELETE FROM t WHERE date_add(alta, interval -1 month) < current_date"),
+            Some("( DATE_ADD(alta, INTERVAL -1 MONTH) < CURRENT_DATE )".to_string())
+        );
+        assert_eq!(
+            condition("DELETE FROM t WHERE EXTRACT(YEAR FROM alta) = 2024 AND alta < NOW()"),
+            Some("( ( EXTRACT(YEAR FROM alta) = 2024 ) AND ( alta < NOW() ) )".to_string())
+        );
+        assert_eq!(
+            parse("UPDATE t SET alta = DATE_TRUNC('Month', CURRENT_TIMESTAMP)"),
+            Ok(Statement::Update(UpdateStatement {
+                table: "t".to_string(),
+                assignments: vec![(
+                    "alta".to_string(),
+                    Expr::Function(Function::DateTrunc {
+                        unit: DateUnit::Month,
+                        value: Box::new(Expr::Function(Function::Now)),
+                    })
+                )],
+                condition: None,
+            }))
+        );
+        assert_eq!(
+            parse("DELETE FROM t WHERE DATE_ADD(alta, 1 DAY) > alta"),
+            Err(MiniSQLError::InvalidSyntax(
+                "expected INTERVAL at line 1, column 36 ".to_string()
+            ))
+        );
+        assert_eq!(
+            parse("DELETE FROM t WHERE EXTRACT(WEEK FROM alta) = 1"),
+            Err(MiniSQLError::InvalidSyntax(
+                "expected a unit: YEAR, MONTH, DAY, HOUR, MINUTE or SECOND at line 1, column 29 "
+                    .to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_aggregates() {
+        let statement = match parse(
+            "SELECT COUNT(*), count(DISTINCT o.producto), max(cantidad) FROM ordenes o",
+        ) {
+            Ok(Statement::Select(select)) => select,
+            other => panic!("unexpected statement: {:?}", other),
+        };
+        let rendered: Vec<String> = statement
+            .projection
+            .iter()
+            .map(|item| match item {
+                SelectItem::Aggregate { aggregate, .. } => aggregate.to_string(),
+                other => panic!("unexpected item: {:?}", other),
+            })
+            .collect();
+        assert_eq!(
+            rendered,
+            vec!["COUNT(*)", "COUNT(DISTINCT o.producto)", "MAX(cantidad)"]
+        );
+        assert_eq!(
+            parse("SELECT SUM(*) FROM ordenes"),
+            Err(MiniSQLError::InvalidSyntax(
+                "expected a field to aggregate at line 1, column 12 ".to_string()
+            ))
+        );
+        assert_eq!(
+            parse("SELECT TOTAL(cantidad) FROM ordenes"),
+            Err(MiniSQLError::InvalidSyntax(
+                "unknown function TOTAL at line 1, column 8 ".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_group_by_and_having() {
+        let statement = match parse(
+            "SELECT id_cliente, SUM(cantidad) FROM ordenes GROUP BY id_cliente, o.producto HAVING SUM(cantidad) > 3",
+        ) {
+            Ok(Statement::Select(select)) => select,
+            other => panic!("unexpected statement: {:?}", other),
+        };
+        assert_eq!(statement.group_by, vec!["id_cliente", "o.producto"]);
+        assert_eq!(
+            statement.having,
+            Some(Expr::Binary {
+                left: Box::new(Expr::Aggregate(Aggregate {
+                    function: AggregateFunction::Sum,
+                    distinct: false,
+                    argument: Some("cantidad".to_string()),
+                })),
+                operator: BinaryOperator::Greater,
+                right: number("3"),
+            })
+        );
+        assert_eq!(
+            parse("SELECT id_cliente FROM ordenes GROUP id_cliente"),
+            Err(MiniSQLError::InvalidSyntax(
+                "expected BY at line 1, column 38 ".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_limit_and_offset() {
+        let limits = |sentence: &str| match parse(sentence) {
+            Ok(Statement::Select(select)) => Ok((select.limit, select.offset)),
+            Ok(other) => panic!("unexpected statement: {:?}", other),
+            Err(err) => Err(err),
+        };
+        assert_eq!(limits("SELECT * FROM ordenes"), Ok((None, 0)));
+        assert_eq!(limits("SELECT * FROM ordenes LIMIT 3"), Ok((Some(3), 0)));
+        assert_eq!(
+            limits("SELECT * FROM ordenes ORDER BY id LIMIT 3 OFFSET 2"),
+            Ok((Some(3), 2))
+        );
+        assert_eq!(
+            limits("SELECT * FROM ordenes FETCH FIRST 1 ROW ONLY"),
+            Ok((Some(1), 0))
+        );
+        assert_eq!(
+            limits("SELECT * FROM ordenes OFFSET 4 ROWS FETCH NEXT 2 ROWS ONLY"),
+            Ok((Some(2), 4))
+        );
+        assert_eq!(limits("SELECT * FROM ordenes OFFSET 4"), Ok((None, 4)));
+        assert_eq!(
+            limits("SELECT * FROM ordenes LIMIT -1"),
+            Err(MiniSQLError::InvalidSyntax(
+                "expected a number of registers at line 1, column 29 ".to_string()
+            ))
+        );
+        assert_eq!(
+            limits("SELECT * FROM ordenes FETCH FIRST 2 ROWS"),
+            Err(MiniSQLError::InvalidSyntax(
+                "expected ONLY at line 1, column 41 ".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_order_keys_and_aliases() {
+        let statement = match parse(
+            "SELECT apellido AS a, COUNT(*) total FROM clientes ORDER BY a, 2 DESC NULLS LAST, nombre NULLS FIRST",
+        ) {
+            Ok(Statement::Select(select)) => select,
+            other => panic!("unexpected statement: {:?}", other),
+        };
+        let aliases: Vec<Option<&str>> = statement
+            .projection
+            .iter()
+            .map(|item| item.alias())
+            .collect();
+        assert_eq!(aliases, vec![Some("a"), Some("total")]);
+        assert_eq!(
+            statement.order_by,
+            vec![
+                OrderBy {
+                    expr: Expr::Column("a".to_string()),
+                    ascending: true,
+                    nulls_first: None
+                },
+                OrderBy {
+                    expr: *number("2"),
+                    ascending: false,
+                    nulls_first: Some(false)
+                },
+                OrderBy {
+                    expr: Expr::Column("nombre".to_string()),
+                    ascending: true,
+                    nulls_first: Some(true)
+                }
+            ]
+        );
+        assert_eq!(
+            parse("SELECT * FROM clientes ORDER BY nombre NULLS"),
+            Err(MiniSQLError::InvalidSyntax(
+                "expected LAST at line 1, column 45 ".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_distinct() {
+        let distinct = |sentence: &str| match parse(sentence) {
+            Ok(Statement::Select(select)) => select.distinct,
+            other => panic!("unexpected statement: {:?}", other),
+        };
+        assert_eq!(distinct("SELECT nombre FROM clientes"), None);
+        assert_eq!(
+            distinct("SELECT DISTINCT nombre, apellido FROM clientes"),
+            Some(Distinct::All)
+        );
+        assert_eq!(
+            distinct("SELECT DISTINCT ON (apellido, 2) nombre FROM clientes ORDER BY apellido"),
+            Some(Distinct::On(vec![
+                Expr::Column("apellido".to_string()),
+                *number("2")
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_parse_non_reserved_keywords_as_names() {
+        let statement = match parse(
+            "SELECT First, last AS key FROM t LEFT JOIN row ON t.id = row.id WHERE Rows > 1 ORDER BY first NULLS LAST",
+        ) {
+            Ok(Statement::Select(select)) => select,
+            other => panic!("unexpected statement: {:?}", other),
+        };
+        assert_eq!(
+            statement.projection,
+            vec![
+                SelectItem::Column {
+                    name: "First".to_string(),
+                    alias: None
+                },
+                SelectItem::Column {
+                    name: "last".to_string(),
+                    alias: Some("key".to_string())
+                }
+            ]
+        );
+        assert_eq!(statement.joins[0].kind, JoinKind::Left);
+        assert_eq!(statement.joins[0].table.name, "row");
+        assert_eq!(
+            statement.condition,
+            Some(binary(
+                Expr::Column("Rows".to_string()),
+                BinaryOperator::Greater,
+                *number("1")
+            ))
+        );
+        assert_eq!(
+            statement.order_by,
+            vec![OrderBy {
+                expr: Expr::Column("first".to_string()),
+                ascending: true,
+                nulls_first: Some(false)
+            }]
+        );
+
+        assert_eq!(
+            parse("INSERT INTO t (to, only) VALUES (1, 2)").map(|statement| match statement {
+                Statement::Insert(insert) => insert.columns,
+                _ => vec![],
+            }),
+            Ok(vec!["to".to_string(), "only".to_string()])
+        );
+        // las palabras que arman la sentencia siguen reservadas
+        assert!(parse("SELECT from FROM t").is_err());
+    }
+}
@@ -0,0 +1,357 @@
+use crate::errors::apperrors::MiniSQLError;
+
+/// Words that are always read as part of the SQL syntax, never as a column or table name.
+/// A quoted identifier ( "ORDER" ) can still be used to reference a column named like one of them.
+const KEYWORDS: [&str; 60] = [
+    "SELECT", "FROM", "WHERE", "ORDER", "BY", "ASC", "DESC", "INSERT", "INTO", "VALUES", "UPDATE",
+    "SET", "DELETE", "AND", "OR", "NOT", "TRUE", "FALSE", "CREATE", "TABLE", "IF", "EXISTS",
+    "PRIMARY", "KEY", "UNIQUE", "DEFAULT", "NULL", "DROP", "TRUNCATE", "ALTER", "ADD", "COLUMN",
+    "RENAME", "TO", "JOIN", "INNER", "LEFT", "OUTER", "CROSS", "ON", "AS", "DISTINCT", "GROUP",
+    "HAVING", "LIMIT", "OFFSET", "FETCH", "FIRST", "NEXT", "ROW", "ROWS", "ONLY", "NULLS", "LAST",
+    "LIKE", "ILIKE", "ESCAPE", "IN", "BETWEEN", "IS",
+];
+
+/// Keywords that only mean something on a few places of a sentence. Anywhere else a field
+/// or table name is expected they are read as that name, so a column can be called `first`.
+const NON_RESERVED: [&str; 20] = [
+    "KEY", "ADD", "COLUMN", "RENAME", "TO", "LEFT", "INNER", "OUTER", "CROSS", "OFFSET", "FETCH",
+    "FIRST", "NEXT", "ROW", "ROWS", "ONLY", "NULLS", "LAST", "PRIMARY", "UNIQUE",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenKind {
+    Keyword,
+    Identifier,
+    StringLiteral,
+    NumericLiteral,
+    Operator,
+    Punctuation,
+}
+
+/// A single lexical unit of a sentence
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    /// keywords are stored in uppercase, string literals without their quotes
+    pub value: String,
+    /// byte offset where the token starts inside the original sentence
+    pub offset: usize,
+}
+
+impl Token {
+    pub fn is_keyword(&self, keyword: &str) -> bool {
+        self.kind == TokenKind::Keyword && self.value == keyword
+    }
+
+    /// An identifier, or a non reserved keyword that can be used as one
+    pub fn is_name(&self) -> bool {
+        match self.kind {
+            TokenKind::Identifier => true,
+            TokenKind::Keyword => NON_RESERVED.contains(&self.value.as_str()),
+            _ => false,
+        }
+    }
+
+    pub fn is_punctuation(&self, symbol: &str) -> bool {
+        self.kind == TokenKind::Punctuation && self.value == symbol
+    }
+
+    pub fn is_operator(&self, symbol: &str) -> bool {
+        self.kind == TokenKind::Operator && self.value == symbol
+    }
+}
+
+/// Splits a SQL sentence into typed tokens.
+///
+/// String literals keep their content untouched, so a value like `'a, b'` or `'OR'`
+/// is a single `StringLiteral` token and is never read as syntax.
+///
+/// # Examples
+///
+/// ```
+/// tokenize("SELECT * FROM clientes WHERE nombre = 'OR'");
+/// ```
+///
+/// # Errors
+///
+/// Returns `MiniSQLError::InvalidSyntax` on unterminated literals or unknown characters.
+pub fn tokenize(sentence: &str) -> Result<Vec<Token>, MiniSQLError> {
+    let mut tokens: Vec<Token> = Vec::new();
+    let chars: Vec<(usize, char)> = sentence.char_indices().collect();
+    let mut index = 0;
+
+    while let Some(&(offset, c)) = chars.get(index) {
+        if c.is_whitespace() {
+            index += 1;
+            continue;
+        }
+
+        let (token, next) = if c == '\'' {
+            read_quoted(&chars, index, sentence, TokenKind::StringLiteral)?
+        } else if c == '"' {
+            read_quoted(&chars, index, sentence, TokenKind::Identifier)?
+        } else if c.is_ascii_digit() || (c == '.' && next_is_digit(&chars, index)) {
+            read_number(&chars, index, sentence)
+        } else if c == '-' && next_is_digit(&chars, index) && !follows_value(&tokens) {
+            // un signo negativo delante de un numero se toma como parte del literal
+            let (mut token, next) = read_number(&chars, index + 1, sentence);
+            token.value.insert(0, '-');
+            token.offset = offset;
+            (token, next)
+        } else if c.is_alphabetic() || c == '_' {
+            read_word(&chars, index, sentence)
+        } else {
+            read_symbol(&chars, index, sentence)?
+        };
+
+        tokens.push(token);
+        index = next;
+    }
+
+    Ok(tokens)
+}
+
+/// Builds the syntax error for a problem found at `offset`, pointing to its line and column
+pub fn syntax_error(sentence: &str, offset: usize, message: &str) -> MiniSQLError {
+    let before = &sentence[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = match before.rfind('\n') {
+        Some(index) => index + 1,
+        None => 0,
+    };
+    let column = before[line_start..].chars().count() + 1;
+    MiniSQLError::InvalidSyntax(format!("{} at line {}, column {} ", message, line, column))
+}
+
+fn next_is_digit(chars: &[(usize, char)], index: usize) -> bool {
+    matches!(chars.get(index + 1), Some((_, c)) if c.is_ascii_digit())
+}
+
+/// true when the previous token can be the left side of a subtraction
+fn follows_value(tokens: &[Token]) -> bool {
+    match tokens.last() {
+        Some(token) => match token.kind {
+            TokenKind::Identifier | TokenKind::StringLiteral | TokenKind::NumericLiteral => true,
+            TokenKind::Punctuation => token.value == ")",
+            _ => false,
+        },
+        None => false,
+    }
+}
+
+fn end_offset(chars: &[(usize, char)], index: usize, sentence: &str) -> usize {
+    match chars.get(index) {
+        Some((offset, _)) => *offset,
+        None => sentence.len(),
+    }
+}
+
+fn read_quoted(
+    chars: &[(usize, char)],
+    start: usize,
+    sentence: &str,
+    kind: TokenKind,
+) -> Result<(Token, usize), MiniSQLError> {
+    let (offset, quote) = chars[start];
+    let mut value = String::new();
+    let mut index = start + 1;
+
+    while let Some(&(_, c)) = chars.get(index) {
+        if c == quote {
+            // dos comillas seguidas representan una comilla dentro del literal
+            if matches!(chars.get(index + 1), Some((_, next)) if *next == quote) {
+                value.push(quote);
+                index += 2;
+                continue;
+            }
+            return Ok((
+                Token {
+                    kind,
+                    value,
+                    offset,
+                },
+                index + 1,
+            ));
+        }
+        value.push(c);
+        index += 1;
+    }
+
+    Err(syntax_error(sentence, offset, "unterminated quoted value"))
+}
+
+fn read_number(chars: &[(usize, char)], start: usize, sentence: &str) -> (Token, usize) {
+    let offset = chars[start].0;
+    let mut index = start;
+    let mut seen_dot = false;
+
+    while let Some(&(_, c)) = chars.get(index) {
+        if c.is_ascii_digit() {
+            index += 1;
+        } else if c == '.' && !seen_dot {
+            seen_dot = true;
+            index += 1;
+        } else {
+            break;
+        }
+    }
+
+    let end = end_offset(chars, index, sentence);
+    (
+        Token {
+            kind: TokenKind::NumericLiteral,
+            value: sentence[offset..end].to_string(),
+            offset,
+        },
+        index,
+    )
+}
+
+fn read_word(chars: &[(usize, char)], start: usize, sentence: &str) -> (Token, usize) {
+    let offset = chars[start].0;
+    let mut index = start;
+
+    while let Some(&(_, c)) = chars.get(index) {
+        if c.is_alphanumeric() || c == '_' {
+            index += 1;
+        } else {
+            break;
+        }
+    }
+
+    let end = end_offset(chars, index, sentence);
+    let word = &sentence[offset..end];
+    let upper = word.to_uppercase();
+
+    let token = if KEYWORDS.contains(&upper.as_str()) {
+        Token {
+            kind: TokenKind::Keyword,
+            value: upper,
+            offset,
+        }
+    } else {
+        Token {
+            kind: TokenKind::Identifier,
+            value: word.to_string(),
+            offset,
+        }
+    };
+    (token, index)
+}
+
+fn read_symbol(
+    chars: &[(usize, char)],
+    start: usize,
+    sentence: &str,
+) -> Result<(Token, usize), MiniSQLError> {
+    let (offset, c) = chars[start];
+    let next = chars.get(start + 1).map(|(_, c)| *c);
+
+    let (kind, value) = match (c, next) {
+        ('<', Some('>')) => (TokenKind::Operator, "<>".to_string()),
+        ('<' | '>' | '!', Some('=')) => (TokenKind::Operator, format!("{}=", c)),
+        ('|', Some('|')) => (TokenKind::Operator, "||".to_string()),
+        ('=' | '<' | '>' | '+' | '-' | '*' | '/' | '%', _) => (TokenKind::Operator, c.to_string()),
+        ('(' | ')' | ',' | ';' | '.', _) => (TokenKind::Punctuation, c.to_string()),
+        _ => {
+            return Err(syntax_error(
+                sentence,
+                offset,
+                &format!("unexpected character '{}'", c),
+            ))
+        }
+    };
+    let length = value.chars().count();
+
+    Ok((
+        Token {
+            kind,
+            value,
+            offset,
+        },
+        start + length,
+    ))
+}
+
+#[cfg(test)]
+mod test_tokenizer {
+    use super::*;
+
+    fn kinds_and_values(sentence: &str) -> Vec<(TokenKind, String)> {
+        tokenize(sentence)
+            .unwrap()
+            .into_iter()
+            .map(|token| (token.kind, token.value))
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize_select() {
+        let tokens = kinds_and_values("select nombre, id FROM clientes WHERE id >= 5;");
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::Keyword, "SELECT".to_string()),
+                (TokenKind::Identifier, "nombre".to_string()),
+                (TokenKind::Punctuation, ",".to_string()),
+                (TokenKind::Identifier, "id".to_string()),
+                (TokenKind::Keyword, "FROM".to_string()),
+                (TokenKind::Identifier, "clientes".to_string()),
+                (TokenKind::Keyword, "WHERE".to_string()),
+                (TokenKind::Identifier, "id".to_string()),
+                (TokenKind::Operator, ">=".to_string()),
+                (TokenKind::NumericLiteral, "5".to_string()),
+                (TokenKind::Punctuation, ";".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_literals_are_not_syntax() {
+        let tokens = kinds_and_values("x = 'OR' AND y = 'a, b' AND z = '1'");
+        assert_eq!(tokens[2], (TokenKind::StringLiteral, "OR".to_string()));
+        assert_eq!(tokens[3], (TokenKind::Keyword, "AND".to_string()));
+        assert_eq!(tokens[6], (TokenKind::StringLiteral, "a, b".to_string()));
+        assert_eq!(tokens[10], (TokenKind::StringLiteral, "1".to_string()));
+    }
+
+    #[test]
+    fn test_tokenize_escaped_quote_and_offsets() {
+        let tokens = tokenize("nombre = 'O''Brien'").unwrap();
+        assert_eq!(tokens[2].value, "O'Brien");
+        assert_eq!(tokens[0].offset, 0);
+        assert_eq!(tokens[1].offset, 7);
+        assert_eq!(tokens[2].offset, 9);
+    }
+
+    #[test]
+    fn test_tokenize_operators_and_negative_numbers() {
+        let tokens = kinds_and_values("a <> -5 AND b != c - 1");
+        assert_eq!(tokens[1], (TokenKind::Operator, "<>".to_string()));
+        assert_eq!(tokens[2], (TokenKind::NumericLiteral, "-5".to_string()));
+        assert_eq!(tokens[5], (TokenKind::Operator, "!=".to_string()));
+        assert_eq!(tokens[7], (TokenKind::Operator, "-".to_string()));
+        assert_eq!(tokens[8], (TokenKind::NumericLiteral, "1".to_string()));
+    }
+
+    #[test]
+    fn test_tokenize_unicode_identifier() {
+        let tokens = kinds_and_values("Correo_electrónico = 'López'");
+        assert_eq!(
+            tokens[0],
+            (TokenKind::Identifier, "Correo_electrónico".to_string())
+        );
+        assert_eq!(tokens[2], (TokenKind::StringLiteral, "López".to_string()));
+    }
+
+    #[test]
+    fn test_tokenize_unterminated_literal() {
+        let result = tokenize("nombre = 'pepe");
+        assert_eq!(
+            result,
+            Err(MiniSQLError::InvalidSyntax(
+                "unterminated quoted value at line 1, column 10 ".to_string()
+            ))
+        );
+    }
+}
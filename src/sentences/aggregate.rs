@@ -0,0 +1,558 @@
+use super::conditions::Coercion;
+use super::join::join_key;
+use crate::errors::apperrors::MiniSQLError;
+use crate::parser::ast::{Aggregate, AggregateFunction};
+use crate::types::decimal::Decimal;
+use crate::types::value::{is_null, promote, ColumnType, Value, NULL};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+/// Running state of an aggregate function, updated one line at a time
+/// so the lines themselves never have to be kept.
+#[derive(Clone)]
+pub struct Accumulator {
+    aggregate: Aggregate,
+    /// position and declared type of the aggregated field, `None` for COUNT(*)
+    field: Option<(usize, Option<ColumnType>)>,
+    /// values already aggregated, only for DISTINCT
+    seen: Option<HashSet<String>>,
+    count: i64,
+    sum: Option<Value>,
+    /// current minimum or maximum
+    best: Option<Value>,
+    coercion: Coercion,
+}
+
+/// Resolves the field of an aggregate with the table `indexes` and `types`.
+///
+/// A stored value that is not valid for the declared type of its field is still counted,
+/// compared as text by MIN and MAX and left out of SUM and AVG, unless the coercion is strict.
+///
+/// # Errors
+///
+/// - `MiniSQLError::InvalidColumn` if the aggregated field does not exist.
+pub fn compile_aggregate(
+    aggregate: &Aggregate,
+    indexes: &HashMap<String, usize>,
+    types: &[Option<ColumnType>],
+    coercion: Coercion,
+) -> Result<Accumulator, MiniSQLError> {
+    let field = match &aggregate.argument {
+        Some(name) => match indexes.get(name) {
+            Some(index) => Some((*index, types.get(*index).copied().flatten())),
+            None => {
+                return Err(MiniSQLError::InvalidColumn(format!(
+                    "requested field [ {} ] could not be found",
+                    name
+                )))
+            }
+        },
+        None => None,
+    };
+
+    Ok(Accumulator {
+        aggregate: aggregate.clone(),
+        field,
+        seen: if aggregate.distinct {
+            Some(HashSet::new())
+        } else {
+            None
+        },
+        count: 0,
+        sum: None,
+        best: None,
+        coercion,
+    })
+}
+
+impl Accumulator {
+    /// Adds a line that matched the query, NULL values are skipped
+    pub fn update(&mut self, line: &[String]) -> Result<(), MiniSQLError> {
+        let (index, column_type) = match self.field {
+            Some(field) => field,
+            None => {
+                self.count += 1;
+                return Ok(());
+            }
+        };
+
+        let text = line.get(index).map(|value| value.as_str()).unwrap_or(NULL);
+        if is_null(text, column_type) {
+            return Ok(());
+        }
+        let value = match self.read_value(text, column_type)? {
+            Some(value) => value,
+            // un valor invalido para su tipo no suma, pero si se cuenta y se compara como texto
+            None if self.aggregate.function == AggregateFunction::Sum
+                || self.aggregate.function == AggregateFunction::Avg =>
+            {
+                return Ok(());
+            }
+            None => Value::Text(text.to_string()),
+        };
+        if let Some(seen) = &mut self.seen {
+            // `join_key`, la clave de GROUP BY y de SELECT DISTINCT: `1` y `01` son el mismo valor
+            if !seen.insert(join_key(text, column_type)) {
+                return Ok(());
+            }
+        }
+        self.count += 1;
+
+        match self.aggregate.function {
+            AggregateFunction::Count => (),
+            AggregateFunction::Sum | AggregateFunction::Avg => {
+                self.sum = Some(match self.sum.take() {
+                    Some(sum) => self.add(sum, value)?,
+                    None => value,
+                });
+            }
+            AggregateFunction::Min | AggregateFunction::Max => {
+                let replace = match &self.best {
+                    Some(best) => {
+                        let ordering = value.total_cmp(best);
+                        if self.aggregate.function == AggregateFunction::Min {
+                            ordering == Ordering::Less
+                        } else {
+                            ordering == Ordering::Greater
+                        }
+                    }
+                    None => true,
+                };
+                if replace {
+                    self.best = Some(value);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Final value, as it is stored on the line. Functions other than COUNT are NULL without values.
+    pub fn result(&self) -> String {
+        match self.aggregate.function {
+            AggregateFunction::Count => self.count.to_string(),
+            AggregateFunction::Sum => self
+                .sum
+                .as_ref()
+                .map(|sum| sum.to_string())
+                .unwrap_or_else(|| NULL.to_string()),
+            AggregateFunction::Avg => match &self.sum {
+                Some(Value::Integer(sum)) => {
+                    Value::Real(*sum as f64 / self.count as f64).to_string()
+                }
+                Some(Value::Real(sum)) => Value::Real(sum / self.count as f64).to_string(),
+                Some(Value::Decimal(sum)) => {
+                    match sum.checked_div(&Decimal::from_integer(self.count)) {
+                        Some(average) => average.to_string(),
+                        None => Value::Real(sum.to_f64() / self.count as f64).to_string(),
+                    }
+                }
+                _ => NULL.to_string(),
+            },
+            AggregateFunction::Min | AggregateFunction::Max => self
+                .best
+                .as_ref()
+                .map(|best| best.to_string())
+                .unwrap_or_else(|| NULL.to_string()),
+        }
+    }
+
+    /// Type of the result, used to compare it on HAVING. A SUM keeps the type of its field,
+    /// an integer one as BIGINT, and an AVG is REAL unless its field is DECIMAL.
+    /// Over an undeclared field the type of a SUM depends on its values, so it is not known.
+    pub fn result_type(&self) -> Option<ColumnType> {
+        let field_type = self.field.and_then(|(_, column_type)| column_type);
+        match self.aggregate.function {
+            AggregateFunction::Count => Some(ColumnType::BigInt),
+            AggregateFunction::Sum => match field_type {
+                Some(ColumnType::Integer | ColumnType::BigInt) => Some(ColumnType::BigInt),
+                field_type => field_type,
+            },
+            AggregateFunction::Avg => match field_type {
+                Some(ColumnType::Decimal) => Some(ColumnType::Decimal),
+                _ => Some(ColumnType::Real),
+            },
+            AggregateFunction::Min | AggregateFunction::Max => field_type,
+        }
+    }
+
+    /// Reads a value with the declared type of the field, or guesses a number otherwise.
+    /// `None` for a value that is not valid for the declared type when the coercion is implicit.
+    fn read_value(
+        &self,
+        text: &str,
+        column_type: Option<ColumnType>,
+    ) -> Result<Option<Value>, MiniSQLError> {
+        let value = match column_type {
+            Some(column_type) => match column_type.parse(text) {
+                Some(value) => value,
+                None if self.coercion == Coercion::Implicit => return Ok(None),
+                None => {
+                    return Err(MiniSQLError::InvalidSyntax(format!(
+                        "invalid value {} for a field of type {} on {} ",
+                        text, column_type, self.aggregate
+                    )))
+                }
+            },
+            None => Value::infer(text),
+        };
+
+        let numeric = value.is_number();
+        let needs_number = matches!(
+            self.aggregate.function,
+            AggregateFunction::Sum | AggregateFunction::Avg
+        );
+        if needs_number && !numeric {
+            return Err(MiniSQLError::InvalidSyntax(format!(
+                "can not apply {} to the non numeric value {} ",
+                self.aggregate, text
+            )));
+        }
+        Ok(Some(value))
+    }
+
+    /// Adds with the same promotion as arithmetic: integers stay integers until a decimal or
+    /// a real is added
+    fn add(&self, sum: Value, value: Value) -> Result<Value, MiniSQLError> {
+        match promote(&sum, &value) {
+            Some((Value::Integer(a), Value::Integer(b))) => match a.checked_add(b) {
+                Some(total) => Ok(Value::Integer(total)),
+                None => Err(MiniSQLError::Generic(format!(
+                    "integer overflow while computing {} ",
+                    self.aggregate
+                ))),
+            },
+            Some((Value::Decimal(a), Value::Decimal(b))) => match a.checked_add(&b) {
+                Some(total) => Ok(Value::Decimal(total)),
+                None => Err(MiniSQLError::Generic(format!(
+                    "decimal overflow while computing {} ",
+                    self.aggregate
+                ))),
+            },
+            Some((Value::Real(a), Value::Real(b))) => Ok(Value::Real(a + b)),
+            _ => Err(MiniSQLError::Generic(format!(
+                "unexpected error while computing {} ",
+                self.aggregate
+            ))),
+        }
+    }
+}
+
+/// Registers grouped by the values of the GROUP BY fields, each group keeps its own accumulators.
+///
+/// Only the first register of each group and the accumulators are kept in memory.
+pub struct Grouping {
+    /// position and declared type of each field of the group key
+    fields: Vec<(usize, Option<ColumnType>)>,
+    accumulators: Vec<Accumulator>,
+    positions: HashMap<Vec<String>, usize>,
+    /// first register of each group, in the order the groups appeared
+    groups: Vec<(Vec<String>, Vec<Accumulator>)>,
+}
+
+impl Grouping {
+    pub fn new(
+        fields: Vec<(usize, Option<ColumnType>)>,
+        accumulators: Vec<Accumulator>,
+    ) -> Grouping {
+        Grouping {
+            fields,
+            accumulators,
+            positions: HashMap::new(),
+            groups: vec![],
+        }
+    }
+
+    /// Adds a register to its group, creating the group the first time its key is seen
+    pub fn update(&mut self, line: Vec<String>) -> Result<(), MiniSQLError> {
+        let key: Vec<String> = self
+            .fields
+            .iter()
+            .map(|(index, column_type)| {
+                join_key(
+                    line.get(*index).map(|s| s.as_str()).unwrap_or(""),
+                    *column_type,
+                )
+            })
+            .collect();
+
+        let position = match self.positions.get(&key) {
+            Some(position) => *position,
+            None => {
+                self.positions.insert(key, self.groups.len());
+                self.groups.push((line.clone(), self.accumulators.clone()));
+                self.groups.len() - 1
+            }
+        };
+        for accumulator in self.groups[position].1.iter_mut() {
+            accumulator.update(&line)?;
+        }
+        Ok(())
+    }
+
+    /// One line per group: the first register of the group, completed to `width` fields,
+    /// followed by the result of each aggregate.
+    ///
+    /// Without GROUP BY fields there is always a single group, even if no register was read.
+    pub fn into_lines(mut self, width: usize) -> Vec<Vec<String>> {
+        if self.fields.is_empty() && self.groups.is_empty() {
+            self.groups.push((vec![], self.accumulators.clone()));
+        }
+        self.groups
+            .into_iter()
+            .map(|(mut line, accumulators)| {
+                line.resize(width, "".to_string());
+                line.extend(accumulators.iter().map(|accumulator| accumulator.result()));
+                line
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test_aggregate {
+    use super::*;
+
+    fn aggregate(
+        function: AggregateFunction,
+        distinct: bool,
+        types: &[Option<ColumnType>],
+        values: &[&str],
+    ) -> Result<String, MiniSQLError> {
+        aggregate_with(function, types, values, distinct, Coercion::Implicit)
+    }
+
+    fn aggregate_with(
+        function: AggregateFunction,
+        types: &[Option<ColumnType>],
+        values: &[&str],
+        distinct: bool,
+        coercion: Coercion,
+    ) -> Result<String, MiniSQLError> {
+        let indexes = HashMap::from([("x".to_string(), 0)]);
+        let mut accumulator = compile_aggregate(
+            &Aggregate {
+                function,
+                distinct,
+                argument: Some("x".to_string()),
+            },
+            &indexes,
+            types,
+            coercion,
+        )?;
+        for value in values {
+            accumulator.update(&[value.to_string()])?;
+        }
+        Ok(accumulator.result())
+    }
+
+    fn sum_by_first_field(lines: &[[&str; 2]]) -> Vec<Vec<String>> {
+        let indexes = HashMap::from([("x".to_string(), 1)]);
+        let sum = compile_aggregate(
+            &Aggregate {
+                function: AggregateFunction::Sum,
+                distinct: false,
+                argument: Some("x".to_string()),
+            },
+            &indexes,
+            &[],
+            Coercion::Implicit,
+        )
+        .unwrap();
+        let mut grouping = Grouping::new(vec![(0, None)], vec![sum]);
+        for line in lines {
+            grouping
+                .update(line.iter().map(|value| value.to_string()).collect())
+                .unwrap();
+        }
+        grouping.into_lines(2)
+    }
+
+    #[test]
+    fn test_grouping_keeps_first_register_and_order() {
+        let lines = sum_by_first_field(&[["b", "1"], ["a", "2"], ["b", "3"], ["", "4"]]);
+        assert_eq!(
+            lines,
+            vec![vec!["b", "1", "4"], vec!["a", "2", "2"], vec!["", "4", "4"]]
+        );
+    }
+
+    #[test]
+    fn test_grouping_normalizes_numbers() {
+        let lines = sum_by_first_field(&[["5", "1"], ["05", "2"]]);
+        assert_eq!(lines, vec![vec!["5", "1", "3"]]);
+    }
+
+    #[test]
+    fn test_grouping_without_registers() {
+        assert!(sum_by_first_field(&[]).is_empty());
+        let grouping = Grouping::new(vec![], vec![]);
+        assert_eq!(grouping.into_lines(2), vec![vec!["", ""]]);
+    }
+
+    #[test]
+    fn test_count_skips_null_values() {
+        // un campo vacio sin tipo declarado es un texto vacio, no NULL
+        let result = aggregate(AggregateFunction::Count, false, &[], &["1", "", "3", "1"]);
+        assert_eq!(result, Ok("4".to_string()));
+        let result = aggregate(AggregateFunction::Count, false, &[], &["1", NULL, "3"]);
+        assert_eq!(result, Ok("2".to_string()));
+        let result = aggregate(
+            AggregateFunction::Count,
+            false,
+            &[Some(ColumnType::Text)],
+            &["a", "", NULL],
+        );
+        assert_eq!(result, Ok("2".to_string()));
+        let result = aggregate(AggregateFunction::Count, true, &[], &["1", NULL, "3", "01"]);
+        assert_eq!(result, Ok("2".to_string()));
+    }
+
+    #[test]
+    fn test_count_distinct_normalizes_numbers() {
+        let result = aggregate(
+            AggregateFunction::Count,
+            true,
+            &[],
+            &["1", "1.0", "01", "2"],
+        );
+        assert_eq!(result, Ok("2".to_string()));
+        let result = aggregate(
+            AggregateFunction::Count,
+            true,
+            &[Some(ColumnType::Decimal)],
+            &["1.50", "1.5", "2"],
+        );
+        assert_eq!(result, Ok("2".to_string()));
+    }
+
+    #[test]
+    fn test_sum_and_avg() {
+        assert_eq!(
+            aggregate(AggregateFunction::Sum, false, &[], &["1", "2", "4"]),
+            Ok("7".to_string())
+        );
+        assert_eq!(
+            aggregate(AggregateFunction::Sum, false, &[], &["1", "2.5"]),
+            Ok("3.5".to_string())
+        );
+        assert_eq!(
+            aggregate(AggregateFunction::Avg, false, &[], &["1", "2"]),
+            Ok("1.5".to_string())
+        );
+        assert_eq!(
+            aggregate(AggregateFunction::Sum, false, &[], &[]),
+            Ok(NULL.to_string())
+        );
+        assert_eq!(
+            aggregate(AggregateFunction::Avg, false, &[], &["1", NULL, "4"]),
+            Ok("2.5".to_string())
+        );
+    }
+
+    #[test]
+    fn test_sum_and_avg_keep_the_type_of_the_field() {
+        let result_type = |function: AggregateFunction, column_type: Option<ColumnType>| {
+            let indexes = HashMap::from([("x".to_string(), 0)]);
+            compile_aggregate(
+                &Aggregate {
+                    function,
+                    distinct: false,
+                    argument: Some("x".to_string()),
+                },
+                &indexes,
+                &[column_type],
+                Coercion::Implicit,
+            )
+            .map(|accumulator| accumulator.result_type())
+        };
+        let integer = Some(ColumnType::Integer);
+        let decimal = Some(ColumnType::Decimal);
+        assert_eq!(
+            result_type(AggregateFunction::Sum, integer),
+            Ok(Some(ColumnType::BigInt))
+        );
+        assert_eq!(result_type(AggregateFunction::Sum, decimal), Ok(decimal));
+        assert_eq!(result_type(AggregateFunction::Sum, None), Ok(None));
+        assert_eq!(
+            result_type(AggregateFunction::Avg, integer),
+            Ok(Some(ColumnType::Real))
+        );
+        assert_eq!(result_type(AggregateFunction::Avg, decimal), Ok(decimal));
+
+        assert_eq!(
+            aggregate(AggregateFunction::Sum, false, &[decimal], &["0.1", "0.2"]),
+            Ok("0.3".to_string())
+        );
+        assert_eq!(
+            aggregate(AggregateFunction::Sum, false, &[integer], &["2", "3"]),
+            Ok("5".to_string())
+        );
+    }
+
+    #[test]
+    fn test_invalid_values_for_the_declared_type() {
+        let integer = [Some(ColumnType::Integer)];
+        let values = ["3", "abc", "10"];
+        let implicit = |function| aggregate(function, false, &integer, &values);
+        assert_eq!(implicit(AggregateFunction::Count), Ok("3".to_string()));
+        assert_eq!(implicit(AggregateFunction::Min), Ok("3".to_string()));
+        // el texto va despues de cualquier numero
+        assert_eq!(implicit(AggregateFunction::Max), Ok("abc".to_string()));
+        assert_eq!(implicit(AggregateFunction::Sum), Ok("13".to_string()));
+        assert_eq!(implicit(AggregateFunction::Avg), Ok("6.5".to_string()));
+        assert_eq!(
+            aggregate_with(
+                AggregateFunction::Count,
+                &integer,
+                &values,
+                false,
+                Coercion::Strict
+            ),
+            Err(MiniSQLError::InvalidSyntax(
+                "invalid value abc for a field of type INTEGER on COUNT(x) ".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_sum_rejects_text() {
+        assert_eq!(
+            aggregate(AggregateFunction::Sum, false, &[], &["1", "pepe"]),
+            Err(MiniSQLError::InvalidSyntax(
+                "can not apply SUM(x) to the non numeric value pepe ".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_sum_overflow() {
+        let max = i64::MAX.to_string();
+        assert_eq!(
+            aggregate(AggregateFunction::Sum, false, &[], &[&max, "1"]),
+            Err(MiniSQLError::Generic(
+                "integer overflow while computing SUM(x) ".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_min_max_by_type() {
+        assert_eq!(
+            aggregate(AggregateFunction::Max, false, &[], &["9", "10", "2"]),
+            Ok("10".to_string())
+        );
+        assert_eq!(
+            aggregate(
+                AggregateFunction::Min,
+                false,
+                &[Some(ColumnType::Date)],
+                &["2024-01-10", "2023-12-31"]
+            ),
+            Ok("2023-12-31".to_string())
+        );
+        assert_eq!(
+            aggregate(AggregateFunction::Min, false, &[], &["pepe", "ana"]),
+            Ok("ana".to_string())
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use super::common::{format_to_csv, get_headers, literal_to_stored};
+use crate::errors::apperrors::MiniSQLError;
+use crate::file;
+use crate::parser::ast::{AlterOperation, AlterTableStatement, ColumnConstraint, ColumnDefinition};
+use crate::types::value::NULL;
+use csv::Reader;
+use std::fs::File;
+
+/// What has to change on every register of the table
+enum LineChange {
+    Append(String),
+    Remove(usize),
+    Keep,
+}
+
+/// Executes a parsed `ALTER TABLE` statement.
+///
+/// Adding, dropping or renaming a field rewrites the whole table on a temporary file
+/// that replaces the original once it is complete. The new schema is also written on a
+/// temporary file before the table is replaced, so failing to write it leaves both as they were.
+/// Renaming the table moves its files.
+///
+/// # Examples
+///
+/// ```
+/// if let Statement::AlterTable(alter) = parse("ALTER TABLE clientes ADD COLUMN pais TEXT DEFAULT 'AR'")? {
+///     execute_alter_statement(alter, &"user/data/tables");
+/// }
+/// ```
+///
+/// # Errors
+///
+/// This function will return an error of type `MiniSQLError` if:
+///
+/// - The provided table is invalid, or the new table name is already taken.
+/// - The field to drop or rename does not exist, or the new field name is already taken.
+/// - The new field has a default value but no type, or the value does not fit its type.
+///
+/// # Returns
+///
+/// - `Ok(())` if the query executes successfully.
+/// - `Err(MiniSQLError)` if an error occurs during execution.
+///
+pub fn execute_alter_statement(
+    alter: AlterTableStatement,
+    route: &String,
+) -> Result<(), MiniSQLError> {
+    let file_iter = file::handler::new_file_iterator(route, &alter.table)?;
+
+    match &alter.operation {
+        AlterOperation::RenameTable(new_name) => {
+            file::handler::rename_table(route, &alter.table, new_name)
+        }
+        _ => execute_alter(&alter, file_iter, route),
+    }
+}
+
+fn execute_alter(
+    sentence: &AlterTableStatement,
+    file_iter: Reader<File>,
+    file_path: &String,
+) -> Result<(), MiniSQLError> {
+    let (file_iter, mut headers) = get_headers(file_iter)?;
+    let mut schema = file::schema::read_schema(file_path, &sentence.table)?;
+    let table_size = headers.len();
+
+    let change = match &sentence.operation {
+        AlterOperation::AddColumn {
+            name,
+            column_type,
+            default,
+        } => {
+            check_new_field(&headers, name)?;
+            // sin tipo no hay esquema donde guardar el DEFAULT para los proximos INSERT
+            if column_type.is_none() && default.is_some() {
+                return Err(MiniSQLError::InvalidSyntax(format!(
+                    "field [ {} ] needs a type to have a DEFAULT value ",
+                    name
+                )));
+            }
+            let value = match default {
+                Some(default) => literal_to_stored(default, column_type, name)?,
+                None => NULL.to_string(),
+            };
+            if let Some(column_type) = column_type {
+                schema.add_column(ColumnDefinition {
+                    name: name.to_string(),
+                    column_type: *column_type,
+                    constraints: default
+                        .iter()
+                        .map(|default| ColumnConstraint::Default(default.clone()))
+                        .collect(),
+                });
+            }
+            headers.push(name.to_string());
+            LineChange::Append(value)
+        }
+        AlterOperation::DropColumn(name) => {
+            let index = find_field(&headers, name)?;
+            if headers.len() == 1 {
+                return Err(MiniSQLError::InvalidTable(format!(
+                    "can not drop [ {} ], a table must keep at least one field ",
+                    name
+                )));
+            }
+            schema.drop_column(name);
+            headers.remove(index);
+            LineChange::Remove(index)
+        }
+        AlterOperation::RenameColumn { from, to } => {
+            let index = find_field(&headers, from)?;
+            check_new_field(&headers, to)?;
+            schema.rename_column(from, to);
+            headers[index] = to.to_string();
+            LineChange::Keep
+        }
+        AlterOperation::RenameTable(_) => LineChange::Keep,
+    };
+
+    let mut new_file = file::handler::create_file(file_path, &sentence.table)?;
+    new_file.write_record(&headers)?;
+    for result in file_iter.into_records() {
+        let record = result?;
+        let mut line = format_to_csv(&record);
+        match &change {
+            LineChange::Append(value) => {
+                // las filas incompletas se completan para que el valor quede en su campo
+                line.resize(table_size, NULL.to_string());
+                line.push(value.to_string());
+            }
+            LineChange::Remove(index) if *index < line.len() => {
+                line.remove(*index);
+            }
+            _ => (),
+        }
+        new_file.write_record(&line)?;
+    }
+    new_file.flush()?;
+
+    // el esquema se escribe antes de reemplazar la tabla, si falla la tabla queda como estaba
+    let keep_schema =
+        !schema.columns().is_empty() || file::schema::has_schema(file_path, &sentence.table);
+    if keep_schema {
+        file::schema::write_temp_schema(file_path, &sentence.table, schema.columns())?;
+    }
+    file::handler::rename_file(file_path, &sentence.table)?;
+    if keep_schema {
+        file::schema::replace_schema(file_path, &sentence.table)?;
+    }
+    Ok(())
+}
+
+fn find_field(headers: &[String], name: &str) -> Result<usize, MiniSQLError> {
+    match headers.iter().position(|header| header == name) {
+        Some(index) => Ok(index),
+        None => Err(MiniSQLError::InvalidColumn(format!(
+            "requested field [ {} ] could not be found",
+            name
+        ))),
+    }
+}
+
+fn check_new_field(headers: &[String], name: &str) -> Result<(), MiniSQLError> {
+    if headers.iter().any(|header| header == name) {
+        return Err(MiniSQLError::InvalidColumn(format!(
+            "field [ {} ] already exists ",
+            name
+        )));
+    }
+    Ok(())
+}
@@ -1,48 +1,47 @@
+use super::join::join_key;
 use crate::errors::apperrors::MiniSQLError;
-use std::collections::HashMap;
+use crate::parser::ast::{ColumnDefinition, Literal};
+use crate::types::value::{is_null, ColumnType, NULL};
+use csv::{Reader, StringRecord};
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
-use std::io::{BufRead, BufReader};
 
-pub fn get_headers(mut file_iter: BufReader<File>) -> (BufReader<File>, Vec<String>) {
-    let mut buffer: String = String::new();
-    let _ = file_iter.read_line(&mut buffer);
-    (file_iter, format_to_csv(buffer.replace("\n", "")))
+pub fn get_headers(
+    mut file_iter: Reader<File>,
+) -> Result<(Reader<File>, Vec<String>), MiniSQLError> {
+    let headers = format_to_csv(file_iter.headers()?);
+    Ok((file_iter, headers))
 }
 
-pub fn format_to_csv(buffer: String) -> Vec<String> {
-    let vec: Vec<String> = buffer.split(",").map(|s| s.to_string()).collect();
-    vec
+/// Converts a record read from the table into a line of values, quotes already removed
+pub fn format_to_csv(record: &StringRecord) -> Vec<String> {
+    record.iter().map(|s| s.to_string()).collect()
 }
 
+/// Resolves the fields requested by a query to their position on the line, in the order
+/// they were requested. A field requested twice appears twice.
+///
+/// `*` selects every header on its place of the list, any other field is looked up on
+/// `mapped_fields`, so it can be qualified ( `table.field` ) when the query reads more than one table.
 pub fn get_required_fields(
     query_fields: &[String],
     headers: &[String],
-) -> Result<HashMap<String, usize>, MiniSQLError> {
-    if query_fields.len() == 1 && query_fields[0] == "*" {
-        let all_fields: HashMap<String, usize> = add_all_fields(headers);
-        return Ok(all_fields);
-    }
-
-    let mut indexes: HashMap<String, usize> = HashMap::new();
+    mapped_fields: &HashMap<String, usize>,
+) -> Result<Vec<usize>, MiniSQLError> {
+    let mut indexes: Vec<usize> = vec![];
     for field in query_fields {
-        if field == "(" || field == ")" || field == "," {
+        if field == "*" {
+            indexes.extend(0..headers.len());
             continue;
         }
-        let mut index = 0;
-        let mut found = false;
-        for csv_header in headers {
-            if field == csv_header {
-                indexes.insert(field.clone(), index);
-                found = true;
-                continue;
+        match mapped_fields.get(field) {
+            Some(index) => indexes.push(*index),
+            None => {
+                return Err(MiniSQLError::InvalidColumn(format!(
+                    "requested field [ {} ] could not be found",
+                    field
+                )))
             }
-            index += 1;
-        }
-        if !found {
-            return Err(MiniSQLError::InvalidColumn(format!(
-                "requested field [ {} ] could not be found",
-                field
-            )));
         }
     }
 
@@ -62,21 +61,88 @@ pub fn add_all_fields(headers: &[String]) -> HashMap<String, usize> {
     indexes
 }
 
-pub fn validate_table(mut from: Vec<String>) -> Result<String, MiniSQLError> {
-    let table = if let Some(first) = from.pop() {
-        first
-    } else {
-        return Err(MiniSQLError::InvalidTable(
-            "no table was given ".to_string(),
-        ));
+/// Value of a literal as it is stored, checked like `to_stored_value`. The text `\N` is how a
+/// NULL is stored, so it can not be written as a text: it would be read back as NULL.
+pub fn literal_to_stored(
+    literal: &Literal,
+    column_type: &Option<ColumnType>,
+    field: &str,
+) -> Result<String, MiniSQLError> {
+    if matches!(literal, Literal::String(text) if text == NULL) {
+        return Err(MiniSQLError::InvalidColumn(format!(
+            "the text [ {} ] for field [ {} ] is how NULL is stored, write NULL instead ",
+            NULL, field
+        )));
+    }
+    to_stored_value(literal.to_string(), column_type, field)
+}
+
+/// Checks that a value fits the declared type of its field and returns it as it is stored.
+/// NULL fits any type. A number too large for its type is reported as an overflow.
+pub fn to_stored_value(
+    value: String,
+    column_type: &Option<ColumnType>,
+    field: &str,
+) -> Result<String, MiniSQLError> {
+    let column_type = match column_type {
+        Some(column_type) if value != NULL => column_type,
+        _ => return Ok(value),
     };
+    match column_type.parse(&value) {
+        Some(typed) => Ok(typed.to_string()),
+        None if column_type.overflows(&value) => Err(MiniSQLError::InvalidColumn(format!(
+            "value [ {} ] overflows field [ {} ] of type {} ",
+            value, field, column_type
+        ))),
+        None => Err(MiniSQLError::InvalidColumn(format!(
+            "invalid value [ {} ] for field [ {} ] of type {} ",
+            value, field, column_type
+        ))),
+    }
+}
 
-    if !from.is_empty() {
-        from.push(table);
-        return Err(MiniSQLError::InvalidTable(format!(
-            "multiple references for table: {} ",
-            from.join(" ")
-        )));
+/// Empty set of seen values for each UNIQUE or PRIMARY KEY field, by position on the line
+pub fn unique_fields(definitions: &[Option<&ColumnDefinition>]) -> HashMap<usize, HashSet<String>> {
+    let mut unique_values: HashMap<usize, HashSet<String>> = HashMap::new();
+    for (index, definition) in definitions.iter().enumerate() {
+        if definition.is_some_and(|definition| definition.is_unique()) {
+            unique_values.insert(index, HashSet::new());
+        }
+    }
+    unique_values
+}
+
+/// Value of a UNIQUE field as it is compared with the others: `1`, `01` and `1.0` are the same
+/// value on a numeric field. None for NULL, which never repeats.
+pub fn unique_key(value: &str, definition: &ColumnDefinition) -> Option<String> {
+    if is_null(value, Some(definition.column_type)) {
+        return None;
+    }
+    Some(join_key(value, Some(definition.column_type)))
+}
+
+/// Adds the values of a line to the seen values of its UNIQUE fields, failing if one was
+/// already seen
+pub fn check_unique(
+    line: &[String],
+    definitions: &[Option<&ColumnDefinition>],
+    unique_values: &mut HashMap<usize, HashSet<String>>,
+) -> Result<(), MiniSQLError> {
+    // se recorre en el orden de los campos para que el error no dependa del HashMap
+    for (index, definition) in definitions.iter().enumerate() {
+        let (value, definition, values) =
+            match (line.get(index), definition, unique_values.get_mut(&index)) {
+                (Some(value), Some(definition), Some(values)) => (value, definition, values),
+                _ => continue,
+            };
+        if let Some(key) = unique_key(value, definition) {
+            if !values.insert(key) {
+                return Err(MiniSQLError::InvalidColumn(format!(
+                    "duplicate value [ {} ] for unique field [ {} ] ",
+                    value, definition.name
+                )));
+            }
+        }
     }
-    Ok(table)
+    Ok(())
 }
@@ -1,339 +1,1115 @@
 use crate::errors::apperrors::MiniSQLError;
-use std::collections::HashMap;
+use crate::parser::ast::{BinaryOperator, Expr, Function, Literal, UnaryOperator};
+use crate::types::date::{Date, DateUnit, Timestamp};
+use crate::types::value::{is_null, promote, ColumnType, Value, NULL};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
 
-pub fn get_query(
-    condition: &[String],
-    start: usize,
-    end: usize,
+/// A WHERE condition compiled against the headers of a table.
+///
+/// Columns are resolved to their position on the line and literals are parsed only once,
+/// so evaluating it on every line does not go through the parsed sentence again.
+///
+/// Fields with a declared type are compared by that type. Undeclared fields keep
+/// guessing: two values that read as numbers are compared as numbers, otherwise as text.
+/// Numbers of different types are promoted before comparing them: an integer against a
+/// decimal is compared as a decimal, and against a real as a real.
+///
+/// Values of different types are coerced as said by its `Coercion`: by default a number
+/// against a word, or a stored value that does not fit the declared type of its field, is
+/// compared as text instead of failing the whole query.
+///
+/// `LIKE` and `ILIKE` match the value as it is stored, so `codigo LIKE '00%'` keeps its zeros.
+///
+/// A comparison with NULL is neither true nor false but unknown, and AND, OR and NOT follow
+/// three-valued logic: `NULL > 0 OR TRUE` is true, `NOT NULL > 0` is still unknown. Only lines
+/// where the whole condition is true match it. `IS [NOT] NULL` is always true or false.
+pub struct Condition {
+    /// a missing condition applies to every line
+    root: Option<Node>,
+    coercion: Coercion,
+}
+
+/// How a comparison between values of different types is resolved
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Coercion {
+    /// both sides are compared as numbers when they read as numbers, by the declared type
+    /// of the field when they fit it, and as text otherwise
+    #[default]
+    Implicit,
+    /// a comparison between values of different types is an error
+    Strict,
+}
+
+enum Node {
+    /// `None` is unknown, the value of a NULL used as a condition
+    Constant(Option<bool>),
+    Not(Box<Node>),
+    And(Box<Node>, Box<Node>),
+    Or(Box<Node>, Box<Node>),
+    Comparison(Operand, BinaryOperator, Operand),
+    Like(Operand, Like),
+    /// position of the field on the line and its list of values
+    In(usize, InList),
+    /// value checked and whether it is negated
+    IsNull(Operand, bool),
+}
+
+/// The literals of `field IN (...)`, looked up on a hash set instead of comparing one by one
+struct InList {
+    column_type: Option<ColumnType>,
+    /// values read with the type of the field, or the text of the literals of an undeclared field
+    texts: HashSet<String>,
+    /// `number_key` of the unquoted numbers, compared as numbers against an undeclared field
+    numbers: HashSet<String>,
+    /// a NULL on the list turns a value not found into unknown
+    has_null: bool,
+    negated: bool,
+}
+
+/// A LIKE or ILIKE predicate
+struct Like {
+    pattern: LikePattern,
+    escape: Option<char>,
+    negated: bool,
+    case_insensitive: bool,
+}
+
+enum LikePattern {
+    /// a literal pattern is read only once
+    Compiled(Vec<Piece>),
+    /// a pattern taken from the line is read on each line
+    Dynamic(Operand),
+}
+
+#[derive(Debug, PartialEq)]
+enum Piece {
+    /// a character that must be there, in lowercase on ILIKE
+    Char(char),
+    /// `_`, any single character
+    One,
+    /// `%`, any text, even an empty one
+    Any,
+}
+
+enum Operand {
+    /// position of the value on the line and its declared type
+    Column(usize, Option<ColumnType>),
+    /// quoted literal, always compared as text
+    Text(String),
+    /// parsed value and the literal as written
+    Number(Value, String),
+    /// unquoted literal that is not a number, compared as text against other non numeric values
+    Word(String),
+    /// literal already read with the type of the field it is compared with
+    Typed(Value),
+    /// arithmetic or concatenation, computed on each line
+    Computed(Box<Operand>, BinaryOperator, Box<Operand>),
+    /// -x
+    Negative(Box<Operand>),
+    /// DATE_ADD, with the value, the amount to add and its unit
+    DateAdd(Box<Operand>, Box<Operand>, DateUnit),
+    Extract(DateUnit, Box<Operand>),
+    DateTrunc(DateUnit, Box<Operand>),
+    Null,
+}
+
+/// An expression of the projection compiled against the headers of the lines.
+///
+/// It is evaluated the same way as the operands of a condition: `+ - * / %` on numbers,
+/// integers while both sides are integers, and `||` joining the text of both sides.
+/// The date functions read an undeclared field or a text as a date, a timestamp or a time.
+pub struct Scalar {
+    operand: Operand,
+}
+
+/// Compiles a WHERE condition, resolving each column with the table `indexes`
+/// and its declared type on `types`. Values of different types are compared following `coercion`.
+///
+/// # Errors
+///
+/// - `MiniSQLError::InvalidColumn` if the condition references a missing field.
+/// - `MiniSQLError::InvalidSyntax` if a part of the condition can not be evaluated as true or false,
+///   or a literal does not fit the type of the field it is compared with.
+pub fn compile_condition(
+    condition: &Option<Expr>,
     indexes: &HashMap<String, usize>,
-    line: &[String],
-) -> Result<bool, MiniSQLError> {
-    if condition.is_empty() {
-        return Ok(true);
-    }
-    let scope = calculate_scope(condition, start, end)?;
+    types: &[Option<ColumnType>],
+    coercion: Coercion,
+) -> Result<Condition, MiniSQLError> {
+    let root = match condition {
+        Some(condition) => Some(compile_node(condition, indexes, types)?),
+        None => None,
+    };
+    Ok(Condition { root, coercion })
+}
 
-    // buscamos si tiene OR para dividir la condicion
-    let (had_or, value) = search_or(&scope, condition, start, end, indexes, line)?;
-    if had_or {
-        return Ok(value);
-    }
+/// Compiles an expression of the projection, resolving each column with `indexes`
+/// and its declared type on `types`
+///
+/// # Errors
+///
+/// - `MiniSQLError::InvalidColumn` if the expression references a missing field.
+/// - `MiniSQLError::InvalidSyntax` if a part of the expression is not a value.
+pub fn compile_scalar(
+    expr: &Expr,
+    indexes: &HashMap<String, usize>,
+    types: &[Option<ColumnType>],
+) -> Result<Scalar, MiniSQLError> {
+    Ok(Scalar {
+        operand: compile_operand(expr, indexes, types)?,
+    })
+}
 
-    // buscamos si tiene AND para dividir la condicion
-    let (had_and, value) = search_and(&scope, condition, start, end, indexes, line)?;
-    if had_and {
-        return Ok(value);
+impl Condition {
+    /// Returns whether the line matches the condition, an unknown result does not match
+    ///
+    /// # Errors
+    ///
+    /// - `MiniSQLError::InvalidSyntax` if a value of the line can not be compared, or
+    ///   on `Coercion::Strict` if it does not fit the type of the other side.
+    pub fn evaluate(&self, line: &[String]) -> Result<bool, MiniSQLError> {
+        match &self.root {
+            Some(node) => Ok(resolve_condition(node, line, self.coercion)? == Some(true)),
+            None => Ok(true),
+        }
     }
+}
 
-    let (was_unary, value) = resolve_unary_operation(condition, start, end, indexes, line)?;
-    if was_unary {
-        return Ok(value);
+impl Scalar {
+    /// Value of the expression for the line as it is stored, NULL if one of the fields it uses is NULL
+    pub fn evaluate(&self, line: &[String]) -> Result<String, MiniSQLError> {
+        match get_scalar_value(&self.operand, line)? {
+            Some(value) => Ok(value.to_string()),
+            None => Ok(NULL.to_string()),
+        }
     }
+}
 
-    let broken_query_part = &condition[start..end];
-    Err(MiniSQLError::InvalidSyntax(format!(
-        "program was unable to parse query on condition: {} ",
-        broken_query_part.join(" ")
-    )))
-}
-
-fn calculate_scope(
-    condition: &[String],
-    start: usize,
-    end: usize,
-) -> Result<Vec<usize>, MiniSQLError> {
-    let mut scope: Vec<usize> = Vec::new();
-    let mut has_parenthesis = false;
-    let mut parenthesis_stack = 0;
-    for index in start..end {
-        if let Some(condition_part) = condition.get(index) {
-            if condition_part == "(" {
-                if !has_parenthesis {
-                    has_parenthesis = true;
-                    continue;
-                }
-                parenthesis_stack += 1;
-                continue;
-            } else if condition_part == ")" {
-                if !has_parenthesis {
-                    let broken_query_part = &condition[start..end];
-                    return Err(MiniSQLError::InvalidSyntax(format!(
-                        "invalid parenthesis combination: {} ",
-                        broken_query_part.join(" ")
-                    )));
-                }
-                if parenthesis_stack == 0 {
-                    has_parenthesis = false
-                } else {
-                    parenthesis_stack -= 1
-                }
-                continue;
-            } else {
-                if has_parenthesis {
-                    continue;
+fn compile_node(
+    condition: &Expr,
+    indexes: &HashMap<String, usize>,
+    types: &[Option<ColumnType>],
+) -> Result<Node, MiniSQLError> {
+    match condition {
+        Expr::Binary {
+            left,
+            operator: BinaryOperator::Or,
+            right,
+        } => Ok(Node::Or(
+            Box::new(compile_node(left, indexes, types)?),
+            Box::new(compile_node(right, indexes, types)?),
+        )),
+        Expr::Binary {
+            left,
+            operator: BinaryOperator::And,
+            right,
+        } => Ok(Node::And(
+            Box::new(compile_node(left, indexes, types)?),
+            Box::new(compile_node(right, indexes, types)?),
+        )),
+        Expr::Binary {
+            left,
+            operator,
+            right,
+        } if operator.is_comparison() => compile_comparison(left, *operator, right, indexes, types),
+        Expr::Unary {
+            operator: UnaryOperator::Not,
+            expr,
+        } => Ok(Node::Not(Box::new(compile_node(expr, indexes, types)?))),
+        Expr::Literal(Literal::Boolean(value)) => Ok(Node::Constant(Some(*value))),
+        Expr::Literal(Literal::Null) => Ok(Node::Constant(None)),
+        Expr::IsNull { expr, negated } => Ok(Node::IsNull(
+            compile_operand(expr, indexes, types)?,
+            *negated,
+        )),
+        Expr::Like {
+            expr,
+            pattern,
+            escape,
+            negated,
+            case_insensitive,
+        } => {
+            let escape = escape.as_ref().and_then(|escape| escape.chars().next());
+            let pattern = match compile_operand(pattern, indexes, types)? {
+                Operand::Text(value) | Operand::Word(value) | Operand::Number(_, value) => {
+                    LikePattern::Compiled(compile_pattern(&value, escape, *case_insensitive)?)
                 }
-                scope.push(index);
+                operand => LikePattern::Dynamic(operand),
+            };
+            Ok(Node::Like(
+                compile_operand(expr, indexes, types)?,
+                Like {
+                    pattern,
+                    escape,
+                    negated: *negated,
+                    case_insensitive: *case_insensitive,
+                },
+            ))
+        }
+        Expr::InList {
+            expr,
+            list,
+            negated,
+        } => compile_in(expr, list, *negated, indexes, types),
+        Expr::Between {
+            expr,
+            low,
+            high,
+            negated,
+        } => {
+            let between = Node::And(
+                Box::new(compile_comparison(
+                    expr,
+                    BinaryOperator::GreaterEqual,
+                    low,
+                    indexes,
+                    types,
+                )?),
+                Box::new(compile_comparison(
+                    expr,
+                    BinaryOperator::LessEqual,
+                    high,
+                    indexes,
+                    types,
+                )?),
+            );
+            match negated {
+                true => Ok(Node::Not(Box::new(between))),
+                false => Ok(between),
             }
-        } else {
-            let broken_query_part = &condition[start..end];
-            return Err(MiniSQLError::InvalidSyntax(format!(
-                "program found unexpected error while searching for parenthesis: {} ",
-                broken_query_part.join(" ")
-            )));
         }
+        _ => Err(MiniSQLError::InvalidSyntax(format!(
+            "program was unable to parse query on alone condition: {} ",
+            condition
+        ))),
     }
-
-    Ok(scope)
 }
 
-fn search_and(
-    scope: &Vec<usize>,
-    condition: &[String],
-    start: usize,
-    end: usize,
+/// A field against a list of literals goes through a hash set, any other list
+/// is compared value by value as a chain of `=` joined by `OR`
+fn compile_in(
+    expr: &Expr,
+    list: &[Expr],
+    negated: bool,
     indexes: &HashMap<String, usize>,
-    line: &[String],
-) -> Result<(bool, bool), MiniSQLError> {
-    for &part_index in scope {
-        if let Some(part) = condition.get(part_index) {
-            if part == "AND" {
-                let right = get_query(condition, start, part_index, indexes, line)?;
-                let left = get_query(condition, part_index + 1, end, indexes, line)?;
-                return Ok((true, right && left));
+    types: &[Option<ColumnType>],
+) -> Result<Node, MiniSQLError> {
+    let operand = compile_operand(expr, indexes, types)?;
+    let literals = list.iter().all(|value| matches!(value, Expr::Literal(_)));
+    let (index, column_type) = match operand {
+        Operand::Column(index, column_type) if literals => (index, column_type),
+        _ => {
+            let mut node: Option<Node> = None;
+            for value in list {
+                let equal = compile_comparison(expr, BinaryOperator::Equal, value, indexes, types)?;
+                node = Some(match node {
+                    Some(node) => Node::Or(Box::new(node), Box::new(equal)),
+                    None => equal,
+                });
             }
-        } else {
-            let broken_query_part = &condition[start..end];
-            return Err(MiniSQLError::InvalidSyntax(format!(
-                "program found unexpected error while searching AND on query condition: {} ",
-                broken_query_part.join(" ")
-            )));
-        } // como siempre nos manejamos dentro del len no es necesario revisar esto
+            let node = node.unwrap_or(Node::Constant(Some(false)));
+            return match negated {
+                true => Ok(Node::Not(Box::new(node))),
+                false => Ok(node),
+            };
+        }
+    };
+
+    let mut in_list = InList {
+        column_type,
+        texts: HashSet::new(),
+        numbers: HashSet::new(),
+        has_null: false,
+        negated,
+    };
+    for value in list {
+        match (compile_operand(value, indexes, types)?, column_type) {
+            (
+                Operand::Text(value) | Operand::Word(value) | Operand::Number(_, value),
+                Some(column_type),
+            ) => {
+                if let Operand::Typed(typed) = type_literal(Operand::Text(value), column_type)? {
+                    in_list.texts.insert(in_key(&typed));
+                }
+            }
+            (Operand::Number(number, _), None) => {
+                in_list.numbers.extend(number.number_key());
+            }
+            (Operand::Text(value) | Operand::Word(value), None) => {
+                in_list.texts.insert(value);
+            }
+            (Operand::Null, _) => in_list.has_null = true,
+            _ => {}
+        }
     }
+    Ok(Node::In(index, in_list))
+}
 
-    Ok((false, false))
+fn compile_comparison(
+    left: &Expr,
+    operator: BinaryOperator,
+    right: &Expr,
+    indexes: &HashMap<String, usize>,
+    types: &[Option<ColumnType>],
+) -> Result<Node, MiniSQLError> {
+    let mut left = compile_operand(left, indexes, types)?;
+    let mut right = compile_operand(right, indexes, types)?;
+
+    // un literal comparado contra un campo tipado se lee una sola vez con el tipo del campo
+    if let Some(column_type) = declared_type(&left) {
+        right = type_literal(right, column_type)?;
+    }
+    if let Some(column_type) = declared_type(&right) {
+        left = type_literal(left, column_type)?;
+    }
+    Ok(Node::Comparison(left, operator, right))
 }
 
-fn search_or(
-    scope: &Vec<usize>,
-    condition: &[String],
-    start: usize,
-    end: usize,
+fn compile_operand(
+    operand: &Expr,
     indexes: &HashMap<String, usize>,
-    line: &[String],
-) -> Result<(bool, bool), MiniSQLError> {
-    let mut count = start;
-    for &part_index in scope {
-        if let Some(part) = condition.get(part_index) {
-            if part == "OR" {
-                let right = get_query(condition, start, count, indexes, line)?;
-                let left = get_query(condition, count + 1, end, indexes, line)?;
-                return Ok((true, right || left));
+    types: &[Option<ColumnType>],
+) -> Result<Operand, MiniSQLError> {
+    match operand {
+        Expr::Column(name) => match indexes.get(name) {
+            Some(index) => Ok(Operand::Column(
+                *index,
+                types.get(*index).copied().flatten(),
+            )),
+            None => Err(MiniSQLError::InvalidColumn(format!(
+                "requested field [ {} ] could not be found",
+                name
+            ))),
+        },
+        Expr::Literal(Literal::String(value)) => Ok(Operand::Text(value.to_string())),
+        Expr::Literal(Literal::Null) => Ok(Operand::Null),
+        Expr::Literal(literal) => {
+            let value = literal.to_string();
+            match Value::parse_number(&value) {
+                Some(number) => Ok(Operand::Number(number, value)),
+                None => Ok(Operand::Word(value)),
             }
-            count += 1;
-        } else {
-            let broken_query_part = &condition[start..end];
-            return Err(MiniSQLError::InvalidSyntax(format!(
-                "program found unexpected error while searching OR on query condition: {} ",
-                broken_query_part.join(" ")
-            )));
-        } // como siempre nos manejamos dentro del len no es necesario revisar esto
+        }
+        // en HAVING cada agregado ya fue calculado y ocupa un campo mas de la linea
+        Expr::Aggregate(aggregate) => match indexes.get(&aggregate.to_string()) {
+            Some(index) => Ok(Operand::Column(
+                *index,
+                types.get(*index).copied().flatten(),
+            )),
+            None => Err(MiniSQLError::InvalidSyntax(format!(
+                "aggregate function {} can only be used on the projection or HAVING ",
+                aggregate
+            ))),
+        },
+        Expr::Binary {
+            left,
+            operator,
+            right,
+        } if !operator.is_comparison()
+            && !matches!(operator, BinaryOperator::And | BinaryOperator::Or) =>
+        {
+            Ok(Operand::Computed(
+                Box::new(compile_operand(left, indexes, types)?),
+                *operator,
+                Box::new(compile_operand(right, indexes, types)?),
+            ))
+        }
+        Expr::Unary {
+            operator: UnaryOperator::Minus,
+            expr,
+        } => Ok(Operand::Negative(Box::new(compile_operand(
+            expr, indexes, types,
+        )?))),
+        // NOW y CURRENT_DATE se leen una sola vez, valen lo mismo en todas las lineas
+        Expr::Function(Function::Now) => Ok(Operand::Typed(Value::Timestamp(Timestamp::now()))),
+        Expr::Function(Function::CurrentDate) => Ok(Operand::Typed(Value::Date(Date::today()))),
+        Expr::Function(Function::DateAdd {
+            value,
+            amount,
+            unit,
+        }) => Ok(Operand::DateAdd(
+            Box::new(compile_operand(value, indexes, types)?),
+            Box::new(compile_operand(amount, indexes, types)?),
+            *unit,
+        )),
+        Expr::Function(Function::Extract { unit, value }) => Ok(Operand::Extract(
+            *unit,
+            Box::new(compile_operand(value, indexes, types)?),
+        )),
+        Expr::Function(Function::DateTrunc { unit, value }) => Ok(Operand::DateTrunc(
+            *unit,
+            Box::new(compile_operand(value, indexes, types)?),
+        )),
+        _ => Err(MiniSQLError::InvalidSyntax(format!(
+            "invalid value on condition: {} ",
+            operand
+        ))),
     }
-    Ok((false, false))
 }
 
-fn resolve_unary_operation(
-    condition: &[String],
-    start: usize,
-    end: usize,
-    indexes: &HashMap<String, usize>,
-    line: &[String],
-) -> Result<(bool, bool), MiniSQLError> {
-    if let Some(part) = condition.get(start) {
-        if part == "NOT" {
-            Ok((true, !get_query(condition, start + 1, end, indexes, line)?))
-        } else if part == "(" {
-            if let Some(last) = condition.get(end - 1) {
-                if last == ")" {
-                    return Ok((
-                        true,
-                        get_query(condition, start + 1, end - 1, indexes, line)?,
-                    ));
-                } else {
-                    let broken_query_part = &condition[start..end];
-                    Err(MiniSQLError::InvalidSyntax(format!(
-                        "Invalid query, broken condition at: {} consider adding a ')'",
-                        broken_query_part.join(" ")
-                    )))
+fn declared_type(operand: &Operand) -> Option<ColumnType> {
+    match operand {
+        Operand::Column(_, column_type) => *column_type,
+        _ => None,
+    }
+}
+
+/// A number that does not fit a numeric field is kept with its own type and promoted
+/// when compared, so `stock > 2.5` on an INTEGER field compares 2.5 as a real
+fn type_literal(operand: Operand, column_type: ColumnType) -> Result<Operand, MiniSQLError> {
+    match operand {
+        Operand::Text(value) | Operand::Word(value) | Operand::Number(_, value) => {
+            match column_type.parse(&value) {
+                Some(typed) => Ok(Operand::Typed(typed)),
+                None if column_type.is_numeric() && Value::parse_number(&value).is_some() => {
+                    Ok(Operand::Typed(Value::infer(&value)))
                 }
-            } else {
-                Ok((false, false))
-            } // no deberia ocurrir, pero levantamos false para que salte error
-        } else {
-            let value = analyze_condition(condition, start, end - 1, indexes, line)?;
-            return Ok((true, value));
+                None => Err(MiniSQLError::InvalidSyntax(format!(
+                    "invalid value {} for a field of type {} ",
+                    value, column_type
+                ))),
+            }
         }
-    } else {
-        Ok((false, false))
-    } // no deberia ocurrir, pero levantamos false para que salte error
+        operand => Ok(operand),
+    }
 }
 
-fn analyze_condition(
-    condition: &[String],
-    start: usize,
-    end: usize,
-    indexes: &HashMap<String, usize>,
+/// Result of a node for the line, `None` when it is unknown
+fn resolve_condition(
+    node: &Node,
     line: &[String],
-) -> Result<bool, MiniSQLError> {
-    match end - start {
-        0 => execute_unary_condition(condition, start, end),
-        2 => execute_binary_condition(condition, start, end, line, indexes),
-        _ => {
-            let broken_query_part = &condition[start..end];
-            Err(MiniSQLError::InvalidSyntax(format!(
-                "program was unable to execute comparison between: {} ",
-                broken_query_part.join(" ")
-            )))
+    coercion: Coercion,
+) -> Result<Option<bool>, MiniSQLError> {
+    match node {
+        Node::Constant(value) => Ok(*value),
+        Node::Not(node) => Ok(resolve_condition(node, line, coercion)?.map(|value| !value)),
+        // un lado falso alcanza para que AND sea falso aunque el otro sea desconocido
+        Node::And(left, right) => match resolve_condition(left, line, coercion)? {
+            Some(false) => Ok(Some(false)),
+            Some(true) => resolve_condition(right, line, coercion),
+            None => match resolve_condition(right, line, coercion)? {
+                Some(false) => Ok(Some(false)),
+                _ => Ok(None),
+            },
+        },
+        // y un lado verdadero alcanza para que OR sea verdadero
+        Node::Or(left, right) => match resolve_condition(left, line, coercion)? {
+            Some(true) => Ok(Some(true)),
+            Some(false) => resolve_condition(right, line, coercion),
+            None => match resolve_condition(right, line, coercion)? {
+                Some(true) => Ok(Some(true)),
+                _ => Ok(None),
+            },
+        },
+        Node::Comparison(left, operator, right) => {
+            execute_binary_condition(left, *operator, right, line, coercion)
+        }
+        Node::Like(operand, like) => execute_like(operand, like, line),
+        Node::In(index, in_list) => execute_in(*index, in_list, line, coercion),
+        Node::IsNull(operand, negated) => {
+            Ok(Some(get_text_value(operand, line)?.is_none() != *negated))
         }
     }
 }
 
-fn execute_unary_condition(
-    condition: &[String],
-    start: usize,
-    end: usize,
-) -> Result<bool, MiniSQLError> {
-    if let Some(part) = condition.get(start) {
-        match part.as_str() {
-            "true" => Ok(true),
-            "false" => Ok(false),
-            _ => {
-                let broken_query_part = &condition[start..end];
-                Err(MiniSQLError::InvalidSyntax(format!(
-                    "program was unable to parse query on alone condition: {} ",
-                    broken_query_part.join(" ")
+/// A NULL field is unknown, with or without NOT, and so is a value not found on a list with a NULL.
+/// A stored value that does not fit the type of its field is looked up as it is written.
+fn execute_in(
+    index: usize,
+    in_list: &InList,
+    line: &[String],
+    coercion: Coercion,
+) -> Result<Option<bool>, MiniSQLError> {
+    let text = match line.get(index) {
+        Some(text) => text,
+        None => {
+            return Err(MiniSQLError::InvalidSyntax(
+                "program found unexpected error while replacing value from condition".to_string(),
+            ))
+        }
+    };
+    if is_null(text, in_list.column_type) {
+        return Ok(None);
+    }
+    let found = match in_list.column_type {
+        Some(column_type) => match column_type.parse(text) {
+            Some(value) => in_list.texts.contains(&in_key(&value)),
+            None if coercion == Coercion::Implicit => in_list.texts.contains(text),
+            None => {
+                return Err(MiniSQLError::InvalidSyntax(format!(
+                    "invalid type comparison, {} is not a valid {}",
+                    text, column_type
                 )))
             }
+        },
+        None => {
+            in_list.texts.contains(text)
+                || Value::parse_number(text)
+                    .and_then(|number| number.number_key())
+                    .is_some_and(|key| in_list.numbers.contains(&key))
         }
-    } else {
-        let broken_query_part = &condition[start..end];
-        Err(MiniSQLError::InvalidSyntax(format!(
-            "program found unexpected error while parsing query on condition: {} ",
-            broken_query_part.join(" ")
-        )))
+    };
+    if !found && in_list.has_null {
+        return Ok(None);
     }
+    Ok(Some(found != in_list.negated))
 }
 
-fn execute_binary_condition(
-    condition: &[String],
-    start: usize,
-    end: usize,
+/// Text a typed value is looked up with on an IN list, equal numbers give the same text
+fn in_key(value: &Value) -> String {
+    value.number_key().unwrap_or_else(|| value.to_string())
+}
+
+/// A NULL value or pattern is unknown, with or without NOT
+fn execute_like(
+    operand: &Operand,
+    like: &Like,
     line: &[String],
-    indexes: &HashMap<String, usize>,
-) -> Result<bool, MiniSQLError> {
-    let val1 = get_cond_value(condition, start, indexes, line)?;
-    let val2 = get_cond_value(condition, start + 2, indexes, line)?;
-    let val1_numeric = val1.parse::<i32>();
-    let val2_numeric = val2.parse::<i32>();
-
-    if val1_numeric.is_ok() && val2_numeric.is_ok() {
-        if let Some(operator) = condition.get(start + 1) {
-            let num1 = match val1_numeric {
-                Ok(val1) => val1,
-                Err(_) => {
-                    let broken_query_part = &condition[start..end];
+) -> Result<Option<bool>, MiniSQLError> {
+    let text = match get_text_value(operand, line)? {
+        Some(text) if like.case_insensitive => text.to_lowercase(),
+        Some(text) => text,
+        None => return Ok(None),
+    };
+    let dynamic: Vec<Piece>;
+    let pieces = match &like.pattern {
+        LikePattern::Compiled(pieces) => pieces,
+        LikePattern::Dynamic(pattern) => match get_text_value(pattern, line)? {
+            Some(pattern) => {
+                dynamic = compile_pattern(&pattern, like.escape, like.case_insensitive)?;
+                &dynamic
+            }
+            None => return Ok(None),
+        },
+    };
+    let text: Vec<char> = text.chars().collect();
+    Ok(Some(matches_pattern(pieces, &text) != like.negated))
+}
+
+/// Splits a LIKE pattern into the pieces to match. The escape character makes the next one literal.
+fn compile_pattern(
+    pattern: &str,
+    escape: Option<char>,
+    case_insensitive: bool,
+) -> Result<Vec<Piece>, MiniSQLError> {
+    let mut pieces: Vec<Piece> = vec![];
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        let literal = if Some(c) == escape {
+            match chars.next() {
+                Some(escaped) => escaped,
+                None => {
                     return Err(MiniSQLError::InvalidSyntax(format!(
-                        "program found unexpected error while parsing number to int: {} ",
-                        broken_query_part.join(" ")
-                    )));
+                        "LIKE pattern {} can not end with its escape character ",
+                        pattern
+                    )))
                 }
-            };
+            }
+        } else if c == '%' {
+            // varios % seguidos equivalen a uno solo
+            if pieces.last() != Some(&Piece::Any) {
+                pieces.push(Piece::Any);
+            }
+            continue;
+        } else if c == '_' {
+            pieces.push(Piece::One);
+            continue;
+        } else {
+            c
+        };
+        if case_insensitive {
+            pieces.extend(literal.to_lowercase().map(Piece::Char));
+        } else {
+            pieces.push(Piece::Char(literal));
+        }
+    }
+    Ok(pieces)
+}
 
-            let num2 = match val2_numeric {
-                Ok(val2) => val2,
-                Err(_) => {
-                    let broken_query_part = &condition[start..end];
-                    return Err(MiniSQLError::InvalidSyntax(format!(
-                        "program found unexpected error while parsing number to int: {} ",
-                        broken_query_part.join(" ")
-                    )));
+/// Matches the text going back to the last `%` when a piece does not fit,
+/// so each `%` takes as few characters as possible
+fn matches_pattern(pieces: &[Piece], text: &[char]) -> bool {
+    let (mut piece, mut position) = (0, 0);
+    let mut last_any: Option<(usize, usize)> = None;
+    while position < text.len() {
+        match pieces.get(piece) {
+            Some(Piece::Any) => {
+                last_any = Some((piece, position));
+                piece += 1;
+            }
+            Some(Piece::One) => {
+                piece += 1;
+                position += 1;
+            }
+            Some(Piece::Char(c)) if *c == text[position] => {
+                piece += 1;
+                position += 1;
+            }
+            _ => match last_any {
+                // el ultimo % toma un caracter mas y se vuelve a intentar
+                Some((any, start)) => {
+                    last_any = Some((any, start + 1));
+                    piece = any + 1;
+                    position = start + 1;
                 }
-            };
+                None => return false,
+            },
+        }
+    }
+    pieces[piece..].iter().all(|piece| *piece == Piece::Any)
+}
 
-            match operator.as_str() {
-                "=" => Ok(num1 == num2),
-                "!=" | "<>" => Ok(num1 != num2),
-                ">" => Ok(num1 > num2),
-                ">=" => Ok(num1 >= num2),
-                "<=" => Ok(num1 <= num2),
-                "<" => Ok(num1 < num2),
-                _ => Err(MiniSQLError::InvalidSyntax(format!(
-                    "invalid operand for comparison: {} ",
-                    operator
-                ))),
+/// A comparison with a NULL value is unknown
+fn execute_binary_condition(
+    left: &Operand,
+    operator: BinaryOperator,
+    right: &Operand,
+    line: &[String],
+    coercion: Coercion,
+) -> Result<Option<bool>, MiniSQLError> {
+    if matches!(left, Operand::Null) || matches!(right, Operand::Null) {
+        return Ok(None);
+    }
+    if is_computed(left) || is_computed(right) {
+        return execute_computed_condition(left, operator, right, line, coercion);
+    }
+    if is_typed(left) || is_typed(right) {
+        return execute_typed_condition(left, operator, right, line, coercion);
+    }
+
+    let val1 = get_cond_value(left, line)?;
+    let val2 = get_cond_value(right, line)?;
+    if is_null_field(left, val1) || is_null_field(right, val2) {
+        return Ok(None);
+    }
+
+    // un literal entre comillas siempre se compara como texto, aunque parezca un numero
+    if matches!(left, Operand::Text(_)) || matches!(right, Operand::Text(_)) {
+        return compare(val1, val2, operator).map(Some);
+    }
+
+    match (
+        get_numeric_value(left, val1),
+        get_numeric_value(right, val2),
+    ) {
+        (Some(num1), Some(num2)) => match num1.compare(&num2) {
+            Some(ordering) => compare(&ordering, &Ordering::Equal, operator).map(Some),
+            None => Ok(None),
+        },
+        (None, None) => compare(val1, val2, operator).map(Some),
+        // un numero contra una palabra se compara como texto
+        _ if coercion == Coercion::Implicit => compare(val1, val2, operator).map(Some),
+        _ => Err(mismatch(val1, val2)),
+    }
+}
+
+fn mismatch(val1: impl Display, val2: impl Display) -> MiniSQLError {
+    MiniSQLError::InvalidSyntax(format!("invalid type comparison, {} and {} ", val1, val2))
+}
+
+/// Compares both sides as they are stored, unknown if one of them is NULL
+fn compare_as_text(
+    left: &Operand,
+    operator: BinaryOperator,
+    right: &Operand,
+    line: &[String],
+) -> Result<Option<bool>, MiniSQLError> {
+    match (get_text_value(left, line)?, get_text_value(right, line)?) {
+        (Some(val1), Some(val2)) => compare(&val1, &val2, operator).map(Some),
+        _ => Ok(None),
+    }
+}
+
+fn is_typed(operand: &Operand) -> bool {
+    matches!(operand, Operand::Column(_, Some(_)) | Operand::Typed(_))
+}
+
+fn is_computed(operand: &Operand) -> bool {
+    matches!(
+        operand,
+        Operand::Computed(..)
+            | Operand::Negative(_)
+            | Operand::DateAdd(..)
+            | Operand::Extract(..)
+            | Operand::DateTrunc(..)
+    )
+}
+
+/// Whether the text read for an operand is a NULL stored on a field
+fn is_null_field(operand: &Operand, text: &str) -> bool {
+    matches!(operand, Operand::Column(_, column_type) if is_null(text, *column_type))
+}
+
+/// Compares the values of both sides once computed, unknown if one of them is NULL.
+/// Values that are not comparable, such as a number and a text, are compared as text.
+fn execute_computed_condition(
+    left: &Operand,
+    operator: BinaryOperator,
+    right: &Operand,
+    line: &[String],
+    coercion: Coercion,
+) -> Result<Option<bool>, MiniSQLError> {
+    let (val1, val2) = match (
+        get_scalar_value(left, line)?,
+        get_scalar_value(right, line)?,
+    ) {
+        (Some(val1), Some(val2)) => (val1, val2),
+        _ => return Ok(None),
+    };
+    compare_values(&val1, operator, &val2, coercion)
+}
+
+fn compare_values(
+    val1: &Value,
+    operator: BinaryOperator,
+    val2: &Value,
+    coercion: Coercion,
+) -> Result<Option<bool>, MiniSQLError> {
+    match val1.compare(val2) {
+        Some(ordering) => compare(&ordering, &Ordering::Equal, operator).map(Some),
+        None if coercion == Coercion::Implicit => {
+            compare(&val1.to_string(), &val2.to_string(), operator).map(Some)
+        }
+        None => Err(mismatch(val1, val2)),
+    }
+}
+
+/// Text of an operand for the current line as it is stored, `None` if it is NULL
+fn get_text_value(operand: &Operand, line: &[String]) -> Result<Option<String>, MiniSQLError> {
+    match operand {
+        Operand::Column(..) => {
+            let text = get_cond_value(operand, line)?;
+            if is_null_field(operand, text) {
+                return Ok(None);
             }
-        } else {
-            let broken_query_part = &condition[start..end];
-            Err(MiniSQLError::InvalidSyntax(format!(
-                "program found unexpected error while parsing query on condition: {} ",
-                broken_query_part.join(" ")
-            )))
+            Ok(Some(text.to_string()))
         }
-    } else if val1_numeric.is_err() && val2_numeric.is_err() {
-        if let Some(operator) = condition.get(start + 1) {
-            match operator.as_str() {
-                "=" => return Ok(val1 == val2),
-                "!=" | "<>" => return Ok(val1 != val2),
-                ">" => return Ok(val1 > val2),
-                ">=" => return Ok(val1 >= val2),
-                "<=" => return Ok(val1 <= val2),
-                "<" => return Ok(val1 < val2),
-                _ => {
-                    return Err(MiniSQLError::InvalidSyntax(format!(
-                        "invalid operand for comparison: {} ",
-                        operator
-                    )))
+        Operand::Text(value) | Operand::Word(value) | Operand::Number(_, value) => {
+            Ok(Some(value.to_string()))
+        }
+        operand => Ok(get_scalar_value(operand, line)?.map(|value| value.to_string())),
+    }
+}
+
+/// Value of an operand for the current line, `None` if it uses a NULL.
+///
+/// Typed fields are read with their type and undeclared ones as integers, reals or text.
+fn get_scalar_value(operand: &Operand, line: &[String]) -> Result<Option<Value>, MiniSQLError> {
+    match operand {
+        Operand::Computed(left, operator, right) => {
+            match (
+                get_scalar_value(left, line)?,
+                get_scalar_value(right, line)?,
+            ) {
+                (Some(left), Some(right)) => Ok(Some(calculate(left, *operator, right)?)),
+                _ => Ok(None),
+            }
+        }
+        Operand::Negative(operand) => match get_scalar_value(operand, line)? {
+            Some(Value::Integer(number)) => match number.checked_neg() {
+                Some(number) => Ok(Some(Value::Integer(number))),
+                None => Err(overflow(&format!("-{}", number))),
+            },
+            Some(Value::Real(number)) => Ok(Some(Value::Real(-number))),
+            Some(Value::Decimal(number)) => match number.checked_neg() {
+                Some(number) => Ok(Some(Value::Decimal(number))),
+                None => Err(overflow(&format!("-{}", number))),
+            },
+            Some(value) => Err(MiniSQLError::InvalidSyntax(format!(
+                "unable to apply - to {} ",
+                value
+            ))),
+            None => Ok(None),
+        },
+        Operand::DateAdd(value, amount, unit) => match (
+            get_scalar_value(value, line)?,
+            get_scalar_value(amount, line)?,
+        ) {
+            (Some(value), Some(amount)) => Ok(Some(date_add(value, amount, *unit)?)),
+            _ => Ok(None),
+        },
+        Operand::Extract(unit, value) => match get_scalar_value(value, line)? {
+            Some(value) => {
+                let value = as_temporal(value)?;
+                match value.extract(*unit) {
+                    Some(part) => Ok(Some(Value::Integer(part))),
+                    None => Err(MiniSQLError::InvalidSyntax(format!(
+                        "unable to extract {} from {} ",
+                        unit, value
+                    ))),
                 }
             }
-        } else {
-            let broken_query_part = &condition[start..end];
-            return Err(MiniSQLError::InvalidSyntax(format!(
-                "program found unexpected error while parsing query on condition: {} ",
-                broken_query_part.join(" ")
-            )));
+            None => Ok(None),
+        },
+        Operand::DateTrunc(unit, value) => match get_scalar_value(value, line)? {
+            Some(value) => {
+                let value = as_temporal(value)?;
+                match value.truncate(*unit) {
+                    Some(truncated) => Ok(Some(truncated)),
+                    None => Err(MiniSQLError::InvalidSyntax(format!(
+                        "unable to truncate {} to {} ",
+                        value, unit
+                    ))),
+                }
+            }
+            None => Ok(None),
+        },
+        Operand::Null => Ok(None),
+        Operand::Typed(value) => Ok(Some(value.clone())),
+        Operand::Text(value) => Ok(Some(Value::Text(value.to_string()))),
+        Operand::Number(number, _) => Ok(Some(number.clone())),
+        Operand::Word(value) => match value.as_str() {
+            "true" => Ok(Some(Value::Boolean(true))),
+            "false" => Ok(Some(Value::Boolean(false))),
+            value => Ok(Some(Value::infer(value))),
+        },
+        Operand::Column(_, column_type) => {
+            let text = get_cond_value(operand, line)?;
+            if is_null(text, *column_type) {
+                return Ok(None);
+            }
+            match column_type {
+                Some(column_type) => match column_type.parse(text) {
+                    Some(value) => Ok(Some(value)),
+                    None => Err(MiniSQLError::InvalidSyntax(format!(
+                        "{} is not a valid {} ",
+                        text, column_type
+                    ))),
+                },
+                None => Ok(Some(Value::infer(text))),
+            }
         }
-    } else {
-        return Err(MiniSQLError::InvalidSyntax(
-            "invalid type comparison, number and string literal".to_string(),
-        ));
     }
 }
 
-fn get_cond_value(
-    condition: &[String],
-    index: usize,
-    indexes: &HashMap<String, usize>,
-    line: &[String],
-) -> Result<String, MiniSQLError> {
-    if let Some(part) = condition.get(index) {
-        if let Some(index) = indexes.get(part) {
-            if let Some(line_value) = line.get(*index) {
-                Ok(line_value.to_string())
-            } else {
-                Err(MiniSQLError::InvalidSyntax(
-                    "program found unexpected error while replacing value from condition"
-                        .to_string(),
-                ))
+/// Applies an arithmetic operator or `||`. Numbers are promoted first: two integers give an
+/// integer, so `7 / 2` is `3`, an integer and a decimal give an exact decimal, and any real on
+/// either side gives a real.
+fn calculate(left: Value, operator: BinaryOperator, right: Value) -> Result<Value, MiniSQLError> {
+    if operator == BinaryOperator::Concat {
+        return Ok(Value::Text(format!("{}{}", left, right)));
+    }
+    let written = format!("{} {} {}", left, operator, right);
+    let dividing = matches!(operator, BinaryOperator::Divide | BinaryOperator::Modulo);
+
+    match promote(&left, &right) {
+        Some((Value::Integer(a), Value::Integer(b))) => {
+            if b == 0 && dividing {
+                return Err(division_by_zero(&written));
             }
-        } else {
-            return Ok(part.as_str().replace("'", ""));
+            let result = match operator {
+                BinaryOperator::Add => a.checked_add(b),
+                BinaryOperator::Subtract => a.checked_sub(b),
+                BinaryOperator::Multiply => a.checked_mul(b),
+                BinaryOperator::Divide => a.checked_div(b),
+                _ => a.checked_rem(b),
+            };
+            match result {
+                Some(result) => Ok(Value::Integer(result)),
+                None => Err(overflow(&written)),
+            }
+        }
+        Some((Value::Decimal(a), Value::Decimal(b))) => {
+            if b.is_zero() && dividing {
+                return Err(division_by_zero(&written));
+            }
+            let result = match operator {
+                BinaryOperator::Add => a.checked_add(&b),
+                BinaryOperator::Subtract => a.checked_sub(&b),
+                BinaryOperator::Multiply => a.checked_mul(&b),
+                BinaryOperator::Divide => a.checked_div(&b),
+                _ => a.checked_rem(&b),
+            };
+            match result {
+                Some(result) => Ok(Value::Decimal(result)),
+                None => Err(overflow(&written)),
+            }
+        }
+        Some((Value::Real(a), Value::Real(b))) => {
+            if b == 0.0 && dividing {
+                return Err(division_by_zero(&written));
+            }
+            let result = match operator {
+                BinaryOperator::Add => a + b,
+                BinaryOperator::Subtract => a - b,
+                BinaryOperator::Multiply => a * b,
+                BinaryOperator::Divide => a / b,
+                _ => a % b,
+            };
+            if !result.is_finite() {
+                return Err(overflow(&written));
+            }
+            Ok(Value::Real(result))
         }
-    } else {
-        Err(MiniSQLError::InvalidSyntax(
+        _ => Err(MiniSQLError::InvalidSyntax(format!(
+            "unable to apply {} to {} and {} ",
+            operator, left, right
+        ))),
+    }
+}
+
+/// A date, time or timestamp, or a text that reads as one of them
+fn as_temporal(value: Value) -> Result<Value, MiniSQLError> {
+    if value.is_temporal() {
+        return Ok(value);
+    }
+    let text = value.to_string();
+    match Value::parse_temporal(&text) {
+        Some(value) => Ok(value),
+        None => Err(MiniSQLError::InvalidSyntax(format!(
+            "{} is not a valid date, time or timestamp ",
+            text
+        ))),
+    }
+}
+
+/// The amount of an interval must be an integer
+fn date_add(value: Value, amount: Value, unit: DateUnit) -> Result<Value, MiniSQLError> {
+    let value = as_temporal(value)?;
+    let result = match amount {
+        Value::Integer(amount) => value.add_interval(amount, unit),
+        _ => None,
+    };
+    match result {
+        Some(result) => Ok(result),
+        None => Err(MiniSQLError::InvalidSyntax(format!(
+            "unable to add INTERVAL {} {} to {} ",
+            amount, unit, value
+        ))),
+    }
+}
+
+fn overflow(written: &str) -> MiniSQLError {
+    MiniSQLError::InvalidSyntax(format!("numeric overflow on {} ", written))
+}
+
+fn division_by_zero(written: &str) -> MiniSQLError {
+    MiniSQLError::InvalidSyntax(format!("division by zero on {} ", written))
+}
+
+/// Compares by the declared types, an undeclared field is read with the type of the other side.
+/// A stored value that does not fit the type is compared as text, unless the coercion is strict.
+fn execute_typed_condition(
+    left: &Operand,
+    operator: BinaryOperator,
+    right: &Operand,
+    line: &[String],
+    coercion: Coercion,
+) -> Result<Option<bool>, MiniSQLError> {
+    let val1 = get_typed_value(left, declared_type(right), line);
+    let val2 = get_typed_value(right, declared_type(left), line);
+
+    let (val1, val2) = match (val1, val2) {
+        (Ok(Some(val1)), Ok(Some(val2))) => (val1, val2),
+        (Ok(_), Ok(_)) => return Ok(None),
+        (Err(err), _) | (_, Err(err)) if coercion == Coercion::Strict => return Err(err),
+        // un valor guardado que no es del tipo del campo se compara como texto
+        _ => return compare_as_text(left, operator, right, line),
+    };
+
+    compare_values(&val1, operator, &val2, coercion)
+}
+
+/// Typed value of an operand for the current line, `None` if it is NULL
+fn get_typed_value(
+    operand: &Operand,
+    other_type: Option<ColumnType>,
+    line: &[String],
+) -> Result<Option<Value>, MiniSQLError> {
+    if let Operand::Typed(value) = operand {
+        return Ok(Some(value.clone()));
+    }
+
+    let text = get_cond_value(operand, line)?;
+    if is_null_field(operand, text) {
+        return Ok(None);
+    }
+    let column_type = match (declared_type(operand), other_type) {
+        (Some(column_type), _) => column_type,
+        (None, Some(column_type)) => column_type,
+        (None, None) => return Ok(Some(Value::Text(text.to_string()))),
+    };
+    match column_type.parse(text) {
+        Some(value) => Ok(Some(value)),
+        None => Err(MiniSQLError::InvalidSyntax(format!(
+            "invalid type comparison, {} is not a valid {}",
+            text, column_type
+        ))),
+    }
+}
+
+fn compare<T: PartialOrd + ?Sized>(
+    val1: &T,
+    val2: &T,
+    operator: BinaryOperator,
+) -> Result<bool, MiniSQLError> {
+    match operator {
+        BinaryOperator::Equal => Ok(val1 == val2),
+        BinaryOperator::NotEqual => Ok(val1 != val2),
+        BinaryOperator::Greater => Ok(val1 > val2),
+        BinaryOperator::GreaterEqual => Ok(val1 >= val2),
+        BinaryOperator::LessEqual => Ok(val1 <= val2),
+        BinaryOperator::Less => Ok(val1 < val2),
+        _ => Err(MiniSQLError::InvalidSyntax(format!(
+            "invalid operand for comparison: {} ",
+            operator
+        ))),
+    }
+}
+
+/// Returns the text of an operand for the current line
+fn get_cond_value<'a>(operand: &'a Operand, line: &'a [String]) -> Result<&'a str, MiniSQLError> {
+    match operand {
+        Operand::Column(index, _) => match line.get(*index) {
+            Some(line_value) => Ok(line_value),
+            None => Err(MiniSQLError::InvalidSyntax(
+                "program found unexpected error while replacing value from condition".to_string(),
+            )),
+        },
+        Operand::Text(value) | Operand::Word(value) | Operand::Number(_, value) => Ok(value),
+        _ => Err(MiniSQLError::InvalidSyntax(
             "program found unexpected error while replacing value from condition".to_string(),
-        ))
+        )),
     }
 }
 
+/// Literals were already parsed on compilation, only line values are parsed here
+fn get_numeric_value(operand: &Operand, value: &str) -> Option<Value> {
+    match operand {
+        Operand::Number(number, _) => Some(number.clone()),
+        Operand::Column(_, _) => Value::parse_number(value),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+fn parse_condition(condition: &str) -> Expr {
+    use crate::parser::ast::Statement;
+    use crate::parser::grammar::parse;
+
+    match parse(&format!("SELECT * FROM t WHERE {}", condition)) {
+        Ok(Statement::Select(select)) => select.condition.unwrap(),
+        other => panic!("invalid test condition: {:?}", other),
+    }
+}
+
+#[cfg(test)]
+fn evaluate(
+    condition: &str,
+    line: &[String],
+    indexes: &HashMap<String, usize>,
+) -> Result<bool, MiniSQLError> {
+    compile_condition(
+        &Some(parse_condition(condition)),
+        indexes,
+        &[],
+        Coercion::Implicit,
+    )?
+    .evaluate(line)
+}
+
 #[cfg(test)]
 mod test_unary {
     use super::*;
@@ -341,41 +1117,31 @@ mod test_unary {
 
     #[test]
     fn test_execute_unary_condition_true() {
-        let condition = vec!["true".to_string()];
-        let result = execute_unary_condition(&condition, 0, 1);
+        let result = evaluate("true", &[], &HashMap::new());
         assert_eq!(result, Ok(true));
     }
     #[test]
     fn test_execute_unary_condition_false() {
-        let condition = vec!["false".to_string()];
-        let result = execute_unary_condition(&condition, 0, 1);
+        let result = evaluate("false", &[], &HashMap::new());
         assert_eq!(result, Ok(false));
     }
 
     #[test]
     fn test_execute_unary_condition_invalid() {
-        let condition = vec!["nombre".to_string()];
-        let result = execute_unary_condition(&condition, 0, 1);
+        let indexes = HashMap::from([("nombre".to_string(), 0)]);
+        let result = evaluate("nombre", &["pepe".to_string()], &indexes);
         assert_eq!(
             result,
-            Err(MiniSQLError::InvalidSyntax(format!(
-                "program was unable to parse query on alone condition: {} ",
-                &condition[0..1].join(" ")
-            )))
+            Err(MiniSQLError::InvalidSyntax(
+                "program was unable to parse query on alone condition: nombre ".to_string()
+            ))
         );
     }
 
     #[test]
-    fn test_execute_unary_condition_empty() {
-        let condition = vec![];
-        let result = execute_unary_condition(&condition, 0, 0);
-        assert_eq!(
-            result,
-            Err(MiniSQLError::InvalidSyntax(format!(
-                "program found unexpected error while parsing query on condition: {} ",
-                &condition[0..0].join(" ")
-            )))
-        );
+    fn test_missing_condition_applies_to_every_line() {
+        let condition = compile_condition(&None, &HashMap::new(), &[], Coercion::Implicit);
+        assert_eq!(condition.map(|c| c.evaluate(&[])), Ok(Ok(true)));
     }
 }
 
@@ -386,88 +1152,142 @@ mod test_binary {
 
     #[test]
     fn test_execute_binary_condition_hardcoded_equal_numbers() {
-        let condition = vec!["1".to_string(), "=".to_string(), "1".to_string()];
-        let indexes = HashMap::new();
-        let line = vec![];
-        let result = execute_binary_condition(&condition, 0, 2, &line, &indexes);
+        let result = evaluate("1 = 1", &[], &HashMap::new());
         assert_eq!(result, Ok(true));
     }
 
     #[test]
     fn test_execute_binary_condition_hardcoded_not_equal_numbers() {
-        let condition = vec!["1".to_string(), "=".to_string(), "2".to_string()];
-        let indexes = HashMap::new();
-        let line = vec![];
-        let result = execute_binary_condition(&condition, 0, 2, &line, &indexes);
+        let result = evaluate("1 = 2", &[], &HashMap::new());
         assert_eq!(result, Ok(false));
     }
 
     #[test]
     fn test_execute_binary_condition_hardcoded_equal_strings() {
-        let condition = vec!["'Pepe'".to_string(), "=".to_string(), "'Pepe'".to_string()];
-        let indexes = HashMap::new();
-        let line = vec![];
-        let result = execute_binary_condition(&condition, 0, 2, &line, &indexes);
+        let result = evaluate("'Pepe' = 'Pepe'", &[], &HashMap::new());
         assert_eq!(result, Ok(true));
     }
 
     #[test]
     fn test_execute_binary_condition_hardcoded_not_equal_strings() {
-        let condition = vec!["'Pepe'".to_string(), "=".to_string(), "'Pablo'".to_string()];
-        let indexes = HashMap::new();
-        let line = vec![];
-        let result = execute_binary_condition(&condition, 0, 2, &line, &indexes);
+        let result = evaluate("'Pepe' = 'Pablo'", &[], &HashMap::new());
         assert_eq!(result, Ok(false));
     }
 
     #[test]
     fn test_execute_binary_condition_equal_values() {
-        let condition = vec!["Nombre".to_string(), "=".to_string(), "'Pepe'".to_string()];
         let indexes = HashMap::from([
             ("ID".to_string(), 0),
             ("Nombre".to_string(), 1),
             ("Edad".to_string(), 2),
         ]);
-        let line: &[String] = &vec!["1002".to_string(), "Pepe".to_string(), "19".to_string()];
-        let result = execute_binary_condition(&condition, 0, 2, &line, &indexes);
+        let line: &[String] = &["1002".to_string(), "Pepe".to_string(), "19".to_string()];
+        let result = evaluate("Nombre = 'Pepe'", line, &indexes);
         assert_eq!(result, Ok(true));
     }
 
     #[test]
     fn test_execute_binary_condition_equal_values_number() {
-        let condition = vec!["Edad".to_string(), "=".to_string(), "19".to_string()];
         let indexes = HashMap::from([
             ("ID".to_string(), 0),
             ("Nombre".to_string(), 1),
             ("Edad".to_string(), 2),
         ]);
-        let line: &[String] = &vec!["1002".to_string(), "Pepe".to_string(), "19".to_string()];
-        let result = execute_binary_condition(&condition, 0, 2, &line, &indexes);
+        let line: &[String] = &["1002".to_string(), "Pepe".to_string(), "19".to_string()];
+        let result = evaluate("Edad = 19", line, &indexes);
         assert_eq!(result, Ok(true));
     }
 
+    #[test]
+    fn test_in_list_on_undeclared_field() {
+        let indexes = HashMap::from([("Nombre".to_string(), 0), ("Edad".to_string(), 1)]);
+        let line: &[String] = &["Pepe".to_string(), "019".to_string()];
+        assert_eq!(evaluate("Edad IN (18, 19)", line, &indexes), Ok(true));
+        assert_eq!(evaluate("Edad IN ('19')", line, &indexes), Ok(false));
+        assert_eq!(
+            evaluate("Nombre IN ('Juan', 'Pepe')", line, &indexes),
+            Ok(true)
+        );
+        assert_eq!(
+            evaluate("Nombre NOT IN ('Juan', 'Pepe')", line, &indexes),
+            Ok(false)
+        );
+        assert_eq!(evaluate("Edad - 1 IN (17, 18)", line, &indexes), Ok(true));
+        assert_eq!(evaluate("Edad IN (Edad, 5)", line, &indexes), Ok(true));
+    }
+
+    #[test]
+    fn test_undeclared_numbers_of_any_size() {
+        let indexes = HashMap::from([("telefono".to_string(), 0), ("monto".to_string(), 1)]);
+        let line: &[String] = &["5551234567".to_string(), "10.10".to_string()];
+        assert_eq!(evaluate("telefono > 999", line, &indexes), Ok(true));
+        assert_eq!(
+            evaluate("telefono = 5551234567.0", line, &indexes),
+            Ok(true)
+        );
+        assert_eq!(evaluate("monto = 10.1", line, &indexes), Ok(true));
+        assert_eq!(evaluate("monto + 0.2 = 10.3", line, &indexes), Ok(true));
+        assert_eq!(evaluate("monto IN (10.100, 2)", line, &indexes), Ok(true));
+    }
+
     #[test]
     fn test_execute_binary_condition_row_equals_row() {
-        let condition = vec![
-            "Nombre".to_string(),
-            "=".to_string(),
-            "Apellido".to_string(),
-        ];
         let indexes = HashMap::from([
             ("ID".to_string(), 0),
             ("Nombre".to_string(), 1),
             ("Apellido".to_string(), 2),
             ("Edad".to_string(), 3),
         ]);
-        let line: &[String] = &vec![
+        let line: &[String] = &[
             "1002".to_string(),
             "Natalia".to_string(),
             "Natalia".to_string(),
             "19".to_string(),
         ];
-        let result = execute_binary_condition(&condition, 0, 2, &line, &indexes);
+        let result = evaluate("Nombre = Apellido", line, &indexes);
         assert_eq!(result, Ok(true));
     }
+
+    #[test]
+    fn test_execute_binary_condition_quoted_number_is_text() {
+        let indexes = HashMap::from([("Edad".to_string(), 0)]);
+        let line: &[String] = &["19".to_string()];
+        let result = evaluate("Edad = '019'", line, &indexes);
+        assert_eq!(result, Ok(false));
+    }
+
+    #[test]
+    fn test_execute_binary_condition_number_and_text() {
+        let indexes = HashMap::from([("Nombre".to_string(), 0)]);
+        let line: &[String] = &["Pepe".to_string()];
+        assert_eq!(evaluate("Nombre > 5", line, &indexes), Ok(true));
+        assert_eq!(evaluate("Nombre = 5", line, &indexes), Ok(false));
+        assert!(evaluate("Nombre + 1 > 5", line, &indexes).is_err());
+
+        let condition = compile_condition(
+            &Some(parse_condition("Nombre > 5")),
+            &indexes,
+            &[],
+            Coercion::Strict,
+        );
+        assert_eq!(
+            condition.map(|condition| condition.evaluate(line)),
+            Ok(Err(MiniSQLError::InvalidSyntax(
+                "invalid type comparison, Pepe and 5 ".to_string()
+            )))
+        );
+    }
+
+    #[test]
+    fn test_compile_missing_column() {
+        let result = evaluate("Apellido = 'diaz'", &[], &HashMap::new());
+        assert_eq!(
+            result,
+            Err(MiniSQLError::InvalidColumn(
+                "requested field [ Apellido ] could not be found".to_string()
+            ))
+        );
+    }
 }
 
 #[cfg(test)]
@@ -476,34 +1296,650 @@ mod test_get_query {
 
     #[test]
     fn test_get_query_parenthesis() {
-        let condition_str = "( ( id = 5 ) AND nombre = 'pepe' OR edad = 23 ) AND edad = 5";
-        let condition: Vec<String> = condition_str.split_whitespace().map(String::from).collect();
-
-        let line_str = "5,pepe,45,123123123";
-        let line: Vec<String> = line_str.split_whitespace().map(String::from).collect();
-
         let mut indexes: HashMap<String, usize> = HashMap::new();
         indexes.insert("id".to_string(), 0);
         indexes.insert("nombre".to_string(), 1);
         indexes.insert("edad".to_string(), 2);
         indexes.insert("dni".to_string(), 3);
 
-        let mut should_apply = get_query(&condition, 0, condition.len(), &indexes, &line);
-        match should_apply {
-            Ok(should_apply) => {
-                assert_eq!(should_apply, true);
+        let condition = compile_condition(
+            &Some(parse_condition(
+                "( ( id = 5 ) AND nombre = 'pepe' OR edad = 23 ) AND edad = 5",
+            )),
+            &indexes,
+            &[],
+            Coercion::Implicit,
+        )
+        .unwrap();
+
+        let line_str = "5,pepe,5,123123123";
+        let line: Vec<String> = line_str.split(',').map(String::from).collect();
+        assert_eq!(condition.evaluate(&line), Ok(true));
+
+        let line_str = "5,carlos,5,123123123";
+        let line: Vec<String> = line_str.split(',').map(String::from).collect();
+        assert_eq!(condition.evaluate(&line), Ok(false));
+    }
+}
+
+#[cfg(test)]
+mod test_precedence {
+    use super::*;
+
+    /// Reference formula, evaluated directly without going through the parser
+    #[derive(Clone)]
+    enum Formula {
+        Atom(usize),
+        Not(Box<Formula>),
+        And(Box<Formula>, Box<Formula>),
+        Or(Box<Formula>, Box<Formula>),
+    }
+
+    const ATOMS: [&str; 3] = ["a", "b", "c"];
+
+    fn reference(formula: &Formula, values: &[bool]) -> bool {
+        match formula {
+            Formula::Atom(index) => values[*index],
+            Formula::Not(inner) => !reference(inner, values),
+            Formula::And(left, right) => reference(left, values) && reference(right, values),
+            Formula::Or(left, right) => reference(left, values) || reference(right, values),
+        }
+    }
+
+    fn precedence(formula: &Formula) -> u8 {
+        match formula {
+            Formula::Or(_, _) => 1,
+            Formula::And(_, _) => 2,
+            Formula::Not(_) => 3,
+            Formula::Atom(_) => 4,
+        }
+    }
+
+    /// Writes the formula with the least parenthesis the SQL precedence allows
+    fn minimal(formula: &Formula, min_precedence: u8) -> String {
+        let text = match formula {
+            Formula::Atom(index) => format!("{} = 1", ATOMS[*index]),
+            Formula::Not(inner) => format!("NOT {}", minimal(inner, 3)),
+            Formula::And(left, right) => format!("{} AND {}", minimal(left, 2), minimal(right, 3)),
+            Formula::Or(left, right) => format!("{} OR {}", minimal(left, 1), minimal(right, 2)),
+        };
+        if precedence(formula) < min_precedence {
+            return format!("( {} )", text);
+        }
+        text
+    }
+
+    /// Writes the formula with every group between parenthesis, nested ones included
+    fn grouped(formula: &Formula) -> String {
+        match formula {
+            Formula::Atom(index) => format!("( {} = 1 )", ATOMS[*index]),
+            Formula::Not(inner) => format!("( NOT {} )", grouped(inner)),
+            Formula::And(left, right) => format!("( {} AND {} )", grouped(left), grouped(right)),
+            Formula::Or(left, right) => format!("( {} OR {} )", grouped(left), grouped(right)),
+        }
+    }
+
+    /// Every formula with exactly `operators` NOT/AND/OR over the atoms
+    fn formulas(operators: usize) -> Vec<Formula> {
+        if operators == 0 {
+            return (0..ATOMS.len()).map(Formula::Atom).collect();
+        }
+        let mut result: Vec<Formula> = formulas(operators - 1)
+            .into_iter()
+            .map(|inner| Formula::Not(Box::new(inner)))
+            .collect();
+        for left_operators in 0..operators {
+            for left in formulas(left_operators) {
+                for right in formulas(operators - 1 - left_operators) {
+                    result.push(Formula::And(
+                        Box::new(left.clone()),
+                        Box::new(right.clone()),
+                    ));
+                    result.push(Formula::Or(Box::new(left.clone()), Box::new(right)));
+                }
+            }
+        }
+        result
+    }
+
+    fn assert_truth_table(formula: &Formula, text: &str) {
+        let indexes = HashMap::from([
+            ("a".to_string(), 0),
+            ("b".to_string(), 1),
+            ("c".to_string(), 2),
+        ]);
+        let condition = compile_condition(
+            &Some(parse_condition(text)),
+            &indexes,
+            &[],
+            Coercion::Implicit,
+        )
+        .unwrap();
+
+        for combination in 0..(1 << ATOMS.len()) {
+            let values: Vec<bool> = (0..ATOMS.len())
+                .map(|bit| combination & (1 << bit) != 0)
+                .collect();
+            let line: Vec<String> = values
+                .iter()
+                .map(|value| if *value { "1" } else { "0" }.to_string())
+                .collect();
+            assert_eq!(
+                condition.evaluate(&line),
+                Ok(reference(formula, &values)),
+                "condition: {} line: {:?}",
+                text,
+                line
+            );
+        }
+    }
+
+    #[test]
+    fn test_parenthesis_before_or() {
+        let indexes = HashMap::from([("a".to_string(), 0), ("b".to_string(), 1)]);
+        let condition = compile_condition(
+            &Some(parse_condition("( a = 1 ) OR b = 2")),
+            &indexes,
+            &[],
+            Coercion::Implicit,
+        )
+        .unwrap();
+        assert_eq!(
+            condition.evaluate(&["1".to_string(), "0".to_string()]),
+            Ok(true)
+        );
+        assert_eq!(
+            condition.evaluate(&["0".to_string(), "2".to_string()]),
+            Ok(true)
+        );
+        assert_eq!(
+            condition.evaluate(&["0".to_string(), "0".to_string()]),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn test_truth_table_minimal_parenthesis() {
+        for operators in 0..=4 {
+            for formula in formulas(operators) {
+                assert_truth_table(&formula, &minimal(&formula, 0));
             }
-            Err(_) => (),
         }
+    }
 
-        let line_str = "5,carlos,45,123123123";
-        let line: Vec<String> = line_str.split_whitespace().map(String::from).collect();
-        should_apply = get_query(&condition, 0, condition.len(), &indexes, &line);
-        match should_apply {
-            Ok(should_apply) => {
-                assert_eq!(should_apply, false);
+    #[test]
+    fn test_truth_table_nested_parenthesis() {
+        for operators in 0..=3 {
+            for formula in formulas(operators) {
+                assert_truth_table(&formula, &grouped(&formula));
             }
-            Err(_) => (),
         }
     }
 }
+
+#[cfg(test)]
+mod test_typed {
+    use super::*;
+
+    fn evaluate_typed(condition: &str, line: &[&str]) -> Result<bool, MiniSQLError> {
+        evaluate_coerced(condition, line, Coercion::Implicit)
+    }
+
+    fn evaluate_coerced(
+        condition: &str,
+        line: &[&str],
+        coercion: Coercion,
+    ) -> Result<bool, MiniSQLError> {
+        let indexes = HashMap::from([
+            ("precio".to_string(), 0),
+            ("alta".to_string(), 1),
+            ("codigo".to_string(), 2),
+            ("activo".to_string(), 3),
+        ]);
+        let types = [
+            Some(ColumnType::Real),
+            Some(ColumnType::Date),
+            Some(ColumnType::Text),
+            Some(ColumnType::Boolean),
+        ];
+        let line: Vec<String> = line.iter().map(|value| value.to_string()).collect();
+        compile_condition(
+            &Some(parse_condition(condition)),
+            &indexes,
+            &types,
+            coercion,
+        )?
+        .evaluate(&line)
+    }
+
+    #[test]
+    fn test_real_field_compares_as_number() {
+        let line = ["19.9", "2024-02-29", "007", "true"];
+        assert_eq!(evaluate_typed("precio < 100", &line), Ok(true));
+        assert_eq!(evaluate_typed("precio > '2'", &line), Ok(true));
+    }
+
+    #[test]
+    fn test_date_field_compares_chronologically() {
+        let line = ["19.9", "2024-02-29", "007", "true"];
+        assert_eq!(evaluate_typed("alta > '2024-02-10'", &line), Ok(true));
+        assert_eq!(evaluate_typed("alta = '2024-02-29'", &line), Ok(true));
+    }
+
+    #[test]
+    fn test_text_field_does_not_guess_numbers() {
+        let line = ["19.9", "2024-02-29", "007", "true"];
+        assert_eq!(evaluate_typed("codigo = 7", &line), Ok(false));
+        assert_eq!(evaluate_typed("codigo = '007'", &line), Ok(true));
+    }
+
+    #[test]
+    fn test_boolean_field() {
+        let line = ["19.9", "2024-02-29", "007", "true"];
+        assert_eq!(evaluate_typed("activo = TRUE", &line), Ok(true));
+        assert_eq!(evaluate_typed("NOT activo = false", &line), Ok(true));
+    }
+
+    #[test]
+    fn test_empty_typed_field_never_matches() {
+        let line = ["", "2024-02-29", "007", "true"];
+        assert_eq!(evaluate_typed("precio < 100", &line), Ok(false));
+        assert_eq!(evaluate_typed("precio >= 100", &line), Ok(false));
+    }
+
+    #[test]
+    fn test_literal_must_fit_declared_type() {
+        let line = ["19.9", "2024-02-29", "007", "true"];
+        assert_eq!(
+            evaluate_typed("alta > 'ayer'", &line),
+            Err(MiniSQLError::InvalidSyntax(
+                "invalid value ayer for a field of type DATE ".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_in_list_reads_literals_with_the_field_type() {
+        let line = ["19.90", "2024-02-29", "007", "true"];
+        assert_eq!(evaluate_typed("precio IN (5, 19.9)", &line), Ok(true));
+        assert_eq!(
+            evaluate_typed("alta NOT IN ('2024-02-29')", &line),
+            Ok(false)
+        );
+        assert_eq!(evaluate_typed("codigo IN (7, '7')", &line), Ok(false));
+        assert_eq!(
+            evaluate_typed("alta IN ('ayer')", &line),
+            Err(MiniSQLError::InvalidSyntax(
+                "invalid value ayer for a field of type DATE ".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_empty_typed_field_is_not_in_any_list() {
+        let line = ["", "2024-02-29", "007", "true"];
+        assert_eq!(evaluate_typed("precio IN (1, 2)", &line), Ok(false));
+        assert_eq!(evaluate_typed("precio NOT IN (1, 2)", &line), Ok(false));
+    }
+
+    #[test]
+    fn test_between_includes_both_ends() {
+        let line = ["19.9", "2024-02-29", "007", "true"];
+        assert_eq!(
+            evaluate_typed("precio BETWEEN 19.9 AND 20", &line),
+            Ok(true)
+        );
+        assert_eq!(
+            evaluate_typed("alta BETWEEN '2024-01-01' AND '2024-02-28'", &line),
+            Ok(false)
+        );
+        assert_eq!(
+            evaluate_typed("alta NOT BETWEEN '2024-01-01' AND '2024-02-28'", &line),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn test_invalid_stored_value() {
+        let line = ["caro", "2024-02-29", "007", "true"];
+        assert_eq!(
+            evaluate_coerced("precio > 1", &line, Coercion::Strict),
+            Err(MiniSQLError::InvalidSyntax(
+                "invalid type comparison, caro is not a valid REAL".to_string()
+            ))
+        );
+        assert_eq!(
+            evaluate_coerced("precio IN (1, 2)", &line, Coercion::Strict),
+            Err(MiniSQLError::InvalidSyntax(
+                "invalid type comparison, caro is not a valid REAL".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_invalid_stored_value_is_compared_as_text() {
+        let line = ["caro", "2024-02-29", "007", "true"];
+        assert_eq!(evaluate_typed("precio > 1", &line), Ok(true));
+        assert_eq!(evaluate_typed("precio IN (1, 2)", &line), Ok(false));
+        assert_eq!(evaluate_typed("precio = codigo", &line), Ok(false));
+        assert_eq!(evaluate_typed("precio > codigo", &line), Ok(true));
+    }
+}
+
+#[cfg(test)]
+mod test_scalar {
+    use super::*;
+
+    fn evaluate_scalar(expr: &str, line: &[&str]) -> Result<String, MiniSQLError> {
+        use crate::parser::ast::{SelectItem, Statement};
+        use crate::parser::grammar::parse;
+
+        let expr = match parse(&format!("SELECT {} FROM t", expr)) {
+            Ok(Statement::Select(select)) => match &select.projection[0] {
+                SelectItem::Expr { expr, .. } => expr.clone(),
+                other => panic!("invalid test expression: {:?}", other),
+            },
+            other => panic!("invalid test expression: {:?}", other),
+        };
+        let indexes = HashMap::from([
+            ("precio".to_string(), 0),
+            ("cantidad".to_string(), 1),
+            ("nombre".to_string(), 2),
+        ]);
+        let types = [Some(ColumnType::Real), None, None];
+        let line: Vec<String> = line.iter().map(|value| value.to_string()).collect();
+        compile_scalar(&expr, &indexes, &types)?.evaluate(&line)
+    }
+
+    #[test]
+    fn test_arithmetic_on_fields() {
+        let line = ["2.5", "4", "pepe"];
+        assert_eq!(
+            evaluate_scalar("precio * cantidad", &line),
+            Ok("10".to_string())
+        );
+        assert_eq!(evaluate_scalar("cantidad / 3", &line), Ok("1".to_string()));
+        assert_eq!(
+            evaluate_scalar("cantidad % 3 - 2", &line),
+            Ok("-1".to_string())
+        );
+        assert_eq!(
+            evaluate_scalar("-precio + 1", &line),
+            Ok("-1.5".to_string())
+        );
+    }
+
+    #[test]
+    fn test_concatenation() {
+        let line = ["2.5", "4", "pepe"];
+        assert_eq!(
+            evaluate_scalar("nombre || ' x' || cantidad", &line),
+            Ok("pepe x4".to_string())
+        );
+    }
+
+    #[test]
+    fn test_null_field_gives_null() {
+        let line = ["", "4", "pepe"];
+        assert_eq!(
+            evaluate_scalar("precio * cantidad", &line),
+            Ok(NULL.to_string())
+        );
+        let line = ["2.5", NULL, "pepe"];
+        assert_eq!(
+            evaluate_scalar("nombre || cantidad", &line),
+            Ok(NULL.to_string())
+        );
+    }
+
+    #[test]
+    fn test_invalid_arithmetic() {
+        let line = ["2.5", "0", "pepe"];
+        assert_eq!(
+            evaluate_scalar("nombre * 2", &line),
+            Err(MiniSQLError::InvalidSyntax(
+                "unable to apply * to pepe and 2 ".to_string()
+            ))
+        );
+        assert_eq!(
+            evaluate_scalar("precio / cantidad", &line),
+            Err(MiniSQLError::InvalidSyntax(
+                "division by zero on 2.5 / 0 ".to_string()
+            ))
+        );
+        assert_eq!(
+            evaluate_scalar("cantidad + 9223372036854775807", &["1", "1", ""]),
+            Err(MiniSQLError::InvalidSyntax(
+                "numeric overflow on 1 + 9223372036854775807 ".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_expressions_on_conditions() {
+        let indexes = HashMap::from([("precio".to_string(), 0), ("cantidad".to_string(), 1)]);
+        let line = ["2.5".to_string(), "4".to_string()];
+        assert_eq!(
+            evaluate("precio * cantidad = 10", &line, &indexes),
+            Ok(true)
+        );
+        assert_eq!(evaluate("cantidad - 1 > 3", &line, &indexes), Ok(false));
+        assert_eq!(evaluate("-cantidad < 0", &line, &indexes), Ok(true));
+    }
+}
+
+#[cfg(test)]
+mod test_like {
+    use super::*;
+
+    fn like(text: &str, pattern: &str) -> bool {
+        let pieces = compile_pattern(pattern, Some('!'), false).unwrap();
+        matches_pattern(&pieces, &text.chars().collect::<Vec<char>>())
+    }
+
+    #[test]
+    fn test_wildcards() {
+        assert!(like("mario@gmail.com", "%@gmail.com"));
+        assert!(like("mario", "m_r%"));
+        assert!(like("", "%"));
+        assert!(like("abcbc", "%bc"));
+        assert!(like("aXbYc", "a%b%c"));
+        assert!(!like("mario", "m_r"));
+        assert!(!like("mario", "%z%"));
+        assert!(!like("", "_"));
+    }
+
+    #[test]
+    fn test_escaped_wildcards() {
+        assert!(like("100%", "100!%"));
+        assert!(!like("1000", "100!%"));
+        assert!(like("a_b", "a!_b"));
+        assert!(!like("axb", "a!_b"));
+        assert_eq!(
+            compile_pattern("a!", Some('!'), false),
+            Err(MiniSQLError::InvalidSyntax(
+                "LIKE pattern a! can not end with its escape character ".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_like_on_conditions() {
+        let indexes = HashMap::from([("nombre".to_string(), 0), ("codigo".to_string(), 1)]);
+        let line = ["Mario".to_string(), "007".to_string()];
+        assert_eq!(evaluate("nombre LIKE 'M%'", &line, &indexes), Ok(true));
+        assert_eq!(evaluate("nombre LIKE 'm%'", &line, &indexes), Ok(false));
+        assert_eq!(evaluate("nombre ILIKE 'm%IO'", &line, &indexes), Ok(true));
+        assert_eq!(evaluate("nombre NOT LIKE '%x%'", &line, &indexes), Ok(true));
+        assert_eq!(
+            evaluate("NOT nombre NOT ILIKE 'MARIO'", &line, &indexes),
+            Ok(true)
+        );
+        assert_eq!(evaluate("codigo LIKE '00_'", &line, &indexes), Ok(true));
+        assert_eq!(
+            evaluate("codigo LIKE '0%' AND nombre LIKE '%o'", &line, &indexes),
+            Ok(true)
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_null {
+    use super::*;
+
+    fn evaluate_null(condition: &str) -> Result<bool, MiniSQLError> {
+        let indexes = HashMap::from([
+            ("telefono".to_string(), 0),
+            ("stock".to_string(), 1),
+            ("nombre".to_string(), 2),
+        ]);
+        let types = [None, Some(ColumnType::Integer), Some(ColumnType::Text)];
+        let line = [NULL.to_string(), "".to_string(), "".to_string()];
+        compile_condition(
+            &Some(parse_condition(condition)),
+            &indexes,
+            &types,
+            Coercion::Implicit,
+        )?
+        .evaluate(&line)
+    }
+
+    #[test]
+    fn test_comparison_with_null_is_unknown() {
+        assert_eq!(evaluate_null("telefono > 0"), Ok(false));
+        assert_eq!(evaluate_null("NOT telefono > 0"), Ok(false));
+        assert_eq!(evaluate_null("stock = 0 OR NOT stock = 0"), Ok(false));
+        assert_eq!(evaluate_null("telefono || 'x' = 'x'"), Ok(false));
+        assert_eq!(evaluate_null("1 = NULL"), Ok(false));
+        assert_eq!(evaluate_null("telefono LIKE '%'"), Ok(false));
+    }
+
+    #[test]
+    fn test_three_valued_logic() {
+        assert_eq!(evaluate_null("telefono > 0 OR TRUE"), Ok(true));
+        assert_eq!(evaluate_null("NOT (telefono > 0 AND FALSE)"), Ok(true));
+        assert_eq!(evaluate_null("NOT (telefono > 0 AND TRUE)"), Ok(false));
+        assert_eq!(evaluate_null("NOT (NULL OR FALSE)"), Ok(false));
+    }
+
+    #[test]
+    fn test_is_null() {
+        assert_eq!(evaluate_null("telefono IS NULL"), Ok(true));
+        assert_eq!(evaluate_null("stock IS NULL"), Ok(true));
+        assert_eq!(evaluate_null("stock + 1 IS NULL"), Ok(true));
+        assert_eq!(evaluate_null("nombre IS NULL"), Ok(false));
+        assert_eq!(evaluate_null("nombre IS NOT NULL"), Ok(true));
+        assert_eq!(evaluate_null("NOT telefono IS NULL"), Ok(false));
+    }
+
+    #[test]
+    fn test_in_list_with_null() {
+        assert_eq!(evaluate_null("stock IN (1, 2)"), Ok(false));
+        assert_eq!(evaluate_null("stock NOT IN (1, 2)"), Ok(false));
+        assert_eq!(evaluate_null("nombre IN ('', NULL)"), Ok(true));
+        assert_eq!(evaluate_null("nombre NOT IN ('x', NULL)"), Ok(false));
+        assert_eq!(evaluate_null("nombre IN ('x', NULL) OR TRUE"), Ok(true));
+    }
+}
+
+#[cfg(test)]
+mod test_dates {
+    use super::*;
+
+    fn evaluate_dates(condition: &str, line: &[&str]) -> Result<bool, MiniSQLError> {
+        let indexes = HashMap::from([
+            ("dia".to_string(), 0),
+            ("creado".to_string(), 1),
+            ("hora".to_string(), 2),
+            ("texto".to_string(), 3),
+        ]);
+        let types = [
+            Some(ColumnType::Date),
+            Some(ColumnType::Timestamp),
+            Some(ColumnType::Time),
+            None,
+        ];
+        let line: Vec<String> = line.iter().map(|value| value.to_string()).collect();
+        compile_condition(
+            &Some(parse_condition(condition)),
+            &indexes,
+            &types,
+            Coercion::Strict,
+        )?
+        .evaluate(&line)
+    }
+
+    const LINE: [&str; 4] = [
+        "2024-01-31",
+        "2024-01-31T09:30:00",
+        "09:30:00",
+        "2024-02-01 08:00",
+    ];
+
+    #[test]
+    fn test_compare_dates_and_times() {
+        assert_eq!(evaluate_dates("creado > '2024-01-31'", &LINE), Ok(true));
+        assert_eq!(evaluate_dates("dia < creado", &LINE), Ok(true));
+        assert_eq!(evaluate_dates("creado < texto", &LINE), Ok(true));
+        assert_eq!(
+            evaluate_dates("hora BETWEEN '09:00' AND '10:00'", &LINE),
+            Ok(true)
+        );
+        assert_eq!(
+            evaluate_dates("dia < NOW() AND dia < CURRENT_DATE", &LINE),
+            Ok(true)
+        );
+        assert_eq!(
+            evaluate_dates("hora > '25:00'", &LINE),
+            Err(MiniSQLError::InvalidSyntax(
+                "invalid value 25:00 for a field of type TIME ".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_date_functions() {
+        assert_eq!(
+            evaluate_dates("DATE_ADD(dia, INTERVAL 1 MONTH) = '2024-02-29'", &LINE),
+            Ok(true)
+        );
+        assert_eq!(
+            evaluate_dates("DATE_ADD(texto, INTERVAL -8 HOUR) = '2024-02-01'", &LINE),
+            Ok(true)
+        );
+        assert_eq!(
+            evaluate_dates(
+                "EXTRACT(MINUTE FROM creado) + EXTRACT(HOUR FROM hora) = 39",
+                &LINE
+            ),
+            Ok(true)
+        );
+        assert_eq!(
+            evaluate_dates("DATE_TRUNC('year', creado) = '2024-01-01'", &LINE),
+            Ok(true)
+        );
+        assert_eq!(
+            evaluate_dates("DATE_TRUNC('day', texto) = dia", &LINE),
+            Ok(false)
+        );
+        assert_eq!(
+            evaluate_dates("EXTRACT(SECOND FROM dia) = 0", &LINE),
+            Err(MiniSQLError::InvalidSyntax(
+                "unable to extract SECOND from 2024-01-31 ".to_string()
+            ))
+        );
+        assert_eq!(
+            evaluate_dates(
+                "DATE_ADD(hora, INTERVAL 1 DAY) = hora",
+                &[NULL, NULL, NULL, NULL]
+            ),
+            Ok(false)
+        );
+        assert_eq!(
+            evaluate_dates("DATE_ADD(dia, INTERVAL 1.5 DAY) > dia", &LINE),
+            Err(MiniSQLError::InvalidSyntax(
+                "unable to add INTERVAL 1.5 DAY to 2024-01-31 ".to_string()
+            ))
+        );
+    }
+}
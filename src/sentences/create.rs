@@ -0,0 +1,79 @@
+use super::common::literal_to_stored;
+use crate::errors::apperrors::MiniSQLError;
+use crate::file;
+use crate::parser::ast::{ColumnConstraint, CreateTableStatement};
+use std::collections::HashSet;
+
+/// Executes a parsed `CREATE TABLE` statement.
+///
+/// Creates the `.csv` of the table with only its header, and the `.schema` with
+/// the declared column definitions next to it.
+///
+/// # Examples
+///
+/// ```
+/// if let Statement::CreateTable(create) = parse("CREATE TABLE cuentas (id INTEGER PRIMARY KEY, titular TEXT NOT NULL)")? {
+///     execute_create_statement(create, &"user/data/tables");
+/// }
+/// ```
+///
+/// # Errors
+///
+/// This function will return an error of type `MiniSQLError` if:
+///
+/// - The table already exists and `IF NOT EXISTS` was not given.
+/// - A field is declared twice, more than one field is the primary key,
+///   or a default value does not fit the type of its field.
+/// - The files could not be written.
+///
+/// # Returns
+///
+/// - `Ok(())` if the table was created, or already existed with `IF NOT EXISTS`.
+/// - `Err(MiniSQLError)` if an error occurs during execution.
+///
+pub fn execute_create_statement(
+    create: CreateTableStatement,
+    route: &String,
+) -> Result<(), MiniSQLError> {
+    if create.if_not_exists && file::handler::table_exists(route, &create.table) {
+        return Ok(());
+    }
+    validate_columns(&create)?;
+
+    let headers: Vec<String> = create
+        .columns
+        .iter()
+        .map(|column| column.name.to_string())
+        .collect();
+    file::handler::create_table(route, &create.table, &headers)?;
+    file::schema::write_schema(route, &create.table, &create.columns)?;
+    Ok(())
+}
+
+fn validate_columns(create: &CreateTableStatement) -> Result<(), MiniSQLError> {
+    let mut names: HashSet<&str> = HashSet::new();
+    let mut primary_keys = 0;
+
+    for column in &create.columns {
+        if !names.insert(&column.name) {
+            return Err(MiniSQLError::InvalidColumn(format!(
+                "field [ {} ] is declared more than once ",
+                column.name
+            )));
+        }
+        if column.constraints.contains(&ColumnConstraint::PrimaryKey) {
+            primary_keys += 1;
+        }
+        if let Some(default) = column.default_value() {
+            literal_to_stored(default, &Some(column.column_type), &column.name)?;
+        }
+    }
+
+    if primary_keys > 1 {
+        return Err(MiniSQLError::InvalidTable(format!(
+            "table {} can not have more than one primary key ",
+            create.table
+        )));
+    }
+    Ok(())
+}
@@ -1,27 +1,33 @@
-use super::common::{add_all_fields, format_to_csv, get_headers, validate_table};
-use super::conditions::get_query;
+use super::common::{add_all_fields, format_to_csv, get_headers};
+use super::conditions::{compile_condition, Coercion};
 use crate::errors::apperrors::MiniSQLError;
 use crate::file;
+use crate::parser::ast::DeleteStatement;
+use crate::types::value::NULL;
+use csv::Reader;
 use std::fs::File;
-use std::io::Write;
-use std::io::{BufRead, BufReader};
 
-/// Executes a `DELETE` query with the provided SQL string.
+/// Executes a parsed `DELETE` statement.
 ///
 /// This function encapsulates the entire lifecycle of a `DELETE`,
 /// including the creation, execution, and handling of the query.
 ///
+/// Values of different types on the WHERE are compared following `coercion`.
+///
 /// # Examples
 ///
 /// ```
-/// execute_delete_statement(["DELETE", "FROM", "clientes", "WHERE", "id_cliente", "=", "107"], &"user/data/tables");
+/// if let Statement::Delete(delete) = parse("DELETE FROM clientes WHERE id_cliente = 107")? {
+///     execute_delete_statement(delete, &"user/data/tables", Coercion::Implicit);
+/// }
 /// ```
 ///
 /// # Errors
 ///
 /// This function will return an error of type `MiniSQLError` if:
 ///
-/// - The SQL string is invalid.
+/// - A referenced field does not exist on the table.
+/// - A register can not be compared with the condition, the error tells its number.
 /// - The provided table is invalid.
 /// - The query fails for any other reason.
 ///
@@ -31,107 +37,43 @@ use std::io::{BufRead, BufReader};
 /// - `Err(MiniSQLError)` if an error occurs during execution.
 ///
 pub fn execute_delete_statement(
-    sententence_vec: Vec<String>,
+    delete: DeleteStatement,
     route: &String,
+    coercion: Coercion,
 ) -> Result<(), MiniSQLError> {
-    let delete = new_delete(sententence_vec)?;
-    let file_iter = file::handler::new_file_iterator(route, &delete.target_table)?;
+    let file_iter = file::handler::new_file_iterator(route, &delete.table)?;
 
-    execute_delete(&delete, file_iter, route)?;
+    execute_delete(&delete, file_iter, route, coercion)?;
     Ok(())
 }
 
-/// Contains all requiered data to execute a DELETE statement given row values
-struct Delete {
-    /// FROM --> target_table
-    target_table: String,
-    /// WHERE --> condition ; as a vector of each part, id = 1 --> ["id", "=", "1"]
-    condition: Vec<String>,
-}
-
-fn new_delete(sentence_parts: Vec<String>) -> Result<Delete, MiniSQLError> {
-    let (condition, table) = decode_delete(sentence_parts)?;
-    Ok(Delete {
-        target_table: table,
-        condition,
-    })
-}
-
-fn decode_delete(sentence_parts: Vec<String>) -> Result<(Vec<String>, String), MiniSQLError> {
-    let mut condition: Vec<String> = Vec::new();
-    let mut from: Vec<String> = Vec::new();
-
-    (condition, from) = match_fields_delete(sentence_parts, condition, from)?;
-
-    let table = validate_table(from)?;
-
-    Ok((condition, table))
-}
-
-fn match_fields_delete(
-    sentence_parts: Vec<String>,
-    mut condition: Vec<String>,
-    mut from: Vec<String>,
-) -> Result<(Vec<String>, Vec<String>), MiniSQLError> {
-    let mut base = "";
-    for part in &sentence_parts {
-        match part.as_str() {
-            "DELETE" => {
-                base = "";
-                continue;
-            }
-            "FROM" => {
-                base = "from";
-                continue;
-            }
-            "WHERE" => {
-                base = "condition";
-                continue;
-            }
-            _ => (),
-        }
-
-        match base {
-            "condition" => condition.push(part.to_string()),
-            "from" => from.push(part.to_string()),
-            _ => {
-                return Err(MiniSQLError::InvalidSyntax(format!(
-                    "Invalid sentence: {} ",
-                    sentence_parts.join(" ")
-                )))
-            }
-        }
-    }
-    Ok((condition, from))
-}
-
 fn execute_delete(
-    sentence: &Delete,
-    file_iter: BufReader<File>,
+    sentence: &DeleteStatement,
+    file_iter: Reader<File>,
     file_path: &String,
+    coercion: Coercion,
 ) -> Result<(), MiniSQLError> {
-    let (file_iter, headers) = get_headers(file_iter);
+    let (file_iter, headers) = get_headers(file_iter)?;
     let mapped_fields = add_all_fields(&headers);
-    let mut new_file = file::handler::create_file(file_path, &sentence.target_table)?;
+    let types = file::schema::read_schema(file_path, &sentence.table)?.column_types(&headers)?;
+    let condition = compile_condition(&sentence.condition, &mapped_fields, &types, coercion)?;
+    let mut new_file = file::handler::create_file(file_path, &sentence.table)?;
 
-    let headers = headers.join(",").replace("\n", "");
-    writeln!(new_file, "{}", headers)?;
-    for result in file_iter.lines() {
+    new_file.write_record(&headers)?;
+    for (position, result) in file_iter.into_records().enumerate() {
         let record = result?;
-        let line = format_to_csv(record);
-        let should_apply = get_query(
-            &sentence.condition,
-            0,
-            sentence.condition.len(),
-            &mapped_fields,
-            &line,
-        )?;
+        let mut line = format_to_csv(&record);
+        // una fila incompleta se completa con NULL, asi tambien se guarda si no se borra
+        line.resize(headers.len(), NULL.to_string());
+        let should_apply = condition
+            .evaluate(&line)
+            .map_err(|err| err.on_row(position + 1))?;
         if !should_apply {
-            let csv_line = line.join(",").replace("\n", "");
-            writeln!(new_file, "{}", csv_line)?;
+            new_file.write_record(&line)?;
         }
     }
-    file::handler::rename_file(file_path, &sentence.target_table)?;
+    new_file.flush()?;
+    file::handler::rename_file(file_path, &sentence.table)?;
 
     Ok(())
 }
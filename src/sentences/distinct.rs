@@ -0,0 +1,223 @@
+use super::common::format_to_csv;
+use super::join::join_key;
+use crate::errors::apperrors::MiniSQLError;
+use crate::file;
+use crate::types::value::ColumnType;
+use csv::Writer;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+
+/// Distinct keys kept in memory before the lines start going to disk
+const MEMORY_KEYS: usize = 100_000;
+/// Number of files the lines are split into once they go to disk
+const PARTITIONS: usize = 16;
+
+/// Position and declared type of each field of a key
+pub type KeyFields = Vec<(usize, Option<ColumnType>)>;
+
+/// Keeps the first line of each key, the key being the values on the positions of `fields`
+/// read with their declared type, the same key GROUP BY uses: `1` and `01` are equal on a
+/// numeric field.
+///
+/// While the keys fit in memory each line is known to be new as soon as it is read.
+/// After that, lines with a key not seen yet are written to one of several files, chosen
+/// by the hash of the key, and each file is deduplicated on its own by `finish`. Equal keys
+/// always land on the same file, so only one file has to be in memory at a time.
+pub struct Deduplicator {
+    fields: KeyFields,
+    seen: HashSet<Vec<String>>,
+    memory_keys: usize,
+    spill: Option<Spill>,
+}
+
+struct Spill {
+    dir: PathBuf,
+    files: Vec<Writer<File>>,
+}
+
+impl Deduplicator {
+    pub fn new(fields: KeyFields) -> Deduplicator {
+        Deduplicator::with_memory(fields, MEMORY_KEYS)
+    }
+
+    fn with_memory(fields: KeyFields, memory_keys: usize) -> Deduplicator {
+        Deduplicator {
+            fields,
+            seen: HashSet::new(),
+            memory_keys,
+            spill: None,
+        }
+    }
+
+    /// Returns the line if its key was not seen before. Once the keys went to disk,
+    /// lines that may be new are kept for `finish` and `None` is returned.
+    pub fn push(&mut self, line: Vec<String>) -> Result<Option<Vec<String>>, MiniSQLError> {
+        let key = key(&line, &self.fields);
+        if self.seen.contains(&key) {
+            return Ok(None);
+        }
+        if self.seen.len() < self.memory_keys {
+            self.seen.insert(key);
+            return Ok(Some(line));
+        }
+
+        if self.spill.is_none() {
+            self.spill = Some(Spill::create()?);
+        }
+        if let Some(spill) = &mut self.spill {
+            spill.files[partition(&key)].write_record(&line)?;
+        }
+        Ok(None)
+    }
+
+    /// Hands to `on_line` the first line of each key that went to disk, until it returns `false`
+    pub fn finish(
+        mut self,
+        mut on_line: impl FnMut(Vec<String>) -> Result<bool, MiniSQLError>,
+    ) -> Result<(), MiniSQLError> {
+        let mut spill = match self.spill.take() {
+            Some(spill) => spill,
+            None => return Ok(()),
+        };
+        self.seen.clear();
+
+        for (position, mut writer) in spill.files.drain(..).enumerate() {
+            writer.flush()?;
+            drop(writer);
+            let mut seen: HashSet<Vec<String>> = HashSet::new();
+            for result in
+                file::handler::new_spill_iterator(&spill.dir.join(partition_name(position)))?
+                    .into_records()
+            {
+                let line = format_to_csv(&result?);
+                if seen.insert(key(&line, &self.fields)) && !on_line(line)? {
+                    return Ok(());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Spill {
+    fn create() -> Result<Spill, MiniSQLError> {
+        let dir = file::handler::create_spill_dir()?;
+        let mut files: Vec<Writer<File>> = vec![];
+        for position in 0..PARTITIONS {
+            files.push(file::handler::create_spill_file(
+                &dir.join(partition_name(position)),
+            )?);
+        }
+        Ok(Spill { dir, files })
+    }
+}
+
+/// The temporary files are removed however the query ends
+impl Drop for Spill {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Values of the key normalized with `join_key`, public so DISTINCT ON compares its keys alike
+pub fn key(line: &[String], fields: &[(usize, Option<ColumnType>)]) -> Vec<String> {
+    fields
+        .iter()
+        .map(|(index, column_type)| {
+            join_key(
+                line.get(*index).map(|s| s.as_str()).unwrap_or(""),
+                *column_type,
+            )
+        })
+        .collect()
+}
+
+fn partition(key: &[String]) -> usize {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    (hasher.finish() % PARTITIONS as u64) as usize
+}
+
+fn partition_name(position: usize) -> String {
+    format!("partition-{}.csv", position)
+}
+
+#[cfg(test)]
+mod test_distinct {
+    use super::*;
+
+    fn deduplicate(lines: &[[&str; 2]], fields: KeyFields, memory_keys: usize) -> Vec<String> {
+        let mut deduplicator = Deduplicator::with_memory(fields, memory_keys);
+        let mut result: Vec<String> = vec![];
+        for line in lines {
+            let line: Vec<String> = line.iter().map(|value| value.to_string()).collect();
+            if let Some(line) = deduplicator.push(line).unwrap() {
+                result.push(line.join(" "));
+            }
+        }
+        deduplicator
+            .finish(|line| {
+                result.push(line.join(" "));
+                Ok(true)
+            })
+            .unwrap();
+        result
+    }
+
+    #[test]
+    fn test_keeps_first_line_of_each_key() {
+        let lines = [["a", "1"], ["b", "2"], ["a", "3"], ["b", "2"]];
+        assert_eq!(
+            deduplicate(&lines, vec![(0, None), (1, None)], 10),
+            vec!["a 1", "b 2", "a 3"]
+        );
+        assert_eq!(deduplicate(&lines, vec![(0, None)], 10), vec!["a 1", "b 2"]);
+    }
+
+    #[test]
+    fn test_numbers_compared_by_their_type() {
+        let lines = [["1", "a"], ["01", "b"], ["1.0", "c"], ["a", "d"]];
+        let real = Some(ColumnType::Real);
+        assert_eq!(deduplicate(&lines, vec![(0, real)], 10), vec!["1 a", "a d"]);
+        // tambien una vez que las claves van a disco
+        assert_eq!(deduplicate(&lines, vec![(0, real)], 0), vec!["1 a", "a d"]);
+        assert_eq!(deduplicate(&lines, vec![(0, None)], 10), vec!["1 a", "a d"]);
+    }
+
+    #[test]
+    fn test_keys_that_do_not_fit_in_memory_go_to_disk() {
+        let values: Vec<String> = (0..200).map(|value| (value % 50).to_string()).collect();
+        let lines: Vec<[&str; 2]> = values.iter().map(|value| [value.as_str(), "x"]).collect();
+
+        let mut result = deduplicate(&lines, vec![(0, None)], 10);
+        assert_eq!(result.len(), 50);
+        // las primeras claves se conocen en memoria y salen en el orden en que se leyeron
+        assert_eq!(
+            result[..10],
+            deduplicate(&lines[..10], vec![(0, None)], 10)[..]
+        );
+        result.sort();
+        result.dedup();
+        assert_eq!(result.len(), 50);
+    }
+
+    #[test]
+    fn test_finish_stops_when_asked() {
+        let values: Vec<String> = (0..30).map(|value| value.to_string()).collect();
+        let mut deduplicator = Deduplicator::with_memory(vec![(0, None)], 5);
+        for value in &values {
+            deduplicator.push(vec![value.to_string()]).unwrap();
+        }
+        let mut read = 0;
+        deduplicator
+            .finish(|_| {
+                read += 1;
+                Ok(read < 3)
+            })
+            .unwrap();
+        assert_eq!(read, 3);
+    }
+}
@@ -0,0 +1,44 @@
+use crate::errors::apperrors::MiniSQLError;
+use crate::file;
+use crate::parser::ast::DropTableStatement;
+
+/// Executes a parsed `DROP TABLE` statement.
+///
+/// Deletes the `.csv` of the table and its `.schema`, if it has one.
+///
+/// # Examples
+///
+/// ```
+/// if let Statement::DropTable(drop) = parse("DROP TABLE IF EXISTS cuentas")? {
+///     execute_drop_statement(drop, &"user/data/tables");
+/// }
+/// ```
+///
+/// # Errors
+///
+/// This function will return an error of type `MiniSQLError` if:
+///
+/// - The table does not exist and `IF EXISTS` was not given.
+/// - The files could not be removed.
+///
+/// # Returns
+///
+/// - `Ok(())` if the table was dropped, or did not exist with `IF EXISTS`.
+/// - `Err(MiniSQLError)` if an error occurs during execution.
+///
+pub fn execute_drop_statement(
+    drop: DropTableStatement,
+    route: &String,
+) -> Result<(), MiniSQLError> {
+    if !file::handler::table_exists(route, &drop.table) {
+        if drop.if_exists {
+            return Ok(());
+        }
+        return Err(MiniSQLError::InvalidTable(format!(
+            "Unable to open file at {}/{}",
+            route, drop.table
+        )));
+    }
+
+    file::handler::drop_table(route, &drop.table)
+}
@@ -1,29 +1,36 @@
-use super::common::{add_all_fields, get_headers, get_required_fields, validate_table};
+use super::common::{
+    add_all_fields, check_unique, get_headers, get_required_fields, literal_to_stored,
+    unique_fields, unique_key,
+};
 use crate::errors::apperrors::MiniSQLError;
 use crate::file;
-use std::collections::HashMap;
-use std::io::Write;
+use crate::parser::ast::{ColumnDefinition, InsertStatement, Literal};
+use crate::types::value::{is_null, ColumnType, NULL};
+use std::collections::{HashMap, HashSet};
 
-/// Executes a `INSERT` query with the provided SQL string.
+/// Executes a parsed `INSERT` statement.
 ///
 /// This function encapsulates the entire lifecycle of a `INSERT`,
 /// including the creation, execution, and handling of the query.
 ///
-/// Will append the registers to the table
+/// Will append the registers to the table. Fields left out of the sentence take their
+/// declared DEFAULT, or NULL without one, and NOT NULL, UNIQUE and PRIMARY KEY fields are
+/// checked before writing.
 ///
 /// # Examples
 ///
 /// ```
-/// execute_insert_statement(["INSERT", "INTO", "clientes", "(", "nombre", ",", "apellido", ")", "VALUES", "(", "'pepe'", ",", "'garcia'", ")"], &"user/data/tables");
-/// execute_insert_statement(["INSERT", "INTO", "clientes", "(", "id_cliente", ",", "nombre", ",", "apellido", ",", "email", ",", "telefono", ")", "VALUES", "(", "111", ",", "'pepe'", ",", "'garcia'", ",", "'pepe@email.com'", ",", "5551234990", ")"], &"user/data/tables");
-/// execute_insert_statement(["INSERT", "INTO", "clientes", "(", "nombre", ",", "apellido", ")", "VALUES", "(", "'pepe'", ",", "'garcia'", ")",",", "(", "carlos", ",", "rodriguez", ")"], &"user/data/tables");
+/// if let Statement::Insert(insert) = parse("INSERT INTO clientes (nombre, apellido) VALUES ('pepe', 'garcia'), ('carlos', 'rodriguez')")? {
+///     execute_insert_statement(insert, &"user/data/tables");
+/// }
 /// ```
 ///
 /// # Errors
 ///
 /// This function will return an error of type `MiniSQLError` if:
 ///
-/// - The SQL string is invalid.
+/// - A referenced field does not exist on the table.
+/// - A value does not fit the declared type of its field or breaks one of its constraints.
 /// - The provided table is invalid.
 /// - The query fails for any other reason.
 ///
@@ -33,187 +40,135 @@ use std::io::Write;
 /// - `Err(MiniSQLError)` if an error occurs during execution.
 ///  
 pub fn execute_insert_statement(
-    sententence_vec: Vec<String>,
+    insert: InsertStatement,
     route: &String,
 ) -> Result<(), MiniSQLError> {
-    let insert = new_insert(sententence_vec)?;
     execute_insert(&insert, route)?;
     Ok(())
 }
 
-/// Contains all requiered data to execute a INSERT statement given row values
-struct Insert {
-    /// INTO --> target_table
-    target_table: String,
-    /// ( nombre , apellido ) --> fields; ["nombre", "apellido"]
-    fields: Vec<String>,
-    /// VALUES ('pepe', 'garcia'), ('carlos', 'rodriguez') --> values ; as vector containing each new register as a vector of strings
-    values: Vec<Vec<String>>,
-}
-
-struct FieldsToInsert {
-    fields: Vec<String>,
-    target_table: Vec<String>,
-    values: Vec<Vec<String>>,
-}
-
-fn new_insert(sentence_parts: Vec<String>) -> Result<Insert, MiniSQLError> {
-    decode_insert(sentence_parts)
-}
+fn execute_insert(sentence: &InsertStatement, route: &String) -> Result<(), MiniSQLError> {
+    let headers: Vec<String>;
+    {
+        let file = file::handler::new_file_iterator(route, &sentence.table)?;
+        let (_, headers_file) = get_headers(file)?;
+        headers = headers_file
+    };
 
-fn decode_insert(sentence_parts: Vec<String>) -> Result<Insert, MiniSQLError> {
-    let fields: Vec<String> = Vec::new();
-    let from: Vec<String> = Vec::new();
-    let values: Vec<Vec<String>> = Vec::new();
+    let schema = file::schema::read_schema(route, &sentence.table)?;
+    let types = schema.column_types(&headers)?;
+    let definitions: Vec<Option<&ColumnDefinition>> =
+        headers.iter().map(|header| schema.column(header)).collect();
+    let base_line = get_default_line(&definitions, &types, &headers)?;
+    let mut unique_values = read_unique_values(route, &sentence.table, &definitions)?;
+    let positions = get_required_fields(&sentence.columns, &headers, &add_all_fields(&headers))?;
 
-    let fields_to_insert = match_fields_insert(sentence_parts, fields, from, values)?;
+    // se valida todo antes de escribir para no dejar un insert aplicado a medias
+    let mut new_lines: Vec<Vec<String>> = vec![];
+    for line in &sentence.values {
+        let formatted_line = format_new_line(
+            line,
+            &positions,
+            &sentence.columns,
+            &types,
+            base_line.clone(),
+        )?;
+        check_constraints(&formatted_line, &definitions, &mut unique_values)?;
+        new_lines.push(formatted_line);
+    }
 
-    let table = validate_table(fields_to_insert.target_table)?;
+    let mut new_file = file::handler::create_file_append(route, &sentence.table)?;
+    for line in new_lines {
+        new_file.write_record(&line)?;
+    }
+    new_file.flush()?;
 
-    Ok(Insert {
-        target_table: table,
-        fields: fields_to_insert.fields,
-        values: fields_to_insert.values,
-    })
+    Ok(())
 }
 
-fn match_fields_insert(
-    sentence_parts: Vec<String>,
-    mut fields: Vec<String>,
-    mut from: Vec<String>,
-    mut values: Vec<Vec<String>>,
-) -> Result<FieldsToInsert, MiniSQLError> {
-    let mut value: Vec<String> = Vec::new();
-
-    let mut base = "";
-    for part in &sentence_parts {
-        match part.as_str() {
-            "INSERT" => {
-                base = "from";
-                continue;
-            }
-            "INTO" => {
-                if base == "from" {
-                    base = "from table"
-                }
-                continue;
-            }
-            "(" => {
-                if base == "from table" {
-                    base = "fields ("
-                } else if base == "values" {
-                    base = "values ("
-                }
-                continue;
-            }
-            ")" => {
-                if base == "fields (" {
-                    base = "fields ()"
-                } else if base == "values (" {
-                    base = "values ()";
-                    values.push(value.clone());
-                    value = Vec::new();
-                }
-                continue;
-            }
-            "," => {
-                if base == "values ()" {
-                    base = "values (";
-                }
-                continue;
-            }
-            "VALUES" => {
-                if base != "fields ()" {
-                    return Err(MiniSQLError::InvalidSyntax(format!(
-                        "Invalid sentence VALUES were given but missing fields to be replaced: {} ",
-                        sentence_parts.join(" ")
-                    )));
-                } else {
-                    base = "values"
-                }
-                continue;
-            }
-            _ => (),
-        }
-        match base {
-            "from table" => from.push(part.to_string()),
-            "fields (" => fields.push(part.to_string()),
-            "values (" => value.push(part.to_string()),
-            _ => {
-                return Err(MiniSQLError::InvalidSyntax(format!(
-                    "Invalid sentence: {} ",
-                    sentence_parts.join(" ")
-                )))
-            }
+/// Line with the default value of each field, or NULL if it has none
+fn get_default_line(
+    definitions: &[Option<&ColumnDefinition>],
+    types: &[Option<ColumnType>],
+    headers: &[String],
+) -> Result<Vec<String>, MiniSQLError> {
+    let mut line: Vec<String> = vec![];
+    for (index, definition) in definitions.iter().enumerate() {
+        match definition.and_then(|definition| definition.default_value()) {
+            Some(default) => line.push(literal_to_stored(default, &types[index], &headers[index])?),
+            None => line.push(NULL.to_string()),
         }
     }
+    Ok(line)
+}
+
+/// Values already stored on each UNIQUE or PRIMARY KEY field
+fn read_unique_values(
+    route: &String,
+    table: &String,
+    definitions: &[Option<&ColumnDefinition>],
+) -> Result<HashMap<usize, HashSet<String>>, MiniSQLError> {
+    let mut unique_values = unique_fields(definitions);
+    if unique_values.is_empty() {
+        return Ok(unique_values);
+    }
 
-    for value in &values {
-        if value.len() != fields.len() {
-            let message = format!(
-                "Invalid sentence VALUE, too little arguments were sent: {} 
-            \n. {} were requiered but sent {} ",
-                value.join(" "),
-                fields.len(),
-                values.len()
-            );
-            return Err(MiniSQLError::InvalidSyntax(message));
+    let file_iter = file::handler::new_file_iterator(route, table)?;
+    for result in file_iter.into_records() {
+        let record = result?;
+        for (index, values) in unique_values.iter_mut() {
+            let key = match (record.get(*index), definitions[*index]) {
+                (Some(value), Some(definition)) => unique_key(value, definition),
+                _ => None,
+            };
+            if let Some(key) = key {
+                values.insert(key);
+            }
         }
     }
-    Ok(FieldsToInsert {
-        fields,
-        target_table: from,
-        values,
-    })
+    Ok(unique_values)
 }
 
-fn execute_insert(sentence: &Insert, route: &String) -> Result<(), MiniSQLError> {
-    let headers: Vec<String>;
-    {
-        let file = file::handler::new_file_iterator(route, &sentence.target_table)?;
-        let (_, headers_file) = get_headers(file);
-        headers = headers_file
-    };
-
-    let mapped_fields = add_all_fields(&headers);
-    let mut new_file = file::handler::create_file_append(route, &sentence.target_table)?;
-    let indexes = get_required_fields(&sentence.fields, &headers)?;
-
-    for line in &sentence.values {
-        let formatted_line =
-            format_new_line(line, &indexes, &sentence.fields, mapped_fields.len())?;
-        let csv_line = formatted_line.join(",").replace("\n", "");
-        writeln!(new_file, "{}", csv_line)?;
+fn check_constraints(
+    line: &[String],
+    definitions: &[Option<&ColumnDefinition>],
+    unique_values: &mut HashMap<usize, HashSet<String>>,
+) -> Result<(), MiniSQLError> {
+    for (index, definition) in definitions.iter().enumerate() {
+        let definition = match definition {
+            Some(definition) => definition,
+            None => continue,
+        };
+        if definition.is_required() && is_null(&line[index], Some(definition.column_type)) {
+            return Err(MiniSQLError::InvalidColumn(format!(
+                "field [ {} ] can not be empty ",
+                definition.name
+            )));
+        }
     }
-
-    Ok(())
+    check_unique(line, definitions, unique_values)
 }
 
 fn format_new_line(
-    line: &[String],
-    indexes: &HashMap<String, usize>,
+    line: &[Literal],
+    positions: &[usize],
     requiered_fields: &[String],
-    line_size: usize,
+    types: &[Option<ColumnType>],
+    mut base_line: Vec<String>,
 ) -> Result<Vec<String>, MiniSQLError> {
-    let mut base_line = vec!["".to_string(); line_size];
-
     for (index, field) in requiered_fields.iter().enumerate() {
-        if let Some(line_index) = indexes.get(field) {
-            if let Some(inserted_value) = line.get(index) {
-                base_line[*line_index] = inserted_value.to_string();
-            } else {
+        match (positions.get(index), line.get(index)) {
+            (Some(line_index), Some(inserted_value)) => {
+                base_line[*line_index] =
+                    literal_to_stored(inserted_value, &types[*line_index], field)?;
+            }
+            _ => {
                 // no deberia ocurrir pero cortamos el flujo si ocurre
                 return Err(MiniSQLError::Generic(format!(
                     "program found unexpected error while inserting new lines: {} ",
                     requiered_fields.join(" ")
                 )));
             }
-        } else {
-            // no deberia ocurrir pero cortamos el flujo si ocurre
-            return Err(MiniSQLError::Generic(format!(
-                "program found unexpected error while inserting new lines: {} ",
-                requiered_fields.join(" ")
-            )));
         }
     }
     Ok(base_line)
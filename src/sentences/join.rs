@@ -0,0 +1,308 @@
+use super::common::{format_to_csv, get_headers};
+use super::conditions::{compile_condition, Coercion, Condition};
+use crate::errors::apperrors::MiniSQLError;
+use crate::file;
+use crate::parser::ast::{BinaryOperator, Expr, Join, JoinKind, SelectStatement, TableReference};
+use crate::types::value::{is_null, ColumnType, Value, NULL};
+use std::collections::{HashMap, HashSet};
+
+/// Registers read by a query, one line for each combination of the joined tables
+/// together with the number of the register of the first table it comes from
+pub type Rows = Box<dyn Iterator<Item = Result<(usize, Vec<String>), MiniSQLError>>>;
+
+/// Fields of every table on the FROM of a query, in the order they appear on each line
+#[derive(Default)]
+pub struct Scope {
+    /// one name per field, qualified when the query reads more than one table
+    pub headers: Vec<String>,
+    /// every way to reference a field: `field`, and `table.field` or `alias.field`
+    pub indexes: HashMap<String, usize>,
+    pub types: Vec<Option<ColumnType>>,
+    qualifiers: HashSet<String>,
+    /// fields present on more than one table, they can only be referenced qualified
+    ambiguous: HashSet<String>,
+}
+
+/// Table on the right side of a join, loaded in memory
+struct JoinedTable {
+    kind: JoinKind,
+    left_size: usize,
+    right_size: usize,
+    rows: Vec<Vec<String>>,
+    condition: Condition,
+    hash: Option<HashIndex>,
+}
+
+/// Registers of the right table grouped by the value of one side of an equality on the ON
+struct HashIndex {
+    left_field: usize,
+    left_type: Option<ColumnType>,
+    buckets: HashMap<String, Vec<usize>>,
+}
+
+/// Opens the tables of a `SELECT` and combines them following its joins.
+///
+/// The first table is streamed from its file. Each joined table is loaded in memory;
+/// when its ON condition has an equality between a field of each side, the registers are
+/// looked up on a hash index instead of being compared with every line.
+/// Values of different types on the ON conditions are compared following `coercion`.
+///
+/// # Errors
+///
+/// - `MiniSQLError::InvalidTable` if a table can not be opened or is referenced twice without an alias.
+/// - `MiniSQLError::InvalidColumn` if an ON condition references a missing or ambiguous field.
+/// - `MiniSQLError::InvalidSyntax` while reading, if a line can not be compared with an ON condition.
+///   The error tells the number of the register of the first table.
+pub fn open_tables(
+    select: &SelectStatement,
+    route: &String,
+    coercion: Coercion,
+) -> Result<(Scope, Rows), MiniSQLError> {
+    let qualify = !select.joins.is_empty();
+    let file_iter = file::handler::new_file_iterator(route, &select.from.name)?;
+    let (file_iter, headers) = get_headers(file_iter)?;
+    let types = file::schema::read_schema(route, &select.from.name)?.column_types(&headers)?;
+
+    let mut scope = Scope::default();
+    scope.add_table(&select.from, &headers, types, qualify)?;
+
+    let width = headers.len();
+    let mut rows: Rows = Box::new(file_iter.into_records().enumerate().map(
+        move |(position, result)| {
+            let mut line = format_to_csv(&result?);
+            // igual que en las tablas del JOIN, una fila incompleta se completa con NULL
+            line.resize(width, NULL.to_string());
+            Ok((position + 1, line))
+        },
+    ));
+    for join in &select.joins {
+        let joined = JoinedTable::load(join, route, &mut scope, coercion)?;
+        rows = Box::new(rows.flat_map(move |left| {
+            match left {
+                Ok((row, left)) => joined
+                    .combine(left)
+                    .into_iter()
+                    .map(|line| match line {
+                        Ok(line) => Ok((row, line)),
+                        Err(err) => Err(err.on_row(row)),
+                    })
+                    .collect(),
+                Err(err) => vec![Err(err)],
+            }
+        }));
+    }
+    Ok((scope, rows))
+}
+
+impl Scope {
+    /// Fails if the field is on more than one table and was not qualified
+    pub fn check_field(&self, name: &str) -> Result<(), MiniSQLError> {
+        if self.ambiguous.contains(name) {
+            return Err(MiniSQLError::InvalidColumn(format!(
+                "field [ {} ] is on more than one table, qualify it as table.{} ",
+                name, name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Checks every field referenced by a condition with `check_field`
+    pub fn check_condition(&self, condition: &Option<Expr>) -> Result<(), MiniSQLError> {
+        match condition {
+            Some(condition) => self.check_expr(condition),
+            None => Ok(()),
+        }
+    }
+
+    pub fn check_expr(&self, expr: &Expr) -> Result<(), MiniSQLError> {
+        match expr {
+            Expr::Column(name) => self.check_field(name),
+            Expr::Aggregate(aggregate) => match &aggregate.argument {
+                Some(field) => self.check_field(field),
+                None => Ok(()),
+            },
+            expr => {
+                for child in expr.children() {
+                    self.check_expr(child)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn add_table(
+        &mut self,
+        table: &TableReference,
+        headers: &[String],
+        types: Vec<Option<ColumnType>>,
+        qualify: bool,
+    ) -> Result<(), MiniSQLError> {
+        let qualifier = table.qualifier();
+        if !self.qualifiers.insert(qualifier.to_string()) {
+            return Err(MiniSQLError::InvalidTable(format!(
+                "table {} is referenced more than once, give it an alias ",
+                qualifier
+            )));
+        }
+
+        for header in headers {
+            let index = self.headers.len();
+            let qualified = format!("{}.{}", qualifier, header);
+            self.indexes.insert(qualified.to_string(), index);
+
+            // un campo repetido entre tablas solo se puede referenciar calificado
+            if self.indexes.contains_key(header) {
+                self.indexes.remove(header);
+                self.ambiguous.insert(header.to_string());
+            } else if !self.ambiguous.contains(header) {
+                self.indexes.insert(header.to_string(), index);
+            }
+
+            self.headers.push(if qualify {
+                qualified
+            } else {
+                header.to_string()
+            });
+        }
+        self.types.extend(types);
+        Ok(())
+    }
+}
+
+impl JoinedTable {
+    fn load(
+        join: &Join,
+        route: &String,
+        scope: &mut Scope,
+        coercion: Coercion,
+    ) -> Result<JoinedTable, MiniSQLError> {
+        let file_iter = file::handler::new_file_iterator(route, &join.table.name)?;
+        let (file_iter, headers) = get_headers(file_iter)?;
+        let types = file::schema::read_schema(route, &join.table.name)?.column_types(&headers)?;
+        let left_size = scope.headers.len();
+        let right_size = headers.len();
+
+        let mut rows: Vec<Vec<String>> = vec![];
+        for result in file_iter.into_records() {
+            let mut line = format_to_csv(&result?);
+            // las filas incompletas se completan para no correr los campos de las tablas siguientes
+            line.resize(right_size, NULL.to_string());
+            rows.push(line);
+        }
+
+        scope.add_table(&join.table, &headers, types, true)?;
+        scope.check_condition(&join.condition)?;
+        let condition = compile_condition(&join.condition, &scope.indexes, &scope.types, coercion)?;
+
+        let hash = match &join.condition {
+            Some(condition) => {
+                find_equality(condition, scope, left_size).map(|(left_field, right_field)| {
+                    let left_type = scope.types[left_field].or(scope.types[right_field]);
+                    let right_type = scope.types[right_field].or(scope.types[left_field]);
+                    let mut buckets: HashMap<String, Vec<usize>> = HashMap::new();
+                    for (index, line) in rows.iter().enumerate() {
+                        let key = join_key(&line[right_field - left_size], right_type);
+                        buckets.entry(key).or_default().push(index);
+                    }
+                    HashIndex {
+                        left_field,
+                        left_type,
+                        buckets,
+                    }
+                })
+            }
+            None => None,
+        };
+
+        Ok(JoinedTable {
+            kind: join.kind,
+            left_size,
+            right_size,
+            rows,
+            condition,
+            hash,
+        })
+    }
+
+    /// Lines made of `left` and each matching register, or `left` followed by NULLs on a LEFT JOIN without matches
+    fn combine(&self, mut left: Vec<String>) -> Vec<Result<Vec<String>, MiniSQLError>> {
+        left.resize(self.left_size, NULL.to_string());
+
+        let candidates: Vec<&Vec<String>> = match &self.hash {
+            Some(hash) => match hash
+                .buckets
+                .get(&join_key(&left[hash.left_field], hash.left_type))
+            {
+                Some(indexes) => indexes.iter().map(|index| &self.rows[*index]).collect(),
+                None => vec![],
+            },
+            None => self.rows.iter().collect(),
+        };
+
+        let mut result: Vec<Result<Vec<String>, MiniSQLError>> = vec![];
+        for right in candidates {
+            let mut line = left.clone();
+            line.extend(right.iter().cloned());
+            // el indice solo descarta registros, la condicion completa decide
+            match self.condition.evaluate(&line) {
+                Ok(true) => result.push(Ok(line)),
+                Ok(false) => (),
+                Err(err) => return vec![Err(err)],
+            }
+        }
+
+        if result.is_empty() && self.kind == JoinKind::Left {
+            left.resize(self.left_size + self.right_size, NULL.to_string());
+            result.push(Ok(left));
+        }
+        result
+    }
+}
+
+/// An equality between a field already read and a field of the joined table,
+/// looked for on the AND chain of the condition
+fn find_equality(condition: &Expr, scope: &Scope, left_size: usize) -> Option<(usize, usize)> {
+    match condition {
+        Expr::Binary {
+            left,
+            operator: BinaryOperator::And,
+            right,
+        } => {
+            find_equality(left, scope, left_size).or_else(|| find_equality(right, scope, left_size))
+        }
+        Expr::Binary {
+            left,
+            operator: BinaryOperator::Equal,
+            right,
+        } => match (left.as_ref(), right.as_ref()) {
+            (Expr::Column(a), Expr::Column(b)) => {
+                let a = *scope.indexes.get(a)?;
+                let b = *scope.indexes.get(b)?;
+                if a < left_size && b >= left_size {
+                    Some((a, b))
+                } else if b < left_size && a >= left_size {
+                    Some((b, a))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Normalized value used on the hash index and to group registers, so equal values
+/// written differently ( `5`, `05` and `5.0` on a numeric field ) land on the same bucket.
+/// Every NULL lands on the same bucket too, the ON condition then leaves them out.
+pub fn join_key(value: &str, column_type: Option<ColumnType>) -> String {
+    if is_null(value, column_type) {
+        return NULL.to_string();
+    }
+    let typed = match column_type {
+        Some(column_type) => column_type.parse(value),
+        None => Value::parse_number(value),
+    };
+    let typed = typed.map(|typed| typed.number_key().unwrap_or_else(|| typed.to_string()));
+    typed.unwrap_or_else(|| value.to_string())
+}
@@ -1,6 +1,16 @@
+mod aggregate;
+pub mod alter;
 mod common;
 mod conditions;
+pub mod create;
 pub mod delete;
+mod distinct;
+pub mod drop;
 pub mod insert;
+mod join;
+mod order;
 pub mod select;
+pub mod truncate;
 pub mod update;
+
+pub use conditions::Coercion;
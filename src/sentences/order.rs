@@ -0,0 +1,464 @@
+use super::conditions::Coercion;
+use crate::errors::apperrors::MiniSQLError;
+use crate::parser::ast::{Expr, Literal, OrderBy};
+use crate::types::value::{is_null, ColumnType, Value, NULL};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::rc::Rc;
+
+/// Order of the lines of a query on its ORDER BY keys, each one breaking the ties of the previous.
+///
+/// A field with a declared type is ordered by that type. The values of an undeclared field
+/// are read as integers, reals or text: a field with only numbers is ordered numerically
+/// ( whatever its size ), and on a field that mixes them numbers go before text.
+/// A stored value that is not valid for the declared type of its field goes after the valid
+/// ones, ordered as text, unless the coercion is strict.
+/// NULLs go first when ascending and last when descending, unless
+/// `NULLS FIRST` or `NULLS LAST` says otherwise.
+pub struct Sorter {
+    keys: Vec<SortKey>,
+    directions: Rc<[Direction]>,
+}
+
+struct SortKey {
+    index: usize,
+    column_type: Option<ColumnType>,
+    coercion: Coercion,
+}
+
+#[derive(Clone, Copy)]
+struct Direction {
+    ascending: bool,
+    nulls_first: bool,
+}
+
+/// Lines kept in order while they are read. With a size only the first `size` lines
+/// are kept, so the rest never stay in memory.
+pub struct OrderedLines {
+    sorter: Sorter,
+    size: Option<usize>,
+    heap: BinaryHeap<Entry>,
+    read: usize,
+}
+
+/// A line with its keys. The greatest entry on the heap is the last one in order,
+/// lines with equal keys keep the order they were read in.
+struct Entry {
+    keys: Vec<Option<Value>>,
+    sequence: usize,
+    directions: Rc<[Direction]>,
+    line: Vec<String>,
+}
+
+/// Resolves each ORDER BY key with the `indexes` and `types` of the lines to order.
+///
+/// `indexes` also has the aliases of the selected fields. A position ( `ORDER BY 2` )
+/// refers to the printed field on that position of `printed`.
+///
+/// With `Coercion::Strict` a stored value that is not valid for the declared type of its
+/// field fails the ordering.
+///
+/// # Errors
+///
+/// - `MiniSQLError::InvalidSyntax` if a key is not a field of the lines or a valid position.
+pub fn compile_order(
+    order_by: &[OrderBy],
+    indexes: &HashMap<String, usize>,
+    types: &[Option<ColumnType>],
+    printed: &[usize],
+    coercion: Coercion,
+) -> Result<Sorter, MiniSQLError> {
+    let mut keys: Vec<SortKey> = vec![];
+    let mut directions: Vec<Direction> = vec![];
+    for key in order_by {
+        let index = resolve_key(&key.expr, indexes, printed)?;
+        keys.push(SortKey {
+            index,
+            column_type: types.get(index).copied().flatten(),
+            coercion,
+        });
+        directions.push(Direction {
+            ascending: key.ascending,
+            nulls_first: key.nulls_first.unwrap_or(key.ascending),
+        });
+    }
+    Ok(Sorter {
+        keys,
+        directions: directions.into(),
+    })
+}
+
+/// Position on the line of a key of ORDER BY or DISTINCT ON: a field, an alias,
+/// an aggregate or a position on the printed fields
+pub fn resolve_key(
+    expr: &Expr,
+    indexes: &HashMap<String, usize>,
+    printed: &[usize],
+) -> Result<usize, MiniSQLError> {
+    match expr {
+        Expr::Literal(Literal::Number(position)) => match position.parse::<usize>() {
+            Ok(position) if position >= 1 && position <= printed.len() => Ok(printed[position - 1]),
+            _ => Err(MiniSQLError::InvalidSyntax(format!(
+                "ORDER BY position {} is not on the selected fields ",
+                position
+            ))),
+        },
+        expr => match indexes.get(&expr.to_string()) {
+            Some(index) => Ok(*index),
+            None => Err(MiniSQLError::InvalidSyntax(format!(
+                "Invalid field to order: {} ",
+                expr
+            ))),
+        },
+    }
+}
+
+impl SortKey {
+    /// `None` for NULL
+    fn value(&self, line: &[String]) -> Result<Option<Value>, MiniSQLError> {
+        let text = line.get(self.index).map(|s| s.as_str()).unwrap_or(NULL);
+        if is_null(text, self.column_type) {
+            return Ok(None);
+        }
+
+        match self.column_type {
+            Some(column_type) => match column_type.parse(text) {
+                Some(value) => Ok(Some(value)),
+                // el texto va despues de cualquier valor del tipo declarado
+                None if self.coercion == Coercion::Implicit => {
+                    Ok(Some(Value::Text(text.to_string())))
+                }
+                None => Err(MiniSQLError::InvalidSyntax(format!(
+                    "unable to order by {}, it is not a valid {} ",
+                    text, column_type
+                ))),
+            },
+            None => Ok(Some(Value::infer(text))),
+        }
+    }
+}
+
+impl OrderedLines {
+    pub fn new(sorter: Sorter, size: Option<usize>) -> OrderedLines {
+        OrderedLines {
+            sorter,
+            size,
+            heap: BinaryHeap::new(),
+            read: 0,
+        }
+    }
+
+    pub fn push(&mut self, line: Vec<String>) -> Result<(), MiniSQLError> {
+        let mut keys: Vec<Option<Value>> = vec![];
+        for key in &self.sorter.keys {
+            keys.push(key.value(&line)?);
+        }
+        self.heap.push(Entry {
+            keys,
+            sequence: self.read,
+            directions: Rc::clone(&self.sorter.directions),
+            line,
+        });
+        self.read += 1;
+
+        // se descarta la ultima linea en orden, nunca se guardan mas de `size`
+        if let Some(size) = self.size {
+            if self.heap.len() > size {
+                self.heap.pop();
+            }
+        }
+        Ok(())
+    }
+
+    pub fn into_lines(self) -> Vec<Vec<String>> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|entry| entry.line)
+            .collect()
+    }
+}
+
+fn compare_keys(a: &Option<Value>, b: &Option<Value>, direction: &Direction) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => {
+            let ordering = a.total_cmp(b);
+            if direction.ascending {
+                ordering
+            } else {
+                ordering.reverse()
+            }
+        }
+        (None, Some(_)) if direction.nulls_first => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) if direction.nulls_first => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+impl Ord for Entry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.keys
+            .iter()
+            .zip(other.keys.iter())
+            .zip(self.directions.iter())
+            .map(|((a, b), direction)| compare_keys(a, b, direction))
+            .find(|ordering| *ordering != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+            .then(self.sequence.cmp(&other.sequence))
+    }
+}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Entry {}
+
+#[cfg(test)]
+mod test_order {
+    use super::*;
+
+    fn key(expr: Expr, ascending: bool, nulls_first: Option<bool>) -> OrderBy {
+        OrderBy {
+            expr,
+            ascending,
+            nulls_first,
+        }
+    }
+
+    fn order_lines(
+        lines: &[&[&str]],
+        order_by: &[OrderBy],
+        types: &[Option<ColumnType>],
+        size: Option<usize>,
+        coercion: Coercion,
+    ) -> Result<Vec<String>, MiniSQLError> {
+        let indexes = HashMap::from([("x".to_string(), 0), ("y".to_string(), 1)]);
+        let sorter = compile_order(order_by, &indexes, types, &[1, 0], coercion)?;
+        let mut ordered = OrderedLines::new(sorter, size);
+        for line in lines {
+            ordered.push(line.iter().map(|value| value.to_string()).collect())?;
+        }
+        Ok(ordered
+            .into_lines()
+            .into_iter()
+            .map(|line| line.join(" "))
+            .collect())
+    }
+
+    fn order(
+        values: &[&str],
+        column_type: Option<ColumnType>,
+        ascending: bool,
+        size: Option<usize>,
+    ) -> Result<Vec<String>, MiniSQLError> {
+        order_with(values, column_type, ascending, size, Coercion::Implicit)
+    }
+
+    fn order_with(
+        values: &[&str],
+        column_type: Option<ColumnType>,
+        ascending: bool,
+        size: Option<usize>,
+        coercion: Coercion,
+    ) -> Result<Vec<String>, MiniSQLError> {
+        let positions: Vec<String> = (0..values.len())
+            .map(|position| position.to_string())
+            .collect();
+        let lines: Vec<Vec<&str>> = values
+            .iter()
+            .zip(positions.iter())
+            .map(|(value, position)| vec![*value, position.as_str()])
+            .collect();
+        let lines: Vec<&[&str]> = lines.iter().map(|line| line.as_slice()).collect();
+        order_lines(
+            &lines,
+            &[key(Expr::Column("x".to_string()), ascending, None)],
+            &[column_type],
+            size,
+            coercion,
+        )
+    }
+
+    #[test]
+    fn test_undeclared_field_numbers_before_text() {
+        assert_eq!(
+            order(&["10", "9", "x"], None, true, None),
+            Ok(vec![
+                "9 1".to_string(),
+                "10 0".to_string(),
+                "x 2".to_string()
+            ])
+        );
+        assert_eq!(
+            order(&["b", "10", "9.5", "", "a"], None, false, None),
+            Ok(vec![
+                "b 0".to_string(),
+                "a 4".to_string(),
+                " 3".to_string(),
+                "10 1".to_string(),
+                "9.5 2".to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn test_undeclared_field_beyond_i32() {
+        assert_eq!(
+            order(&["5551234567", "999", "12345678901"], None, true, None),
+            Ok(vec![
+                "999 1".to_string(),
+                "5551234567 0".to_string(),
+                "12345678901 2".to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn test_equal_keys_keep_their_order() {
+        assert_eq!(
+            order(&["1", "2", "1", "2"], None, false, None),
+            Ok(vec![
+                "2 1".to_string(),
+                "2 3".to_string(),
+                "1 0".to_string(),
+                "1 2".to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn test_keeps_only_the_first_lines() {
+        assert_eq!(
+            order(&["5", "3", "9", "1", "3"], None, true, Some(3)),
+            Ok(vec![
+                "1 3".to_string(),
+                "3 1".to_string(),
+                "3 4".to_string()
+            ])
+        );
+        assert_eq!(order(&["5", "3"], None, true, Some(0)), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_typed_field_with_empty_values() {
+        assert_eq!(
+            order(&["2.5", "", "10"], Some(ColumnType::Real), false, Some(2)),
+            Ok(vec!["10 2".to_string(), "2.5 0".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_typed_field_with_invalid_values() {
+        // sin --strict el valor invalido va despues de los validos, ordenado como texto
+        assert_eq!(
+            order(
+                &["abc", "10", "ab", "2.5"],
+                Some(ColumnType::Real),
+                true,
+                None
+            ),
+            Ok(vec![
+                "2.5 3".to_string(),
+                "10 1".to_string(),
+                "ab 2".to_string(),
+                "abc 0".to_string()
+            ])
+        );
+        assert_eq!(
+            order_with(
+                &["2.5", "x"],
+                Some(ColumnType::Real),
+                true,
+                None,
+                Coercion::Strict
+            ),
+            Err(MiniSQLError::InvalidSyntax(
+                "unable to order by x, it is not a valid REAL ".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_several_keys_with_their_direction() {
+        let lines: &[&[&str]] = &[&["b", "1"], &["a", "1"], &["b", "2"], &["a", "3"]];
+        assert_eq!(
+            order_lines(
+                lines,
+                &[
+                    key(Expr::Column("x".to_string()), true, None),
+                    key(Expr::Column("y".to_string()), false, None)
+                ],
+                &[],
+                None,
+                Coercion::Implicit
+            ),
+            Ok(vec![
+                "a 3".to_string(),
+                "a 1".to_string(),
+                "b 2".to_string(),
+                "b 1".to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn test_order_by_position() {
+        let lines: &[&[&str]] = &[&["a", "2"], &["b", "1"]];
+        let by_position = |position: &str| {
+            order_lines(
+                lines,
+                &[key(
+                    Expr::Literal(Literal::Number(position.to_string())),
+                    true,
+                    None,
+                )],
+                &[],
+                None,
+                Coercion::Implicit,
+            )
+        };
+        assert_eq!(
+            by_position("1"),
+            Ok(vec!["b 1".to_string(), "a 2".to_string()])
+        );
+        assert_eq!(
+            by_position("3"),
+            Err(MiniSQLError::InvalidSyntax(
+                "ORDER BY position 3 is not on the selected fields ".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_nulls_first_and_last() {
+        let lines: &[&[&str]] = &[&["2", "a"], &["", "b"], &["1", "c"]];
+        let ordered = |ascending: bool, nulls_first: Option<bool>| {
+            order_lines(
+                lines,
+                &[key(Expr::Column("x".to_string()), ascending, nulls_first)],
+                &[Some(ColumnType::Integer)],
+                None,
+                Coercion::Implicit,
+            )
+            .unwrap()
+            .iter()
+            .map(|line| line[line.len() - 1..].to_string())
+            .collect::<String>()
+        };
+        assert_eq!(ordered(true, None), "bca");
+        assert_eq!(ordered(false, None), "acb");
+        assert_eq!(ordered(true, Some(false)), "cab");
+        assert_eq!(ordered(false, Some(true)), "bac");
+    }
+}
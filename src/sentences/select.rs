@@ -1,34 +1,73 @@
-use super::common::{add_all_fields, format_to_csv, get_headers, get_required_fields};
-use super::conditions::get_query;
+use super::aggregate::{compile_aggregate, Accumulator, Grouping};
+use super::common::get_required_fields;
+use super::conditions::{compile_condition, compile_scalar, Coercion, Scalar};
+use super::distinct::{self, Deduplicator, KeyFields};
+use super::join::{self, Rows, Scope};
+use super::order::{compile_order, resolve_key, OrderedLines, Sorter};
 use crate::errors::apperrors::MiniSQLError;
-use crate::file;
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use crate::parser::ast::{Aggregate, Distinct, Expr, SelectItem, SelectStatement};
+use crate::types::value::{ColumnType, NULL};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
 
-/// Executes a `SELECT` query with the provided SQL string.
+/// Executes a parsed `SELECT` statement.
 ///
 /// This function encapsulates the entire lifecycle of a `SELECT`,
 /// including the creation, execution, and handling of the query.
 ///
+/// The fields are printed in the order they were selected. A field can be selected more
+/// than once, renamed with `AS alias` and mixed with `*`, which prints every field on its place.
+/// Expressions such as `precio * cantidad AS total` or `nombre || ' ' || apellido` are
+/// computed for each register with the same rules as the operands of WHERE.
+///
 /// For the ORDER BY functionality, it defaults to ASC if the sort type is not specified.
 /// If ORDER BY is not provided, the output will be in the order the data was read.
+/// Several keys can be given, each with its own direction and `NULLS FIRST` or `NULLS LAST`,
+/// and a key can be a field, an alias of a selected field or its position ( `ORDER BY 2` ).
+/// Registers with equal keys keep the order they were read in. A key can also be a date
+/// function such as `EXTRACT(YEAR FROM alta)`, even if it is not selected.
+/// A field with a declared type is ordered by that type. An undeclared field is ordered
+/// numerically when its values are numbers, with numbers before text when they are mixed.
+/// NULLs go first when ascending.
+///
+/// `DISTINCT` prints equal registers once, `DISTINCT ON (keys)` prints the first register
+/// of each key once they are ordered. Keys that do not fit in memory are spilled to disk.
+///
+/// `LIMIT n [OFFSET m]` or `FETCH FIRST n ROWS ONLY` print only part of the registers.
+/// Without ORDER BY the table stops being read once they are printed; with ORDER BY
+/// only the first `n + m` registers in order are kept while reading.
+///
+/// Aggregate functions ( `COUNT(*)`, `COUNT([DISTINCT] field)`, `SUM`, `AVG`, `MIN`, `MAX` )
+/// are computed while the table is read and print a single register, or one register per
+/// group with `GROUP BY`. `HAVING` filters the groups and may use aggregate functions.
+/// A field selected outside of an aggregate must appear on `GROUP BY`.
+///
+/// Several tables can be combined with `JOIN ... ON`, `LEFT JOIN`, `CROSS JOIN` or commas,
+/// and their fields referenced as `table.field` or `alias.field`.
+///
+/// Values of different types on WHERE, ON and HAVING are compared following `coercion`.
+/// A stored value that is not valid for the type of its field is ordered as text, compared as
+/// text by MIN and MAX and left out of SUM and AVG, unless `coercion` is strict.
+/// An error on a register tells its number on the first table.
 ///
 /// The retrieved registers will be printed through STDOUT
 ///
 /// # Examples
 ///
 /// ```
-/// execute_select_statement(["SELECT", "*", "FROM", "users"], &"user/data/tables");
-/// execute_select_statement(["SELECT", "nombre", ",", "apellido", "FROM", "users", "WHERE", "id", "=", "5"], &"user/data/tables");
-/// execute_select_statement(["SELECT", "*", "FROM", "users", "WHERE", "id", "=", "5", "ORDER", "BY", "nombre", "DESC"], &"user/data/tables");
+/// if let Statement::Select(select) = parse("SELECT * FROM users WHERE id = 5 ORDER BY nombre DESC")? {
+///     execute_select_statement(select, &"user/data/tables", Coercion::Implicit);
+/// }
+/// if let Statement::Select(select) = parse("SELECT c.nombre, o.producto FROM clientes c JOIN ordenes o ON c.id_cliente = o.id_cliente")? {
+///     execute_select_statement(select, &"user/data/tables", Coercion::Implicit);
+/// }
 /// ```
 ///
 /// # Errors
 ///
 /// This function will return an error of type `MiniSQLError` if:
 ///
-/// - The SQL string is invalid.
+/// - A referenced field does not exist on the table.
 /// - The provided table is invalid.
 /// - The query fails for any other reason.
 ///
@@ -38,327 +77,541 @@ use std::io::{BufRead, BufReader};
 /// - `Err(MiniSQLError)` if an error occurs during execution.
 ///
 pub fn execute_select_statement(
-    sententence_vec: Vec<String>,
+    select: SelectStatement,
     route: &String,
+    coercion: Coercion,
 ) -> Result<(), MiniSQLError> {
-    let select = new_select(sententence_vec)?;
-    let file_iter = file::handler::new_file_iterator(route, &select.target_table)?;
+    let (scope, rows) = join::open_tables(&select, route, coercion)?;
 
-    execute_select(&select, file_iter)?;
+    execute_select(&select, &scope, rows, coercion)?;
     Ok(())
 }
 
-/// Contains all requiered data to execute a SELECT statement given row values
-struct Select {
-    /// FROM --> target_table
-    target_table: String,
-    /// WHERE --> condition ; as a vector of each part, id = 1 --> ["id", "=", "1"]
-    condition: Vec<String>,
-    /// SELECT --> mapped_fields; a vector contained fields requiered to display
-    mapped_fields: Vec<String>,
-    /// ORDER BY --> order ; if type is absent ASC will be taken as default
-    order: Vec<String>,
-}
-
-struct FieldsToSelect {
-    target_table: Vec<String>,
-    condition: Vec<String>,
-    mapped_fields: Vec<String>,
-    order: Vec<String>,
-}
-
-fn new_select(sentence_parts: Vec<String>) -> Result<Select, MiniSQLError> {
-    decode_select(sentence_parts)
-}
-
-fn decode_select(sentence_parts: Vec<String>) -> Result<Select, MiniSQLError> {
-    let fields: Vec<String> = Vec::new();
-    let condition: Vec<String> = Vec::new();
-    let order_by: Vec<String> = Vec::new();
-    let from: Vec<String> = Vec::new();
-
-    let fields_to_select = match_fields_select(sentence_parts, fields, condition, from, order_by)?;
-
-    let table = validate_select_fields(fields_to_select.target_table, &fields_to_select.order)?;
-
-    Ok(Select {
-        target_table: table,
-        condition: fields_to_select.condition,
-        mapped_fields: fields_to_select.mapped_fields,
-        order: fields_to_select.order,
-    })
-}
+fn execute_select(
+    select: &SelectStatement,
+    scope: &Scope,
+    rows: Rows,
+    coercion: Coercion,
+) -> Result<(), MiniSQLError> {
+    scope.check_condition(&select.condition)?;
+
+    let aggregated = select.projection.iter().any(|item| match item {
+        SelectItem::Aggregate { .. } => true,
+        SelectItem::Expr { expr, .. } => has_aggregate(expr),
+        _ => false,
+    }) || key_exprs(select)
+        .iter()
+        .any(|key| matches!(key, Expr::Aggregate(_)));
+    if aggregated || !select.group_by.is_empty() || select.having.is_some() {
+        return execute_grouped_select(select, scope, rows, coercion);
+    }
 
-fn match_fields_select(
-    sentence_parts: Vec<String>,
-    mut fields: Vec<String>,
-    mut condition: Vec<String>,
-    mut from: Vec<String>,
-    mut order_by: Vec<String>,
-) -> Result<FieldsToSelect, MiniSQLError> {
-    let mut base = "";
-    for part in &sentence_parts {
-        match part.as_str() {
-            "SELECT" => {
-                base = "fields";
-                continue;
-            }
-            "WHERE" => {
-                base = "condition";
-                continue;
-            }
-            "FROM" => {
-                base = "from";
-                continue;
+    // cada expresion de la proyeccion ocupa un campo mas, despues de los campos de las tablas
+    let width = scope.headers.len();
+    let mut indexes = scope.indexes.clone();
+    let mut types = scope.types.clone();
+    let mut scalars: Vec<Scalar> = vec![];
+    let mut projection: Vec<String> = vec![];
+    for item in &select.projection {
+        match item {
+            SelectItem::Column { name, .. } => {
+                scope.check_field(name)?;
+                projection.push(name.to_string());
             }
-            "ORDER" => {
-                base = "order";
-                continue;
-            }
-            "BY" => {
-                if base == "order" {
-                    base = "order by"
+            SelectItem::Expr { expr, .. } => {
+                scope.check_expr(expr)?;
+                if let Entry::Vacant(entry) = indexes.entry(expr.to_string()) {
+                    scalars.push(compile_scalar(expr, &scope.indexes, &scope.types)?);
+                    entry.insert(width + scalars.len() - 1);
+                    types.push(None);
                 }
-                continue;
+                projection.push(expr.to_string());
             }
-            _ => (), // los anteriores casos son los que separan segmentos, aca cae todo lo demas
+            _ => projection.push("*".to_string()),
         }
-
-        match base {
-            "fields" => fields.push(part.to_string()),
-            "condition" => condition.push(part.to_string()),
-            "from" => from.push(part.to_string()),
-            "order by" => order_by.push(part.to_string()),
-            _ => {
-                return Err(MiniSQLError::InvalidSyntax(format!(
-                    "Invalid sentence: {} ",
-                    sentence_parts.join(" ")
-                )))
+    }
+    // una funcion de ORDER BY que no se selecciono se calcula igual, sin imprimirse
+    for key in key_exprs(select) {
+        if let Expr::Function(_) = key {
+            scope.check_expr(key)?;
+            if let Entry::Vacant(entry) = indexes.entry(key.to_string()) {
+                scalars.push(compile_scalar(key, &scope.indexes, &scope.types)?);
+                entry.insert(width + scalars.len() - 1);
+                types.push(None);
             }
         }
     }
+    for field in key_fields(select) {
+        scope.check_field(field)?;
+    }
+    let indices = get_required_fields(&projection, &scope.headers, &indexes)?;
+    add_aliases(select, &mut indexes);
+    let distinct = compile_distinct(select, &indexes, &types, &indices)?;
+    let sorter = if select.order_by.is_empty() {
+        None
+    } else {
+        Some(compile_order(
+            &select.order_by,
+            &indexes,
+            &types,
+            &indices,
+            coercion,
+        )?)
+    };
 
-    Ok(FieldsToSelect {
-        target_table: from,
-        condition,
-        mapped_fields: fields,
-        order: order_by,
-    })
+    print_result(
+        select,
+        |on_line| {
+            apply_select_to_file(select, rows, scope, coercion, |_, mut line| {
+                for scalar in &scalars {
+                    let value = scalar.evaluate(&line)?;
+                    line.push(value);
+                }
+                on_line(line)
+            })
+        },
+        sorter,
+        distinct,
+        &indices,
+    )
 }
 
-fn validate_select_fields(
-    mut from: Vec<String>,
-    order_by: &[String],
-) -> Result<String, MiniSQLError> {
-    let table: String;
-
-    if let Some(first) = from.pop() {
-        table = first
-    } else {
-        return Err(MiniSQLError::InvalidTable(
-            "no table was given ".to_string(),
-        ));
+/// Registers are grouped by the GROUP BY fields while they are read, updating the aggregates
+/// of their group. Each group becomes a line with its first register followed by the result
+/// of every aggregate, so HAVING and ORDER BY are evaluated over those lines.
+/// Selected expressions are computed over those lines too, after HAVING.
+fn execute_grouped_select(
+    select: &SelectStatement,
+    scope: &Scope,
+    rows: Rows,
+    coercion: Coercion,
+) -> Result<(), MiniSQLError> {
+    let mut group_fields: Vec<(usize, Option<ColumnType>)> = vec![];
+    for field in &select.group_by {
+        let index = find_field(scope, field)?;
+        group_fields.push((index, scope.types.get(index).copied().flatten()));
+    }
+    let grouped: HashSet<usize> = group_fields.iter().map(|(index, _)| *index).collect();
+
+    // los agregados de la proyeccion y de HAVING se calculan una sola vez cada uno
+    let mut aggregates: Vec<Aggregate> = vec![];
+    for item in &select.projection {
+        match item {
+            SelectItem::Aggregate { aggregate, .. } => add_aggregate(&mut aggregates, aggregate),
+            SelectItem::Column { name, .. } => check_grouped(scope, &grouped, name)?,
+            SelectItem::Expr { expr, .. } => {
+                scope.check_expr(expr)?;
+                collect_aggregates(scope, &grouped, expr, &mut aggregates)?;
+            }
+            SelectItem::Wildcard => {
+                return Err(MiniSQLError::InvalidSyntax(
+                    "* can not be selected together with aggregate functions ".to_string(),
+                ))
+            }
+        }
+    }
+    if let Some(having) = &select.having {
+        scope.check_condition(&select.having)?;
+        collect_aggregates(scope, &grouped, having, &mut aggregates)?;
+    }
+    for key in key_exprs(select) {
+        if let Expr::Aggregate(aggregate) = key {
+            add_aggregate(&mut aggregates, aggregate);
+        }
+    }
+    for field in key_fields(select) {
+        check_grouped(scope, &grouped, field)?;
     }
 
-    if table.is_empty() {
-        return Err(MiniSQLError::InvalidTable(
-            "no table was given ".to_string(),
-        ));
+    let mut accumulators: Vec<Accumulator> = vec![];
+    for aggregate in &aggregates {
+        if let Some(field) = &aggregate.argument {
+            scope.check_field(field)?;
+        }
+        accumulators.push(compile_aggregate(
+            aggregate,
+            &scope.indexes,
+            &scope.types,
+            coercion,
+        )?);
     }
 
-    if !from.is_empty() {
-        return Err(MiniSQLError::InvalidTable(
-            "multiple references for table".to_string(),
-        ));
+    // cada agregado ocupa un campo mas, despues de los campos de las tablas
+    let width = scope.headers.len();
+    let mut indexes = scope.indexes.clone();
+    let mut types = scope.types.clone();
+    for (position, accumulator) in accumulators.iter().enumerate() {
+        indexes.insert(aggregates[position].to_string(), width + position);
+        types.push(accumulator.result_type());
     }
+    let having = compile_condition(&select.having, &indexes, &types, coercion)?;
+    let mut scalars: Vec<Scalar> = vec![];
+    for item in &select.projection {
+        if let SelectItem::Expr { expr, .. } = item {
+            if !indexes.contains_key(&expr.to_string()) {
+                scalars.push(compile_scalar(expr, &indexes, &types)?);
+                indexes.insert(expr.to_string(), types.len());
+                types.push(None);
+            }
+        }
+    }
+    let printed: Vec<usize> = select
+        .projection
+        .iter()
+        .filter_map(|item| match item {
+            SelectItem::Aggregate { aggregate, .. } => indexes.get(&aggregate.to_string()).copied(),
+            SelectItem::Column { name, .. } => indexes.get(name).copied(),
+            SelectItem::Expr { expr, .. } => indexes.get(&expr.to_string()).copied(),
+            SelectItem::Wildcard => None,
+        })
+        .collect();
+    add_aliases(select, &mut indexes);
+
+    let mut grouping = Grouping::new(group_fields, accumulators);
+    apply_select_to_file(select, rows, scope, coercion, |row, line| {
+        grouping.update(line).map_err(|err| err.on_row(row))?;
+        Ok(true)
+    })?;
 
-    if order_by.len() > 2 {
-        return Err(MiniSQLError::InvalidTable(
-            "multiple references for table".to_string(),
-        ));
+    let mut response: Vec<Vec<String>> = vec![];
+    for mut line in grouping.into_lines(width) {
+        if having.evaluate(&line)? {
+            for scalar in &scalars {
+                let value = scalar.evaluate(&line)?;
+                line.push(value);
+            }
+            response.push(line);
+        }
     }
+    let distinct = compile_distinct(select, &indexes, &types, &printed)?;
+    let sorter = if select.order_by.is_empty() {
+        None
+    } else {
+        Some(compile_order(
+            &select.order_by,
+            &indexes,
+            &types,
+            &printed,
+            coercion,
+        )?)
+    };
 
-    Ok(table)
+    print_result(
+        select,
+        |on_line| {
+            for line in response {
+                if !on_line(line)? {
+                    break;
+                }
+            }
+            Ok(())
+        },
+        sorter,
+        distinct,
+        &printed,
+    )
 }
 
-fn execute_select(select: &Select, file_iter: BufReader<File>) -> Result<(), MiniSQLError> {
-    let (file_iter, headers) = get_headers(file_iter);
-    let requiered_fields = get_required_fields(&select.mapped_fields, &headers)?;
-    let mapped_fields = add_all_fields(&headers);
+/// Prints the lines handed by `source` after DISTINCT, ORDER BY, OFFSET and LIMIT.
+///
+/// Without ORDER BY each line is printed as soon as it is known to be new, and `source`
+/// is told to stop once LIMIT is reached. With ORDER BY the lines are deduplicated before
+/// being ordered, except on DISTINCT ON, that keeps the first line of each key once ordered.
+fn print_result(
+    select: &SelectStatement,
+    source: impl FnOnce(
+        &mut dyn FnMut(Vec<String>) -> Result<bool, MiniSQLError>,
+    ) -> Result<(), MiniSQLError>,
+    sorter: Option<Sorter>,
+    distinct: Option<KeyFields>,
+    printed: &[usize],
+) -> Result<(), MiniSQLError> {
+    let mut output = Output::new(select, printed);
+
+    let sorter = match sorter {
+        Some(sorter) => sorter,
+        None => {
+            let mut deduplicator = distinct.map(Deduplicator::new);
+            source(&mut |line| match &mut deduplicator {
+                Some(deduplicator) => match deduplicator.push(line)? {
+                    Some(line) => Ok(output.print(&line)),
+                    None => Ok(true),
+                },
+                None => Ok(output.print(&line)),
+            })?;
+            if let Some(deduplicator) = deduplicator {
+                if !output.is_done() {
+                    deduplicator.finish(|line| Ok(output.print(&line)))?;
+                }
+            }
+            return Ok(());
+        }
+    };
 
-    let mut response = apply_select_to_file(select, file_iter, &mapped_fields)?;
-    response = order_response(response, &select.order, &mapped_fields)?;
-    print_selected_registers(response, requiered_fields);
+    let (mut deduplicator, distinct_on) = match select.distinct {
+        Some(Distinct::On(_)) => (None, distinct),
+        _ => (distinct.map(Deduplicator::new), None),
+    };
+    // DISTINCT ON descarta lineas despues de ordenar, entonces no se puede acotar el orden a LIMIT
+    let size = match distinct_on {
+        Some(_) => None,
+        None => select.limit.map(|_| output.end),
+    };
+    let mut ordered = OrderedLines::new(sorter, size);
+    source(&mut |line| {
+        match &mut deduplicator {
+            Some(deduplicator) => {
+                if let Some(line) = deduplicator.push(line)? {
+                    ordered.push(line)?;
+                }
+            }
+            None => ordered.push(line)?,
+        }
+        Ok(true)
+    })?;
+    if let Some(deduplicator) = deduplicator {
+        deduplicator.finish(|line| {
+            ordered.push(line)?;
+            Ok(true)
+        })?;
+    }
 
+    // las claves de DISTINCT ON encabezan el orden, las lineas repetidas quedan juntas
+    let mut previous: Option<Vec<String>> = None;
+    let lines = ordered.into_lines();
+    for line in &lines {
+        if let Some(fields) = &distinct_on {
+            let key = distinct::key(line, fields);
+            if previous.as_ref() == Some(&key) {
+                continue;
+            }
+            previous = Some(key);
+        }
+        if !output.print(line) {
+            break;
+        }
+    }
     Ok(())
 }
 
-fn apply_select_to_file(
-    select: &Select,
-    file_iter: BufReader<File>,
-    mapped_fields: &HashMap<String, usize>,
-) -> Result<Vec<Vec<String>>, MiniSQLError> {
-    let mut response: Vec<Vec<String>> = vec![];
+/// Prints the lines between OFFSET and LIMIT
+struct Output<'a> {
+    printed: &'a [usize],
+    offset: usize,
+    end: usize,
+    read: usize,
+}
 
-    for result in file_iter.lines() {
-        let record = result?;
-        let line = format_to_csv(record);
-        let should_apply = get_query(
-            &select.condition,
-            0,
-            select.condition.len(),
-            mapped_fields,
-            &line,
-        )?;
-        if should_apply {
-            response.push(line)
+impl<'a> Output<'a> {
+    fn new(select: &SelectStatement, printed: &'a [usize]) -> Output<'a> {
+        Output {
+            printed,
+            offset: select.offset,
+            end: select
+                .offset
+                .saturating_add(select.limit.unwrap_or(usize::MAX)),
+            read: 0,
         }
     }
 
-    Ok(response)
+    /// Returns whether more lines can be printed
+    fn print(&mut self, line: &[String]) -> bool {
+        if self.is_done() {
+            return false;
+        }
+        if self.read >= self.offset {
+            print_register(line, self.printed);
+        }
+        self.read += 1;
+        !self.is_done()
+    }
+
+    fn is_done(&self) -> bool {
+        self.read >= self.end
+    }
 }
 
-fn print_selected_registers(response: Vec<Vec<String>>, requiered_fields: HashMap<String, usize>) {
-    for register in response {
-        let mut indices: Vec<usize> = requiered_fields.values().cloned().collect();
-        indices.sort();
+/// Positions and types of the DISTINCT keys: every printed field, or the keys of DISTINCT ON
+///
+/// # Errors
+///
+/// - `MiniSQLError::InvalidSyntax` if a key of DISTINCT ON is not a field, or ORDER BY
+///   does not start with the keys of DISTINCT ON.
+fn compile_distinct(
+    select: &SelectStatement,
+    indexes: &HashMap<String, usize>,
+    types: &[Option<ColumnType>],
+    printed: &[usize],
+) -> Result<Option<KeyFields>, MiniSQLError> {
+    let with_types = |indices: &[usize]| {
+        indices
+            .iter()
+            .map(|index| (*index, types.get(*index).copied().flatten()))
+            .collect()
+    };
+    let keys = match &select.distinct {
+        None => return Ok(None),
+        Some(Distinct::All) => return Ok(Some(with_types(printed))),
+        Some(Distinct::On(keys)) => keys,
+    };
 
-        // Imprimo los valores en el orden correcto
-        let mut print_line: Vec<&str> = vec![];
-        for index in indices {
-            if let Some(value) = register.get(index) {
-                print_line.push(value);
-            }
+    let mut indices: Vec<usize> = vec![];
+    for key in keys {
+        indices.push(resolve_key(key, indexes, printed)?);
+    }
+    if !select.order_by.is_empty() {
+        let mut leading: HashSet<usize> = HashSet::new();
+        for key in select.order_by.iter().take(indices.len()) {
+            leading.insert(resolve_key(&key.expr, indexes, printed)?);
+        }
+        if leading != indices.iter().copied().collect() {
+            return Err(MiniSQLError::InvalidSyntax(
+                "DISTINCT ON keys must match the first ORDER BY keys ".to_string(),
+            ));
         }
-        let joined_line = print_line.join(", ");
-        println!("{}", joined_line);
     }
+    Ok(Some(with_types(&indices)))
 }
 
-fn order_response(
-    response: Vec<Vec<String>>,
-    order_by: &[String],
-    mapped_fields: &HashMap<String, usize>,
-) -> Result<Vec<Vec<String>>, MiniSQLError> {
-    if order_by.is_empty() {
-        return Ok(response);
+fn find_field(scope: &Scope, name: &str) -> Result<usize, MiniSQLError> {
+    scope.check_field(name)?;
+    match scope.indexes.get(name) {
+        Some(index) => Ok(*index),
+        None => Err(MiniSQLError::InvalidColumn(format!(
+            "requested field [ {} ] could not be found",
+            name
+        ))),
     }
+}
 
-    let reference_field: &usize = if let Some(search) = order_by.first() {
-        if let Some(field_index) = mapped_fields.get(search) {
-            field_index
-        } else {
-            return Err(MiniSQLError::InvalidSyntax(format!(
-                "Invalid field to order: {} ",
-                search
-            )));
-        }
-    } else {
+/// A field outside of an aggregate has a single value per group only if it is on GROUP BY
+fn check_grouped(scope: &Scope, grouped: &HashSet<usize>, name: &str) -> Result<(), MiniSQLError> {
+    if !grouped.contains(&find_field(scope, name)?) {
         return Err(MiniSQLError::InvalidSyntax(format!(
-            "unexpected error while trying to order: {} ",
-            order_by.join(" ")
+            "field {} must appear on GROUP BY or be used inside an aggregate function ",
+            name
         )));
-    };
-
-    let order_style = get_order_style(order_by)?;
-
-    if response.len() < 2 {
-        // descartando este caso, puedo tomar el primero
-        return Ok(response);
     }
+    Ok(())
+}
 
-    let (was_numeric, mut response) =
-        order_numeric(response, reference_field, order_style, order_by)?;
-    if was_numeric {
-        return Ok(response);
+/// Keys of ORDER BY and DISTINCT ON
+fn key_exprs(select: &SelectStatement) -> Vec<&Expr> {
+    let mut keys: Vec<&Expr> = select.order_by.iter().map(|key| &key.expr).collect();
+    if let Some(Distinct::On(distinct)) = &select.distinct {
+        keys.extend(distinct.iter());
     }
+    keys
+}
 
-    response = order_literal(response, reference_field, order_style);
-    Ok(response)
+/// Fields referenced by ORDER BY or DISTINCT ON that are not aliases of a selected field
+fn key_fields(select: &SelectStatement) -> Vec<&str> {
+    key_exprs(select)
+        .into_iter()
+        .filter_map(|key| match key {
+            Expr::Column(name) => Some(name.as_str()),
+            _ => None,
+        })
+        .filter(|name| {
+            !select
+                .projection
+                .iter()
+                .any(|item| item.alias() == Some(name))
+        })
+        .collect()
 }
 
-fn get_order_style(order_by: &[String]) -> Result<&str, MiniSQLError> {
-    let mut order_style = "ASC";
-
-    if let Some(order_type) = order_by.get(1) {
-        if order_type == "DESC" {
-            order_style = order_type;
-        } else if order_type != "ASC" {
-            return Err(MiniSQLError::InvalidSyntax(format!(
-                "Invalid order type (must be ASC/DESC): {} ",
-                order_type
-            )));
+/// Each alias refers to the same position as the field, aggregate or expression it names
+fn add_aliases(select: &SelectStatement, indexes: &mut HashMap<String, usize>) {
+    for item in &select.projection {
+        let (name, alias) = match item {
+            SelectItem::Column {
+                name,
+                alias: Some(alias),
+            } => (name.to_string(), alias),
+            SelectItem::Aggregate {
+                aggregate,
+                alias: Some(alias),
+            } => (aggregate.to_string(), alias),
+            SelectItem::Expr {
+                expr,
+                alias: Some(alias),
+            } => (expr.to_string(), alias),
+            _ => continue,
+        };
+        if let Some(index) = indexes.get(&name).copied() {
+            indexes.insert(alias.to_string(), index);
         }
-    } else {
-        return Ok(order_style); // si no se envio, se toma ASC por defecto
     }
+}
 
-    Ok(order_style)
+fn add_aggregate(aggregates: &mut Vec<Aggregate>, aggregate: &Aggregate) {
+    if !aggregates.contains(aggregate) {
+        aggregates.push(aggregate.clone());
+    }
 }
 
-fn order_numeric(
-    mut response: Vec<Vec<String>>,
-    reference_field: &usize,
-    order_style: &str,
-    order_by: &[String],
-) -> Result<(bool, Vec<Vec<String>>), MiniSQLError> {
-    let is_numeric_field: bool = if let Some(line) = response.first() {
-        if let Some(field) = line.get(*reference_field) {
-            field.parse::<i32>().is_ok()
-        } else {
-            return Err(MiniSQLError::InvalidSyntax(format!(
-                "unexpected error while trying to order: {} ",
-                order_by.join(" ")
-            )));
+/// Checks the fields of a HAVING condition or a selected expression and collects the aggregates it uses
+fn collect_aggregates(
+    scope: &Scope,
+    grouped: &HashSet<usize>,
+    expr: &Expr,
+    aggregates: &mut Vec<Aggregate>,
+) -> Result<(), MiniSQLError> {
+    match expr {
+        Expr::Column(name) => check_grouped(scope, grouped, name),
+        Expr::Aggregate(aggregate) => {
+            add_aggregate(aggregates, aggregate);
+            Ok(())
         }
-    } else {
-        return Err(MiniSQLError::InvalidSyntax(format!(
-            "unexpected error while trying to order: {} ",
-            order_by.join(" ")
-        )));
-    };
-
-    if is_numeric_field {
-        response.sort_by(|a, b| {
-            let elem_a = a
-                .get(*reference_field)
-                .unwrap_or(&"".to_string())
-                .parse::<i32>()
-                .ok()
-                .unwrap_or(0);
-            let elem_b = b
-                .get(*reference_field)
-                .unwrap_or(&"".to_string())
-                .parse::<i32>()
-                .ok()
-                .unwrap_or(0);
-            if order_style == "ASC" {
-                elem_a.cmp(&elem_b)
-            } else {
-                elem_b.cmp(&elem_a)
+        expr => {
+            for child in expr.children() {
+                collect_aggregates(scope, grouped, child, aggregates)?;
             }
-        });
-        Ok((true, response))
-    } else {
-        Ok((false, response))
+            Ok(())
+        }
     }
 }
 
-fn order_literal(
-    mut response: Vec<Vec<String>>,
-    reference_field: &usize,
-    order_style: &str,
-) -> Vec<Vec<String>> {
-    response.sort_by(|a, b| {
-        let default_a = "".to_string();
-        let default_b = "".to_string();
-        let elem_a = a.get(*reference_field).unwrap_or(&default_a);
-        let elem_b = b.get(*reference_field).unwrap_or(&default_b);
-        if order_style == "ASC" {
-            elem_a.cmp(elem_b)
-        } else {
-            elem_b.cmp(elem_a)
+fn has_aggregate(expr: &Expr) -> bool {
+    match expr {
+        Expr::Aggregate(_) => true,
+        expr => expr.children().into_iter().any(has_aggregate),
+    }
+}
+
+/// Filters the lines with the WHERE condition and hands each one to `on_line` as it is read,
+/// together with the number of its register. The table stops being read as soon as `on_line`
+/// returns `false`.
+fn apply_select_to_file(
+    select: &SelectStatement,
+    rows: Rows,
+    scope: &Scope,
+    coercion: Coercion,
+    mut on_line: impl FnMut(usize, Vec<String>) -> Result<bool, MiniSQLError>,
+) -> Result<(), MiniSQLError> {
+    let condition = compile_condition(&select.condition, &scope.indexes, &scope.types, coercion)?;
+
+    for result in rows {
+        let (row, line) = result?;
+        let should_apply = condition.evaluate(&line).map_err(|err| err.on_row(row))?;
+        if should_apply && !on_line(row, line)? {
+            break;
         }
-    });
-    response
+    }
+
+    Ok(())
+}
+
+/// Prints the values on `indices`, in that order. A NULL is printed empty.
+fn print_register(line: &[String], indices: &[usize]) {
+    let values: Vec<&str> = indices
+        .iter()
+        .map(|index| match line.get(*index) {
+            // un campo que falta se imprime como NULL, sin correr los siguientes
+            Some(value) if value != NULL => value.as_str(),
+            _ => "",
+        })
+        .collect();
+    println!("{}", values.join(", "));
 }
@@ -0,0 +1,42 @@
+use super::common::get_headers;
+use crate::errors::apperrors::MiniSQLError;
+use crate::file;
+use crate::parser::ast::TruncateStatement;
+
+/// Executes a parsed `TRUNCATE TABLE` statement.
+///
+/// Removes every register of the table, keeping only its header and schema.
+///
+/// # Examples
+///
+/// ```
+/// if let Statement::Truncate(truncate) = parse("TRUNCATE TABLE clientes")? {
+///     execute_truncate_statement(truncate, &"user/data/tables");
+/// }
+/// ```
+///
+/// # Errors
+///
+/// This function will return an error of type `MiniSQLError` if:
+///
+/// - The provided table is invalid.
+/// - The table could not be rewritten.
+///
+/// # Returns
+///
+/// - `Ok(())` if the query executes successfully.
+/// - `Err(MiniSQLError)` if an error occurs during execution.
+///
+pub fn execute_truncate_statement(
+    truncate: TruncateStatement,
+    route: &String,
+) -> Result<(), MiniSQLError> {
+    let file_iter = file::handler::new_file_iterator(route, &truncate.table)?;
+    let (_, headers) = get_headers(file_iter)?;
+
+    let mut new_file = file::handler::create_file(route, &truncate.table)?;
+    new_file.write_record(&headers)?;
+    new_file.flush()?;
+    file::handler::rename_file(route, &truncate.table)?;
+    Ok(())
+}
@@ -1,28 +1,47 @@
-use super::common::{add_all_fields, format_to_csv, get_headers, validate_table};
-use super::conditions::get_query;
+use super::common::{
+    add_all_fields, check_unique, format_to_csv, get_headers, literal_to_stored, to_stored_value,
+    unique_fields,
+};
+use super::conditions::{compile_condition, compile_scalar, Coercion, Scalar};
 use crate::errors::apperrors::MiniSQLError;
 use crate::file;
+use crate::file::schema::Schema;
+use crate::parser::ast::{ColumnDefinition, Expr, Literal, UpdateStatement};
+use crate::types::value::{ColumnType, NULL};
+use csv::Reader;
 use std::collections::HashMap;
 use std::fs::File;
-use std::io::Write;
-use std::io::{BufRead, BufReader};
 
-/// Executes an `UPDATE` query with the provided SQL string.
+/// Executes a parsed `UPDATE` statement.
 ///
 /// This function encapsulates the entire lifecycle of an `UPDATE`,
 /// including the creation, execution, and handling of the query.
 ///
+/// Each new value can be an expression over the fields of the register, computed on the
+/// register as it was before the update: `SET a = b, b = a` swaps both fields.
+/// An expression over a NULL field gives NULL, NOT NULL fields can not be set to NULL and
+/// UNIQUE fields can not end up repeated, in which case the table is left as it was.
+/// Values of different types on the WHERE are compared following `coercion`.
+///
 /// # Examples
 ///
 /// ```
-/// execute_update_statement(["UPDATE", "clientes", "SET", "email", "=", "'pepe@hotmail.com'", ",", "nombre", "=", "'pepe'", "WHERE", "id", ">", "108"], &"user/data/tables");
+/// if let Statement::Update(update) = parse("UPDATE clientes SET email = 'pepe@hotmail.com', nombre = 'pepe' WHERE id > 108")? {
+///     execute_update_statement(update, &"user/data/tables", Coercion::Implicit);
+/// }
+/// if let Statement::Update(update) = parse("UPDATE productos SET stock = stock - 1 WHERE id = 3")? {
+///     execute_update_statement(update, &"user/data/tables", Coercion::Strict);
+/// }
 /// ```
 ///
 /// # Errors
 ///
 /// This function will return an error of type `MiniSQLError` if:
 ///
-/// - The SQL string is invalid.
+/// - A referenced field does not exist on the table.
+/// - A NOT NULL or PRIMARY KEY field would be left NULL.
+/// - Two registers would share a value on a UNIQUE or PRIMARY KEY field.
+/// - A register can not be compared with the condition, the error tells its number.
 /// - The provided table is invalid.
 /// - The query fails for any other reason.
 ///
@@ -32,217 +51,156 @@ use std::io::{BufRead, BufReader};
 /// - `Err(MiniSQLError)` if an error occurs during execution.
 ///
 pub fn execute_update_statement(
-    sententence_vec: Vec<String>,
+    update: UpdateStatement,
     route: &String,
+    coercion: Coercion,
 ) -> Result<(), MiniSQLError> {
-    let update = new_update(sententence_vec)?;
-    let file_iter = file::handler::new_file_iterator(route, &update.target_table)?;
+    let file_iter = file::handler::new_file_iterator(route, &update.table)?;
 
-    execute_update(&update, file_iter, route)?;
+    execute_update(&update, file_iter, route, coercion)?;
     Ok(())
 }
 
-/// Contains all requiered data to execute a UPDATE statement given row values
-struct Update {
-    /// FROM --> target_table
-    target_table: String,
-    /// SET edad = 5 --> fields = ["edad", "5"]
-    fields: Vec<(String, String)>,
-    /// WHERE --> condition ; as a vector of each part, id = 1 --> ["id", "=", "1"]
-    condition: Vec<String>,
-}
-
-struct FieldsToUpdate {
-    target_table: Vec<String>,
-    fields: Vec<String>,
-    condition: Vec<String>,
-}
-
-fn new_update(sentence_parts: Vec<String>) -> Result<Update, MiniSQLError> {
-    decode_update(sentence_parts)
-}
-
-fn decode_update(sentence_parts: Vec<String>) -> Result<Update, MiniSQLError> {
-    let fields_raw: Vec<String> = Vec::new();
-    let condition: Vec<String> = Vec::new();
-    let from: Vec<String> = Vec::new();
-
-    let fields_to_update = match_fields_update(sentence_parts, fields_raw, condition, from)?;
-
-    let table = validate_table(fields_to_update.target_table)?;
-
-    let formatted_fields = format_fields_to_update(fields_to_update.fields)?;
-
-    Ok(Update {
-        target_table: table,
-        fields: formatted_fields,
-        condition: fields_to_update.condition,
-    })
-}
-
-fn match_fields_update(
-    sentence_parts: Vec<String>,
-    mut fields_raw: Vec<String>,
-    mut condition: Vec<String>,
-    mut from: Vec<String>,
-) -> Result<FieldsToUpdate, MiniSQLError> {
-    let mut base = "";
-    for part in &sentence_parts {
-        match part.as_str() {
-            "UPDATE" => {
-                base = "from";
-                continue;
-            }
-            "SET" => {
-                base = "fields";
-                continue;
-            }
-            "WHERE" => {
-                base = "condition";
-                continue;
-            }
-            _ => (),
-        }
-
-        match base {
-            "condition" => condition.push(part.to_string()),
-            "from" => from.push(part.to_string()),
-            "fields" => fields_raw.push(part.to_string()),
-            _ => {
-                return Err(MiniSQLError::InvalidSyntax(format!(
-                    "Invalid sentence: {} ",
-                    sentence_parts.join(" ")
-                )))
-            }
+fn execute_update(
+    sentence: &UpdateStatement,
+    file_iter: Reader<File>,
+    file_path: &String,
+    coercion: Coercion,
+) -> Result<(), MiniSQLError> {
+    let (file_iter, headers) = get_headers(file_iter)?;
+    let mapped_fields = add_all_fields(&headers);
+    let schema = file::schema::read_schema(file_path, &sentence.table)?;
+    let types = schema.column_types(&headers)?;
+    let (indexes_to_modify, assignments) =
+        get_fields_to_update(&sentence.assignments, &mapped_fields, &types, &schema)?;
+    let condition = compile_condition(&sentence.condition, &mapped_fields, &types, coercion)?;
+    let definitions: Vec<Option<&ColumnDefinition>> =
+        headers.iter().map(|header| schema.column(header)).collect();
+    let mut unique_values = unique_fields(&definitions);
+
+    let mut new_file = file::handler::create_file(file_path, &sentence.table)?;
+
+    new_file.write_record(&headers)?;
+    for (position, result) in file_iter.into_records().enumerate() {
+        let record = result?;
+        let mut line = format_to_csv(&record);
+        // una fila incompleta se completa con NULL para poder evaluarla y modificar cualquier campo
+        line.resize(headers.len(), NULL.to_string());
+        let should_apply = condition
+            .evaluate(&line)
+            .map_err(|err| err.on_row(position + 1))?;
+        if should_apply {
+            line = update_line(line, &indexes_to_modify, &assignments)?;
         }
+        // la tabla se reescribe entera, asi que se validan todas las lineas y no solo las modificadas
+        check_unique(&line, &definitions, &mut unique_values)
+            .map_err(|err| err.on_row(position + 1))?;
+        new_file.write_record(&line)?;
     }
-    Ok(FieldsToUpdate {
-        target_table: from,
-        fields: fields_raw,
-        condition,
-    })
+    new_file.flush()?;
+    file::handler::rename_file(file_path, &sentence.table)?;
+    Ok(())
 }
 
-fn format_fields_to_update(raw_fields: Vec<String>) -> Result<Vec<(String, String)>, MiniSQLError> {
-    let mut formatted_fields: Vec<(String, String)> = vec![];
-    let mut index: usize = 0;
-    let mut str1: String = String::from("");
+/// New value of an updated field
+enum Assignment {
+    /// literal, checked against the type of the field only once
+    Value(String),
+    /// expression over the fields of the register, with the type and name of the updated field
+    Computed {
+        scalar: Scalar,
+        column_type: Option<ColumnType>,
+        field: String,
+        /// NOT NULL or PRIMARY KEY
+        required: bool,
+    },
+}
 
-    for field in raw_fields {
-        match index {
-            0 => str1 = field,
-            1 => {
-                if field != "=" {
-                    return Err(MiniSQLError::InvalidSyntax(format!(
-                        "Invalid syntax for update, should be a asignation symbol: {} ",
-                        field
-                    )));
-                }
-            }
-            2 => formatted_fields.push((str1.to_string(), field)),
-            3 => {
-                if field == "," {
-                    index = 0;
-                    continue;
+impl Assignment {
+    fn value(&self, line: &[String]) -> Result<String, MiniSQLError> {
+        match self {
+            Assignment::Value(value) => Ok(value.to_string()),
+            Assignment::Computed {
+                scalar,
+                column_type,
+                field,
+                required,
+            } => {
+                let value = scalar.evaluate(line)?;
+                if value == NULL && *required {
+                    return Err(null_on_required(field));
                 }
-                return Err(MiniSQLError::InvalidSyntax(format!(
-                    "Invalid syntax for update, sentence should follow KEY = VALUE format, but has a fourth part: {} ", field)));
-            }
-            _ => {
-                return Err(MiniSQLError::Generic(
-                    "program found unexpected error while parsing update".to_string(),
-                ))
+                to_stored_value(value, column_type, field)
             }
         }
-        index += 1;
     }
-
-    Ok(formatted_fields)
 }
 
-fn execute_update(
-    sentence: &Update,
-    file_iter: BufReader<File>,
-    file_path: &String,
-) -> Result<(), MiniSQLError> {
-    let (file_iter, headers) = get_headers(file_iter);
-    let mapped_fields = add_all_fields(&headers);
-    let (indexes_to_modify, values) = get_fields_to_update(&sentence.fields, &mapped_fields)?;
-
-    let mut new_file = file::handler::create_file(file_path, &sentence.target_table)?;
-
-    let headers = headers.join(",").replace("\n", "");
-    writeln!(new_file, "{}", headers)?;
-    for result in file_iter.lines() {
-        let record = result?;
-        let mut line = format_to_csv(record);
-        let should_apply = get_query(
-            &sentence.condition,
-            0,
-            sentence.condition.len(),
-            &mapped_fields,
-            &line,
-        )?;
-        if should_apply {
-            line = update_line(line, &indexes_to_modify, &values)?;
-        }
-        let csv_line = line.join(",").replace("\n", "");
-        writeln!(new_file, "{}", csv_line)?;
-    }
-    file::handler::rename_file(file_path, &sentence.target_table)?;
-    Ok(())
+fn null_on_required(field: &str) -> MiniSQLError {
+    MiniSQLError::InvalidColumn(format!("field [ {} ] can not be empty ", field))
 }
 
 fn get_fields_to_update(
-    fields: &[(String, String)],
+    fields: &[(String, Expr)],
     mapped_fields: &HashMap<String, usize>,
-) -> Result<(Vec<usize>, Vec<String>), MiniSQLError> {
+    types: &[Option<ColumnType>],
+    schema: &Schema,
+) -> Result<(Vec<usize>, Vec<Assignment>), MiniSQLError> {
     let mut indexes: Vec<usize> = vec![];
-    let mut values: Vec<String> = vec![];
-
-    for field in fields {
-        if let Some(index) = mapped_fields.get(&field.0) {
+    let mut assignments: Vec<Assignment> = vec![];
+
+    for (field, expr) in fields {
+        if let Some(index) = mapped_fields.get(field) {
+            let column_type = types.get(*index).copied().flatten();
+            let required = schema
+                .column(field)
+                .is_some_and(|definition| definition.is_required());
             indexes.push(*index);
-            values.push(field.1.clone())
+            assignments.push(match expr {
+                Expr::Literal(Literal::Null) if required => return Err(null_on_required(field)),
+                Expr::Literal(literal) => {
+                    Assignment::Value(literal_to_stored(literal, &column_type, field)?)
+                }
+                expr => Assignment::Computed {
+                    scalar: compile_scalar(expr, mapped_fields, types)?,
+                    column_type,
+                    field: field.to_string(),
+                    required,
+                },
+            });
         } else {
             return Err(MiniSQLError::InvalidSyntax(format!(
                 "Invalid sentence field {} was not found in table.",
-                field.0
+                field
             )));
         }
     }
 
-    Ok((indexes, values))
+    Ok((indexes, assignments))
 }
 
 fn update_line(
-    line: Vec<String>,
+    mut line: Vec<String>,
     indexes_to_modify: &[usize],
-    values: &[String],
+    assignments: &[Assignment],
 ) -> Result<Vec<String>, MiniSQLError> {
-    let mut new_line: Vec<String> = vec![];
+    // todos los valores se calculan sobre la linea original antes de reemplazar alguno
+    let mut values: Vec<String> = vec![];
+    for assignment in assignments {
+        values.push(assignment.value(&line)?);
+    }
 
-    for (index, column) in line.iter().enumerate() {
-        let mut found = false;
-        for (inner_index, value) in indexes_to_modify.iter().enumerate() {
-            if index == *value {
-                if let Some(new_value) = values.get(inner_index) {
-                    new_line.push(new_value.to_string());
-                    found = true
-                } else {
-                    return Err(MiniSQLError::Generic(format!(
-                        "program found unexpected error while updating a line: {} \n
-                     new fields: {} ",
-                        line.join(" "),
-                        values.join(" ")
-                    )));
-                }
+    for (index, value) in indexes_to_modify.iter().zip(values) {
+        match line.get_mut(*index) {
+            Some(column) => *column = value,
+            None => {
+                return Err(MiniSQLError::Generic(format!(
+                    "program found unexpected error while updating a line: {} ",
+                    line.join(" ")
+                )))
             }
         }
-        if !found {
-            new_line.push(column.to_string());
-        }
     }
 
-    Ok(new_line)
+    Ok(line)
 }
@@ -0,0 +1,492 @@
+use std::fmt;
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+/// Days from 0000-03-01 to 1970-01-01, the day counted as 0
+const EPOCH_DAYS: i64 = 719_468;
+const EPOCH: Date = Date {
+    year: 1970,
+    month: 1,
+    day: 1,
+};
+
+/// Calendar date, written on tables as ISO-8601 ( YYYY-MM-DD )
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    year: i32,
+    month: u32,
+    day: u32,
+}
+
+impl Date {
+    pub fn new(year: i32, month: u32, day: u32) -> Option<Date> {
+        if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+            return None;
+        }
+        Some(Date { year, month, day })
+    }
+
+    /// Reads a YYYY-MM-DD date, returns `None` if it is not a valid day
+    pub fn parse(text: &str) -> Option<Date> {
+        let parts: Vec<&str> = text.trim().split('-').collect();
+        if parts.len() != 3 || parts[0].len() != 4 || parts[1].len() != 2 || parts[2].len() != 2 {
+            return None;
+        }
+        if !parts
+            .iter()
+            .all(|part| part.chars().all(|c| c.is_ascii_digit()))
+        {
+            return None;
+        }
+        let year = parts[0].parse::<i32>().ok()?;
+        let month = parts[1].parse::<u32>().ok()?;
+        let day = parts[2].parse::<u32>().ok()?;
+        Date::new(year, month, day)
+    }
+
+    /// Date in UTC when the sentence started, the same day as `Timestamp::now`
+    pub fn today() -> Date {
+        Timestamp::now().date
+    }
+
+    /// Adds years, months or days. Adding months keeps the day when the month has it and
+    /// moves it to the last day of the month otherwise: `2024-01-31` plus a month is `2024-02-29`.
+    /// `None` for a unit of the time of day or if the year leaves 0000-9999.
+    pub fn add(&self, amount: i64, unit: DateUnit) -> Option<Date> {
+        match unit {
+            DateUnit::Year => self.add_months(amount.checked_mul(12)?),
+            DateUnit::Month => self.add_months(amount),
+            DateUnit::Day => Date::from_days(self.to_days().checked_add(amount)?),
+            _ => None,
+        }
+    }
+
+    /// `None` for a unit of the time of day
+    pub fn extract(&self, unit: DateUnit) -> Option<i64> {
+        match unit {
+            DateUnit::Year => Some(self.year as i64),
+            DateUnit::Month => Some(self.month as i64),
+            DateUnit::Day => Some(self.day as i64),
+            _ => None,
+        }
+    }
+
+    /// First day of the year or month, a date is already truncated to the day or less
+    pub fn truncate(&self, unit: DateUnit) -> Date {
+        match unit {
+            DateUnit::Year => Date {
+                month: 1,
+                day: 1,
+                ..*self
+            },
+            DateUnit::Month => Date { day: 1, ..*self },
+            _ => *self,
+        }
+    }
+
+    fn add_months(&self, amount: i64) -> Option<Date> {
+        let months = (self.year as i64 * 12 + self.month as i64 - 1).checked_add(amount)?;
+        let year = i32::try_from(months.div_euclid(12)).ok()?;
+        let month = months.rem_euclid(12) as u32 + 1;
+        let day = self.day.min(days_in_month(year, month));
+        Date::new(year, month, day).filter(in_range)
+    }
+
+    /// Days since 1970-01-01, negative before it
+    fn to_days(self) -> i64 {
+        let year = self.year as i64 - i64::from(self.month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year.rem_euclid(400);
+        // los meses se cuentan desde marzo, asi el 29 de febrero queda al final del año
+        let month = (self.month as i64 + 9) % 12;
+        let day_of_year = (153 * month + 2) / 5 + self.day as i64 - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - EPOCH_DAYS
+    }
+
+    fn from_days(days: i64) -> Option<Date> {
+        let days = days.checked_add(EPOCH_DAYS)?;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month + 2) / 5 + 1;
+        let month = if month < 10 { month + 3 } else { month - 9 };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        Date::new(i32::try_from(year).ok()?, month as u32, day as u32).filter(in_range)
+    }
+}
+
+/// Only years written with four digits can be read back from a table
+fn in_range(date: &Date) -> bool {
+    (0..=9999).contains(&date.year)
+}
+
+/// Time of day without a time zone, written as ISO-8601 ( HH:MM:SS )
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Time {
+    hour: u32,
+    minute: u32,
+    second: u32,
+}
+
+impl Time {
+    pub fn new(hour: u32, minute: u32, second: u32) -> Option<Time> {
+        if hour > 23 || minute > 59 || second > 59 {
+            return None;
+        }
+        Some(Time {
+            hour,
+            minute,
+            second,
+        })
+    }
+
+    /// Reads a HH:MM:SS or HH:MM time, returns `None` if it is not a valid time of day
+    pub fn parse(text: &str) -> Option<Time> {
+        let parts: Vec<&str> = text.trim().split(':').collect();
+        if !(2..=3).contains(&parts.len())
+            || !parts
+                .iter()
+                .all(|part| part.len() == 2 && part.chars().all(|c| c.is_ascii_digit()))
+        {
+            return None;
+        }
+        let second = match parts.get(2) {
+            Some(second) => second.parse::<u32>().ok()?,
+            None => 0,
+        };
+        Time::new(parts[0].parse().ok()?, parts[1].parse().ok()?, second)
+    }
+
+    /// Adds hours, minutes or seconds going around midnight: `23:00:00` plus two hours is `01:00:00`.
+    /// `None` for a unit of the date.
+    pub fn add(&self, amount: i64, unit: DateUnit) -> Option<Time> {
+        if !unit.is_time_of_day() {
+            return None;
+        }
+        let seconds = amount.checked_mul(unit.seconds()?)?;
+        let seconds = (self.seconds() + seconds.rem_euclid(SECONDS_PER_DAY)) % SECONDS_PER_DAY;
+        Some(Time::from_seconds(seconds))
+    }
+
+    /// `None` for a unit of the date
+    pub fn extract(&self, unit: DateUnit) -> Option<i64> {
+        match unit {
+            DateUnit::Hour => Some(self.hour as i64),
+            DateUnit::Minute => Some(self.minute as i64),
+            DateUnit::Second => Some(self.second as i64),
+            _ => None,
+        }
+    }
+
+    /// `None` for a unit of the date
+    pub fn truncate(&self, unit: DateUnit) -> Option<Time> {
+        match unit {
+            DateUnit::Hour => Time::new(self.hour, 0, 0),
+            DateUnit::Minute => Time::new(self.hour, self.minute, 0),
+            DateUnit::Second => Some(*self),
+            _ => None,
+        }
+    }
+
+    fn seconds(&self) -> i64 {
+        self.hour as i64 * 3600 + self.minute as i64 * 60 + self.second as i64
+    }
+
+    fn from_seconds(seconds: i64) -> Time {
+        Time {
+            hour: (seconds / 3600) as u32,
+            minute: (seconds % 3600 / 60) as u32,
+            second: (seconds % 60) as u32,
+        }
+    }
+}
+
+/// Date and time of day without a time zone, written as ISO-8601 ( YYYY-MM-DDTHH:MM:SS )
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp {
+    date: Date,
+    time: Time,
+}
+
+impl Timestamp {
+    /// Reads a date and a time separated by `T` or a space. A date alone is read at midnight.
+    pub fn parse(text: &str) -> Option<Timestamp> {
+        let text = text.trim();
+        let (date, time) = match text.get(10..11) {
+            Some("T") | Some(" ") => (text.get(..10)?, Time::parse(text.get(11..)?)?),
+            _ => (text, Time::default()),
+        };
+        Some(Timestamp {
+            date: Date::parse(date)?,
+            time,
+        })
+    }
+
+    /// Date and time in UTC, to the second, when the sentence started. The clock is read
+    /// only the first time, so every NOW() and CURRENT_DATE of the sentence see the same
+    /// instant: the program runs a single sentence.
+    pub fn now() -> Timestamp {
+        static STARTED: OnceLock<Timestamp> = OnceLock::new();
+        *STARTED.get_or_init(|| {
+            let seconds = match SystemTime::now().duration_since(UNIX_EPOCH) {
+                Ok(elapsed) => elapsed.as_secs() as i64,
+                Err(_) => 0,
+            };
+            Timestamp::from_seconds(seconds).unwrap_or(Timestamp::from(EPOCH))
+        })
+    }
+
+    /// Adds any unit, days and smaller ones move the date when they go past midnight.
+    /// `None` if the year leaves 0000-9999.
+    pub fn add(&self, amount: i64, unit: DateUnit) -> Option<Timestamp> {
+        match unit.seconds() {
+            Some(seconds) => {
+                Timestamp::from_seconds(self.seconds()?.checked_add(amount.checked_mul(seconds)?)?)
+            }
+            None => Some(Timestamp {
+                date: self.date.add(amount, unit)?,
+                time: self.time,
+            }),
+        }
+    }
+
+    pub fn extract(&self, unit: DateUnit) -> i64 {
+        match self.date.extract(unit) {
+            Some(part) => part,
+            None => self.time.extract(unit).unwrap_or_default(),
+        }
+    }
+
+    pub fn truncate(&self, unit: DateUnit) -> Timestamp {
+        match self.time.truncate(unit) {
+            Some(time) => Timestamp {
+                date: self.date,
+                time,
+            },
+            None => Timestamp {
+                date: self.date.truncate(unit),
+                time: Time::default(),
+            },
+        }
+    }
+
+    /// Seconds since 1970-01-01T00:00:00
+    fn seconds(&self) -> Option<i64> {
+        self.date
+            .to_days()
+            .checked_mul(SECONDS_PER_DAY)?
+            .checked_add(self.time.seconds())
+    }
+
+    fn from_seconds(seconds: i64) -> Option<Timestamp> {
+        Some(Timestamp {
+            date: Date::from_days(seconds.div_euclid(SECONDS_PER_DAY))?,
+            time: Time::from_seconds(seconds.rem_euclid(SECONDS_PER_DAY)),
+        })
+    }
+}
+
+/// A date at midnight
+impl From<Date> for Timestamp {
+    fn from(date: Date) -> Timestamp {
+        Timestamp {
+            date,
+            time: Time::default(),
+        }
+    }
+}
+
+/// Part of a date or a time, used by `DATE_ADD`, `EXTRACT` and `DATE_TRUNC`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateUnit {
+    Year,
+    Month,
+    Day,
+    Hour,
+    Minute,
+    Second,
+}
+
+impl DateUnit {
+    /// Units are not reserved words, they are matched ignoring case
+    pub fn from_name(name: &str) -> Option<DateUnit> {
+        match name.to_uppercase().as_str() {
+            "YEAR" => Some(DateUnit::Year),
+            "MONTH" => Some(DateUnit::Month),
+            "DAY" => Some(DateUnit::Day),
+            "HOUR" => Some(DateUnit::Hour),
+            "MINUTE" => Some(DateUnit::Minute),
+            "SECOND" => Some(DateUnit::Second),
+            _ => None,
+        }
+    }
+
+    /// Hours, minutes or seconds
+    pub fn is_time_of_day(&self) -> bool {
+        matches!(self, DateUnit::Hour | DateUnit::Minute | DateUnit::Second)
+    }
+
+    /// Length of the unit, `None` for years and months, that do not always last the same
+    fn seconds(&self) -> Option<i64> {
+        match self {
+            DateUnit::Year | DateUnit::Month => None,
+            DateUnit::Day => Some(SECONDS_PER_DAY),
+            DateUnit::Hour => Some(3600),
+            DateUnit::Minute => Some(60),
+            DateUnit::Second => Some(1),
+        }
+    }
+}
+
+fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl fmt::Display for Time {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:{:02}:{:02}", self.hour, self.minute, self.second)
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}T{}", self.date, self.time)
+    }
+}
+
+impl fmt::Display for DateUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DateUnit::Year => "YEAR",
+            DateUnit::Month => "MONTH",
+            DateUnit::Day => "DAY",
+            DateUnit::Hour => "HOUR",
+            DateUnit::Minute => "MINUTE",
+            DateUnit::Second => "SECOND",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod test_date {
+    use super::*;
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(Date::parse("2024-02-29"), Date::new(2024, 2, 29));
+        assert_eq!(Date::parse("2023-02-29"), None);
+        assert_eq!(Date::parse("2023-13-01"), None);
+        assert_eq!(Date::parse("2023-1-01"), None);
+        assert_eq!(Date::parse("hoy"), None);
+    }
+
+    #[test]
+    fn test_date_order_and_display() {
+        let first = Date::parse("2023-12-31").unwrap();
+        let second = Date::parse("2024-01-01").unwrap();
+        assert!(first < second);
+        assert_eq!(second.to_string(), "2024-01-01");
+    }
+
+    #[test]
+    fn test_parse_time_and_timestamp() {
+        assert_eq!(Time::parse("09:05"), Time::new(9, 5, 0));
+        assert_eq!(Time::parse("24:00:00"), None);
+        assert_eq!(Time::parse("9:05:00"), None);
+        let timestamp = Timestamp::parse("2024-03-01 10:30:15").unwrap();
+        assert_eq!(timestamp.to_string(), "2024-03-01T10:30:15");
+        assert_eq!(Timestamp::parse("2024-03-01T10:30:15"), Some(timestamp));
+        assert_eq!(
+            Timestamp::parse("2024-03-01").map(|t| t.to_string()),
+            Some("2024-03-01T00:00:00".to_string())
+        );
+        assert_eq!(Timestamp::parse("2024-03-01T25:00"), None);
+        assert!(Timestamp::from(Date::parse("2024-03-01").unwrap()) < timestamp);
+    }
+
+    #[test]
+    fn test_days_round_trip() {
+        let epoch = Date::parse("1970-01-01").unwrap();
+        assert_eq!(epoch.to_days(), 0);
+        assert_eq!(Date::parse("2000-03-01").unwrap().to_days(), 11_017);
+        for days in [-719_528, -1, 0, 59, 10_956, 2_932_896] {
+            assert_eq!(Date::from_days(days).map(|date| date.to_days()), Some(days));
+        }
+        assert_eq!(Date::from_days(2_932_897), None);
+    }
+
+    #[test]
+    fn test_add() {
+        let date = Date::parse("2024-01-31").unwrap();
+        assert_eq!(date.add(1, DateUnit::Month), Date::parse("2024-02-29"));
+        assert_eq!(date.add(-2, DateUnit::Year), Date::parse("2022-01-31"));
+        assert_eq!(date.add(30, DateUnit::Day), Date::parse("2024-03-01"));
+        assert_eq!(date.add(1, DateUnit::Hour), None);
+        assert_eq!(date.add(8000, DateUnit::Year), None);
+
+        let timestamp = Timestamp::parse("2024-12-31T23:30:00").unwrap();
+        assert_eq!(
+            timestamp.add(45, DateUnit::Minute).map(|t| t.to_string()),
+            Some("2025-01-01T00:15:00".to_string())
+        );
+        assert_eq!(
+            timestamp.add(-1, DateUnit::Month).map(|t| t.to_string()),
+            Some("2024-11-30T23:30:00".to_string())
+        );
+        let time = Time::parse("23:00:00").unwrap();
+        assert_eq!(time.add(2, DateUnit::Hour), Time::parse("01:00:00"));
+        assert_eq!(time.add(-25, DateUnit::Hour), Time::parse("22:00:00"));
+        assert_eq!(time.add(1, DateUnit::Day), None);
+        assert_eq!(time.add(1, DateUnit::Month), None);
+    }
+
+    #[test]
+    fn test_extract_and_truncate() {
+        let timestamp = Timestamp::parse("2024-07-15T10:45:30").unwrap();
+        assert_eq!(timestamp.extract(DateUnit::Year), 2024);
+        assert_eq!(timestamp.extract(DateUnit::Minute), 45);
+        assert_eq!(
+            timestamp.truncate(DateUnit::Month).to_string(),
+            "2024-07-01T00:00:00"
+        );
+        assert_eq!(
+            timestamp.truncate(DateUnit::Hour).to_string(),
+            "2024-07-15T10:00:00"
+        );
+        let date = Date::parse("2024-07-15").unwrap();
+        assert_eq!(date.truncate(DateUnit::Year).to_string(), "2024-01-01");
+        assert_eq!(date.extract(DateUnit::Hour), None);
+        let time = Time::parse("10:45:30").unwrap();
+        assert_eq!(time.truncate(DateUnit::Minute), Time::parse("10:45"));
+        assert_eq!(time.truncate(DateUnit::Day), None);
+    }
+
+    #[test]
+    fn test_now_is_read_once() {
+        let started = Timestamp::now();
+        std::thread::sleep(std::time::Duration::from_millis(1100));
+        assert_eq!(Timestamp::now(), started);
+        assert_eq!(Date::today(), started.date);
+    }
+}
@@ -0,0 +1,251 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Most digits a decimal can have after the point, the ones that fit on an `i128`
+const MAX_SCALE: u32 = 38;
+/// Digits a division keeps after the point beyond the ones of its operands
+const DIVISION_DIGITS: u32 = 6;
+
+/// Exact decimal number, kept as a whole amount of units of 10^-scale.
+///
+/// Amounts of money add up without the rounding of a real: `0.1 + 0.2` is `0.3`.
+/// The digits after the point are kept as written, `12.50` is printed as `12.50`,
+/// but two decimals with the same value are equal whatever their scale.
+#[derive(Debug, Clone, Copy)]
+pub struct Decimal {
+    units: i128,
+    scale: u32,
+}
+
+impl Decimal {
+    /// Reads a number written with digits and an optional point ( `-12.50` ), returns `None`
+    /// for any other text or if it has more digits than a decimal can hold
+    pub fn parse(text: &str) -> Option<Decimal> {
+        let text = text.trim();
+        let (negative, digits) = match text.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, text.strip_prefix('+').unwrap_or(text)),
+        };
+        let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        if whole.is_empty() && fraction.is_empty() {
+            return None;
+        }
+
+        let mut units: i128 = 0;
+        for c in whole.chars().chain(fraction.chars()) {
+            let digit = c.to_digit(10)?;
+            units = units.checked_mul(10)?.checked_add(digit as i128)?;
+        }
+        let scale = fraction.len() as u32;
+        if scale > MAX_SCALE {
+            return None;
+        }
+        Some(Decimal {
+            units: if negative { -units } else { units },
+            scale,
+        })
+    }
+
+    pub fn from_integer(number: i64) -> Decimal {
+        Decimal {
+            units: number as i128,
+            scale: 0,
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.units as f64 / 10f64.powi(self.scale as i32)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.units == 0
+    }
+
+    /// Same value without zeros at the end of its digits after the point: `12.50` gives `12.5`
+    pub fn normalized(&self) -> Decimal {
+        let mut normalized = *self;
+        while normalized.scale > 0 && normalized.units % 10 == 0 {
+            normalized.units /= 10;
+            normalized.scale -= 1;
+        }
+        normalized
+    }
+
+    pub fn checked_neg(&self) -> Option<Decimal> {
+        Some(Decimal {
+            units: self.units.checked_neg()?,
+            scale: self.scale,
+        })
+    }
+
+    pub fn checked_add(&self, other: &Decimal) -> Option<Decimal> {
+        let (a, b, scale) = align(self, other)?;
+        Some(Decimal {
+            units: a.checked_add(b)?,
+            scale,
+        })
+    }
+
+    pub fn checked_sub(&self, other: &Decimal) -> Option<Decimal> {
+        let (a, b, scale) = align(self, other)?;
+        Some(Decimal {
+            units: a.checked_sub(b)?,
+            scale,
+        })
+    }
+
+    pub fn checked_mul(&self, other: &Decimal) -> Option<Decimal> {
+        let scale = self.scale + other.scale;
+        if scale > MAX_SCALE {
+            return None;
+        }
+        Some(Decimal {
+            units: self.units.checked_mul(other.units)?,
+            scale,
+        })
+    }
+
+    /// Rounds to `DIVISION_DIGITS` more digits than the operands have, half away from zero,
+    /// and drops the zeros left at the end. `None` when dividing by zero or on overflow.
+    pub fn checked_div(&self, other: &Decimal) -> Option<Decimal> {
+        if other.is_zero() {
+            return None;
+        }
+        let kept = self.scale.max(other.scale);
+        let scale = (kept + DIVISION_DIGITS).min(MAX_SCALE);
+        let numerator = self
+            .units
+            .checked_mul(power_of_ten(scale + other.scale - self.scale)?)?;
+
+        let mut units = numerator / other.units;
+        let remainder = numerator % other.units;
+        if remainder.unsigned_abs() >= other.units.unsigned_abs() - remainder.unsigned_abs() {
+            units += if (numerator < 0) == (other.units < 0) {
+                1
+            } else {
+                -1
+            };
+        }
+
+        let mut result = Decimal { units, scale };
+        while result.scale > kept && result.units % 10 == 0 {
+            result.units /= 10;
+            result.scale -= 1;
+        }
+        Some(result)
+    }
+
+    /// Remainder of the division, with the sign of `self`. `None` when dividing by zero.
+    pub fn checked_rem(&self, other: &Decimal) -> Option<Decimal> {
+        let (a, b, scale) = align(self, other)?;
+        Some(Decimal {
+            units: a.checked_rem(b)?,
+            scale,
+        })
+    }
+}
+
+fn power_of_ten(exponent: u32) -> Option<i128> {
+    10i128.checked_pow(exponent)
+}
+
+/// Units of both decimals on the larger of their scales
+fn align(a: &Decimal, b: &Decimal) -> Option<(i128, i128, u32)> {
+    let scale = a.scale.max(b.scale);
+    let a_units = a.units.checked_mul(power_of_ten(scale - a.scale)?)?;
+    let b_units = b.units.checked_mul(power_of_ten(scale - b.scale)?)?;
+    Some((a_units, b_units, scale))
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Decimal) -> Ordering {
+        match align(self, other) {
+            Some((a, b, _)) => a.cmp(&b),
+            // solo numeros con muchisimos digitos no se pueden alinear
+            None => self.to_f64().total_cmp(&other.to_f64()),
+        }
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Decimal) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Decimal) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = self.units.unsigned_abs().to_string();
+        let sign = if self.units < 0 { "-" } else { "" };
+        let scale = self.scale as usize;
+        if scale == 0 {
+            return write!(f, "{}{}", sign, digits);
+        }
+        // se completa con ceros para que siempre haya un digito antes del punto
+        let digits = format!("{:0>width$}", digits, width = scale + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - scale);
+        write!(f, "{}{}.{}", sign, whole, fraction)
+    }
+}
+
+#[cfg(test)]
+mod test_decimal {
+    use super::*;
+
+    fn decimal(text: &str) -> Decimal {
+        Decimal::parse(text).unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        assert_eq!(decimal("1200.50").to_string(), "1200.50");
+        assert_eq!(decimal("-.5").to_string(), "-0.5");
+        assert_eq!(decimal("+7").to_string(), "7");
+        assert_eq!(decimal("0.007").to_string(), "0.007");
+        assert_eq!(Decimal::parse("1e5"), None);
+        assert_eq!(Decimal::parse("."), None);
+        assert_eq!(Decimal::parse("1.2.3"), None);
+        assert_eq!(Decimal::parse(&"9".repeat(40)), None);
+    }
+
+    #[test]
+    fn test_exact_arithmetic() {
+        let sum = decimal("0.1").checked_add(&decimal("0.2")).unwrap();
+        assert_eq!(sum.to_string(), "0.3");
+        let total = decimal("19.90").checked_mul(&decimal("3")).unwrap();
+        assert_eq!(total.to_string(), "59.70");
+        let change = decimal("100").checked_sub(&decimal("59.70")).unwrap();
+        assert_eq!(change.to_string(), "40.30");
+        let rest = decimal("10.5").checked_rem(&decimal("3")).unwrap();
+        assert_eq!(rest.to_string(), "1.5");
+    }
+
+    #[test]
+    fn test_division() {
+        let third = decimal("10.00").checked_div(&decimal("3")).unwrap();
+        assert_eq!(third.to_string(), "3.33333333");
+        let half = decimal("10.00").checked_div(&decimal("4")).unwrap();
+        assert_eq!(half.to_string(), "2.50");
+        let rounded = decimal("-2").checked_div(&decimal("3")).unwrap();
+        assert_eq!(rounded.to_string(), "-0.666667");
+        assert_eq!(decimal("1").checked_div(&decimal("0.00")), None);
+    }
+
+    #[test]
+    fn test_order_ignores_scale() {
+        assert_eq!(decimal("19.90"), decimal("19.9"));
+        assert!(decimal("-1.5") < decimal("-1.25"));
+        assert_eq!(decimal("12.50").normalized().to_string(), "12.5");
+        let huge = decimal(&"9".repeat(38));
+        assert!(huge.checked_add(&decimal("0.1")).is_none());
+        assert!(decimal("0.1") < huge);
+    }
+}
@@ -0,0 +1,479 @@
+use super::date::{Date, DateUnit, Time, Timestamp};
+use super::decimal::Decimal;
+use std::cmp::Ordering;
+use std::fmt;
+
+/// How a NULL is written on a table, so it is not confused with an empty text
+pub const NULL: &str = "\\N";
+
+/// Whether a stored field holds NULL: the NULL marker, or an empty field on a column declared
+/// with a type other than TEXT, as older tables were written before NULL existed.
+/// An empty field on an undeclared column is an empty text.
+pub fn is_null(text: &str, column_type: Option<ColumnType>) -> bool {
+    text == NULL
+        || (text.is_empty()
+            && matches!(column_type, Some(column_type) if column_type != ColumnType::Text))
+}
+
+/// Type declared for a column on the schema of its table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    /// 32 bit signed integer
+    Integer,
+    /// 64 bit signed integer
+    BigInt,
+    Real,
+    /// exact number with digits after the point, for amounts of money
+    Decimal,
+    Text,
+    Boolean,
+    Date,
+    /// time of day
+    Time,
+    /// date and time of day
+    Timestamp,
+}
+
+/// A value read from a typed column
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Integer(i64),
+    Real(f64),
+    Decimal(Decimal),
+    Text(String),
+    Boolean(bool),
+    Date(Date),
+    Time(Time),
+    Timestamp(Timestamp),
+}
+
+impl ColumnType {
+    /// Looks up a type by its SQL name, ignoring case
+    pub fn from_name(name: &str) -> Option<ColumnType> {
+        match name.to_uppercase().as_str() {
+            "INTEGER" | "INT" => Some(ColumnType::Integer),
+            "BIGINT" => Some(ColumnType::BigInt),
+            "REAL" => Some(ColumnType::Real),
+            "DECIMAL" | "NUMERIC" => Some(ColumnType::Decimal),
+            "TEXT" => Some(ColumnType::Text),
+            "BOOLEAN" => Some(ColumnType::Boolean),
+            "DATE" => Some(ColumnType::Date),
+            "TIME" => Some(ColumnType::Time),
+            "TIMESTAMP" | "DATETIME" => Some(ColumnType::Timestamp),
+            _ => None,
+        }
+    }
+
+    /// Reads a value of this type, returns `None` if the text does not fit on it
+    pub fn parse(&self, text: &str) -> Option<Value> {
+        match self {
+            ColumnType::Integer => text
+                .trim()
+                .parse::<i32>()
+                .ok()
+                .map(|n| Value::Integer(n as i64)),
+            ColumnType::BigInt => text.trim().parse::<i64>().ok().map(Value::Integer),
+            ColumnType::Real => match text.trim().parse::<f64>() {
+                Ok(number) if number.is_finite() => Some(Value::Real(number)),
+                _ => None,
+            },
+            ColumnType::Decimal => Decimal::parse(text).map(Value::Decimal),
+            ColumnType::Text => Some(Value::Text(text.to_string())),
+            ColumnType::Boolean => match text.trim().to_lowercase().as_str() {
+                "true" => Some(Value::Boolean(true)),
+                "false" => Some(Value::Boolean(false)),
+                _ => None,
+            },
+            ColumnType::Date => Date::parse(text).map(Value::Date),
+            ColumnType::Time => Time::parse(text).map(Value::Time),
+            ColumnType::Timestamp => Timestamp::parse(text).map(Value::Timestamp),
+        }
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            ColumnType::Integer | ColumnType::BigInt | ColumnType::Real | ColumnType::Decimal
+        )
+    }
+
+    /// Whether a text that `parse` rejected is a number too large for this type
+    pub fn overflows(&self, text: &str) -> bool {
+        let text = text.trim();
+        let digits = text.strip_prefix('-').unwrap_or(text);
+        let integer = !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit());
+        match self {
+            ColumnType::Integer | ColumnType::BigInt => integer,
+            ColumnType::Decimal => {
+                let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+                !(whole.is_empty() && fraction.is_empty())
+                    && whole
+                        .chars()
+                        .chain(fraction.chars())
+                        .all(|c| c.is_ascii_digit())
+            }
+            ColumnType::Real => {
+                matches!(text.parse::<f64>(), Ok(number) if number.is_infinite())
+                    && !text.to_lowercase().contains("inf")
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Value {
+    /// Orders two values, numbers of different types are first brought to the same type with `promote`.
+    /// A date is compared with a timestamp at its midnight, and a text against a date, time or
+    /// timestamp is read as one: `'2024-03-01'` can be compared with a timestamp.
+    ///
+    /// Returns `None` if the values can not be compared with each other.
+    pub fn compare(&self, other: &Value) -> Option<Ordering> {
+        if let Some(numbers) = promote(self, other) {
+            return match numbers {
+                (Value::Integer(a), Value::Integer(b)) => Some(a.cmp(&b)),
+                (Value::Decimal(a), Value::Decimal(b)) => Some(a.cmp(&b)),
+                (a, b) => as_real(&a).partial_cmp(&as_real(&b)),
+            };
+        }
+        match (self, other) {
+            (Value::Text(a), Value::Text(b)) => Some(a.cmp(b)),
+            (Value::Boolean(a), Value::Boolean(b)) => Some(a.cmp(b)),
+            (Value::Date(a), Value::Date(b)) => Some(a.cmp(b)),
+            (Value::Time(a), Value::Time(b)) => Some(a.cmp(b)),
+            (Value::Timestamp(a), Value::Timestamp(b)) => Some(a.cmp(b)),
+            (Value::Date(a), Value::Timestamp(b)) => Some(Timestamp::from(*a).cmp(b)),
+            (Value::Timestamp(a), Value::Date(b)) => Some(a.cmp(&Timestamp::from(*b))),
+            (Value::Text(text), other) if other.is_temporal() => {
+                read_as(text, other)?.compare(other)
+            }
+            (other, Value::Text(text)) if other.is_temporal() => {
+                other.compare(&read_as(text, other)?)
+            }
+            _ => None,
+        }
+    }
+
+    /// Reads a date, a timestamp or a time without a declared type, `None` if it is none of them
+    pub fn parse_temporal(text: &str) -> Option<Value> {
+        [ColumnType::Date, ColumnType::Timestamp, ColumnType::Time]
+            .iter()
+            .find_map(|column_type| column_type.parse(text))
+    }
+
+    /// Adds an amount of a unit to a date, time or timestamp. Adding hours, minutes or
+    /// seconds to a date gives a timestamp. `None` if the unit does not apply to the value
+    /// or the result leaves the years 0000-9999.
+    pub fn add_interval(&self, amount: i64, unit: DateUnit) -> Option<Value> {
+        match self {
+            Value::Date(date) => match date.add(amount, unit) {
+                Some(date) => Some(Value::Date(date)),
+                None => Timestamp::from(*date)
+                    .add(amount, unit)
+                    .filter(|_| unit.is_time_of_day())
+                    .map(Value::Timestamp),
+            },
+            Value::Time(time) => time.add(amount, unit).map(Value::Time),
+            Value::Timestamp(timestamp) => timestamp.add(amount, unit).map(Value::Timestamp),
+            _ => None,
+        }
+    }
+
+    /// A part of a date, time or timestamp as a number. The time of day of a date can not be extracted.
+    pub fn extract(&self, unit: DateUnit) -> Option<i64> {
+        match self {
+            Value::Date(date) => date.extract(unit),
+            Value::Time(time) => time.extract(unit),
+            Value::Timestamp(timestamp) => Some(timestamp.extract(unit)),
+            _ => None,
+        }
+    }
+
+    /// Drops the parts of a date, time or timestamp smaller than the unit, keeping its type
+    pub fn truncate(&self, unit: DateUnit) -> Option<Value> {
+        match self {
+            Value::Date(date) => Some(Value::Date(date.truncate(unit))),
+            Value::Time(time) => time.truncate(unit).map(Value::Time),
+            Value::Timestamp(timestamp) => Some(Value::Timestamp(timestamp.truncate(unit))),
+            _ => None,
+        }
+    }
+
+    /// Reads a value of a field without a declared type: a number, or text
+    pub fn infer(text: &str) -> Value {
+        Value::parse_number(text).unwrap_or_else(|| Value::Text(text.to_string()))
+    }
+
+    /// Reads a number without a declared type: an integer if it fits on an `i64`, a decimal if it
+    /// is written with digits and a point, a real otherwise ( `1e5` ). `None` if it is not a number.
+    pub fn parse_number(text: &str) -> Option<Value> {
+        if let Ok(number) = text.parse::<i64>() {
+            return Some(Value::Integer(number));
+        }
+        if let Some(number) = Decimal::parse(text) {
+            return Some(Value::Decimal(number));
+        }
+        match text.parse::<f64>() {
+            Ok(number) if number.is_finite() => Some(Value::Real(number)),
+            _ => None,
+        }
+    }
+
+    /// The same text for numbers with the same value, whatever their type: `5`, `5.0` and `5.00`
+    /// all give `5`. `None` if the value is not a number.
+    pub fn number_key(&self) -> Option<String> {
+        let decimal = match self {
+            Value::Integer(number) => Decimal::from_integer(*number),
+            Value::Decimal(number) => *number,
+            Value::Real(number) => match Decimal::parse(&number.to_string()) {
+                Some(number) => number,
+                None => return Some(number.to_string()),
+            },
+            _ => return None,
+        };
+        Some(decimal.normalized().to_string())
+    }
+
+    /// Orders any two values. Values that `compare` can not order are ordered by kind:
+    /// numbers first, then booleans, dates and text.
+    pub fn total_cmp(&self, other: &Value) -> Ordering {
+        match self.compare(other) {
+            Some(ordering) => ordering,
+            None => self.rank().cmp(&other.rank()),
+        }
+    }
+
+    pub fn is_number(&self) -> bool {
+        matches!(self, Value::Integer(_) | Value::Real(_) | Value::Decimal(_))
+    }
+
+    pub fn is_temporal(&self) -> bool {
+        matches!(self, Value::Date(_) | Value::Time(_) | Value::Timestamp(_))
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            Value::Integer(_) | Value::Real(_) | Value::Decimal(_) => 0,
+            Value::Boolean(_) => 1,
+            Value::Date(_) | Value::Timestamp(_) => 2,
+            Value::Time(_) => 3,
+            Value::Text(_) => 4,
+        }
+    }
+}
+
+impl fmt::Display for ColumnType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ColumnType::Integer => "INTEGER",
+            ColumnType::BigInt => "BIGINT",
+            ColumnType::Real => "REAL",
+            ColumnType::Decimal => "DECIMAL",
+            ColumnType::Text => "TEXT",
+            ColumnType::Boolean => "BOOLEAN",
+            ColumnType::Date => "DATE",
+            ColumnType::Time => "TIME",
+            ColumnType::Timestamp => "TIMESTAMP",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Value as it is stored on a table
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Integer(value) => write!(f, "{}", value),
+            Value::Real(value) => write!(f, "{}", value),
+            Value::Decimal(value) => write!(f, "{}", value),
+            Value::Text(value) => write!(f, "{}", value),
+            Value::Boolean(value) => write!(f, "{}", value),
+            Value::Date(value) => write!(f, "{}", value),
+            Value::Time(value) => write!(f, "{}", value),
+            Value::Timestamp(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// Brings two numbers to the same type before comparing or operating on them: two integers
+/// stay integers, an integer and a decimal give two decimals, and a real on either side
+/// gives two reals. `None` if one of them is not a number.
+pub fn promote(left: &Value, right: &Value) -> Option<(Value, Value)> {
+    match (left, right) {
+        (Value::Integer(_), Value::Integer(_))
+        | (Value::Decimal(_), Value::Decimal(_))
+        | (Value::Real(_), Value::Real(_)) => Some((left.clone(), right.clone())),
+        (Value::Integer(a), Value::Decimal(_)) => {
+            Some((Value::Decimal(Decimal::from_integer(*a)), right.clone()))
+        }
+        (Value::Decimal(_), Value::Integer(b)) => {
+            Some((left.clone(), Value::Decimal(Decimal::from_integer(*b))))
+        }
+        _ if left.is_number() && right.is_number() => {
+            Some((Value::Real(as_real(left)), Value::Real(as_real(right))))
+        }
+        _ => None,
+    }
+}
+
+/// A text read with the type of a date, time or timestamp, a date is read as a timestamp
+/// so a text with a time of day can still be compared with it
+fn read_as(text: &str, temporal: &Value) -> Option<Value> {
+    match temporal {
+        Value::Time(_) => ColumnType::Time.parse(text),
+        _ => ColumnType::Timestamp.parse(text),
+    }
+}
+
+fn as_real(value: &Value) -> f64 {
+    match value {
+        Value::Integer(number) => *number as f64,
+        Value::Real(number) => *number,
+        Value::Decimal(number) => number.to_f64(),
+        _ => f64::NAN,
+    }
+}
+
+#[cfg(test)]
+mod test_value {
+    use super::*;
+
+    #[test]
+    fn test_parse_by_type() {
+        assert_eq!(ColumnType::Integer.parse("42"), Some(Value::Integer(42)));
+        assert_eq!(ColumnType::Integer.parse("3000000000"), None);
+        assert_eq!(
+            ColumnType::BigInt.parse("3000000000"),
+            Some(Value::Integer(3000000000))
+        );
+        assert_eq!(ColumnType::Real.parse("1.5"), Some(Value::Real(1.5)));
+        assert_eq!(ColumnType::Real.parse("nan"), None);
+        assert_eq!(
+            ColumnType::Boolean.parse("TRUE"),
+            Some(Value::Boolean(true))
+        );
+        assert_eq!(ColumnType::Boolean.parse("1"), None);
+        assert_eq!(ColumnType::Date.parse("2024-13-01"), None);
+        assert_eq!(
+            ColumnType::Text.parse(""),
+            Some(Value::Text("".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_compare_mixed_numbers() {
+        let integer = Value::Integer(10);
+        let real = Value::Real(9.5);
+        assert_eq!(integer.compare(&real), Some(Ordering::Greater));
+        assert_eq!(integer.compare(&Value::Text("10".to_string())), None);
+        let price = ColumnType::Decimal.parse("10.00").unwrap();
+        assert_eq!(integer.compare(&price), Some(Ordering::Equal));
+        assert_eq!(real.compare(&price), Some(Ordering::Less));
+        assert_eq!(
+            Value::Integer(5551234567).compare(&Value::Integer(999)),
+            Some(Ordering::Greater)
+        );
+    }
+
+    #[test]
+    fn test_promote_and_number_key() {
+        let decimal = ColumnType::Decimal.parse("2.50").unwrap();
+        assert_eq!(
+            promote(&Value::Integer(2), &decimal),
+            Some((ColumnType::Decimal.parse("2").unwrap(), decimal.clone()))
+        );
+        assert_eq!(
+            promote(&decimal, &Value::Real(1.0)),
+            Some((Value::Real(2.5), Value::Real(1.0)))
+        );
+        assert_eq!(promote(&decimal, &Value::Boolean(true)), None);
+        assert_eq!(decimal.number_key(), Some("2.5".to_string()));
+        assert_eq!(Value::Real(2.5).number_key(), Some("2.5".to_string()));
+        assert_eq!(Value::Integer(7).number_key(), Some("7".to_string()));
+        assert_eq!(Value::Boolean(true).number_key(), None);
+    }
+
+    #[test]
+    fn test_overflows() {
+        assert!(ColumnType::Integer.overflows("3000000000"));
+        assert!(!ColumnType::Integer.overflows("2.5"));
+        assert!(ColumnType::BigInt.overflows("-99999999999999999999"));
+        assert!(ColumnType::Decimal.overflows(&"9".repeat(40)));
+        assert!(ColumnType::Real.overflows("1e400"));
+        assert!(!ColumnType::Real.overflows("inf"));
+    }
+
+    #[test]
+    fn test_compare_dates_and_times() {
+        let date = ColumnType::Date.parse("2024-03-01").unwrap();
+        let timestamp = ColumnType::Timestamp.parse("2024-03-01 08:00").unwrap();
+        assert_eq!(date.compare(&timestamp), Some(Ordering::Less));
+        assert_eq!(
+            timestamp.compare(&Value::Text("2024-03-01T08:00:00".to_string())),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(
+            date.compare(&Value::Text("2024-02-29".to_string())),
+            Some(Ordering::Greater)
+        );
+        let time = ColumnType::Time.parse("08:00").unwrap();
+        assert_eq!(time.compare(&date), None);
+        assert_eq!(time.compare(&Value::Text("pronto".to_string())), None);
+        assert_eq!(Value::parse_temporal("08:00"), Some(time));
+    }
+
+    #[test]
+    fn test_date_functions() {
+        let date = ColumnType::Date.parse("2024-01-31").unwrap();
+        assert_eq!(
+            date.add_interval(1, DateUnit::Month).map(|v| v.to_string()),
+            Some("2024-02-29".to_string())
+        );
+        assert_eq!(
+            date.add_interval(90, DateUnit::Minute)
+                .map(|v| v.to_string()),
+            Some("2024-01-31T01:30:00".to_string())
+        );
+        assert_eq!(date.extract(DateUnit::Month), Some(1));
+        assert_eq!(date.extract(DateUnit::Hour), None);
+        assert_eq!(
+            date.truncate(DateUnit::Month).map(|v| v.to_string()),
+            Some("2024-01-01".to_string())
+        );
+        assert_eq!(Value::Integer(5).add_interval(1, DateUnit::Day), None);
+    }
+
+    #[test]
+    fn test_is_null() {
+        assert!(is_null(NULL, Some(ColumnType::Text)));
+        assert!(is_null("", Some(ColumnType::Integer)));
+        assert!(!is_null("", None));
+        assert!(is_null(NULL, None));
+        assert!(!is_null("", Some(ColumnType::Text)));
+        assert!(!is_null("0", None));
+    }
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(ColumnType::from_name("bigint"), Some(ColumnType::BigInt));
+        assert_eq!(ColumnType::from_name("numeric"), Some(ColumnType::Decimal));
+        assert_eq!(ColumnType::from_name("VARCHAR"), None);
+    }
+
+    #[test]
+    fn test_infer_and_total_order() {
+        assert_eq!(Value::infer("5551234567"), Value::Integer(5551234567));
+        assert_eq!(
+            Value::infer("-2.5"),
+            Value::Decimal(Decimal::parse("-2.5").unwrap())
+        );
+        assert_eq!(Value::infer("1e3"), Value::Real(1000.0));
+        assert_eq!(Value::infer("inf"), Value::Text("inf".to_string()));
+        let text = Value::Text("10".to_string());
+        assert_eq!(Value::Integer(99).total_cmp(&text), Ordering::Less);
+        assert_eq!(text.total_cmp(&Value::Real(0.5)), Ordering::Greater);
+        assert_eq!(
+            Value::Integer(2).total_cmp(&Value::Real(10.0)),
+            Ordering::Less
+        );
+    }
+}
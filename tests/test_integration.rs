@@ -5,6 +5,9 @@ const CLIENTES_DIR: &str = "data/tables/clientes.csv";
 const CLIENTES2_DIR: &str = "data/tables/clientes2.csv";
 const ORDENES_DIR: &str = "data/tables/ordenes.csv";
 const PERSONAS_DIR: &str = "data/tables/personas.csv";
+const PRODUCTOS_DIR: &str = "data/tables/productos.csv";
+const PRODUCTOS_SCHEMA_DIR: &str = "data/tables/productos.schema";
+const TABLES_DIR: &str = "data/tables";
 fn duplicate_temp_file(
     original_path: &str,
     new_path: &str,
@@ -20,7 +23,25 @@ fn duplicate_temp_file(
 }
 
 fn delete_file(dir_name: &str) -> io::Result<()> {
-    return fs::remove_dir_all(dir_name);
+    fs::remove_dir_all(dir_name)
+}
+
+/// Runs a sentence over the tables on `route` and returns what it wrote on stdout and stderr
+fn run(route: &str, sentence: &str) -> (String, String) {
+    run_with_options(&[], route, sentence)
+}
+
+fn run_with_options(options: &[&str], route: &str, sentence: &str) -> (String, String) {
+    let output = std::process::Command::new("./target/debug/mini_sql")
+        .args(options)
+        .arg(route)
+        .arg(sentence)
+        .output()
+        .expect("Failed to execute command");
+    (
+        String::from_utf8_lossy(&output.stdout).to_string(),
+        String::from_utf8_lossy(&output.stderr).to_string(),
+    )
 }
 
 #[cfg(test)]
@@ -110,7 +131,7 @@ mod tests_select {
 
     #[test]
     fn test_select_some_fields_where() {
-        let expected: Vec<&str> = vec!["103, javier@email.com\n", "109, rafael@email.com\n"];
+        let expected: Vec<&str> = vec!["javier@email.com, 103\n", "rafael@email.com, 109\n"];
 
         let output = std::process::Command::new("./target/debug/mini_sql")
             .arg("data/tables")
@@ -155,7 +176,7 @@ mod tests_select {
 
     #[test]
     fn test_select_some_fields_where_and() {
-        let expected: Vec<&str> = vec!["103, javier@email.com\n"];
+        let expected: Vec<&str> = vec!["javier@email.com, 103\n"];
 
         let output = std::process::Command::new("./target/debug/mini_sql")
         .arg("data/tables")
@@ -173,12 +194,12 @@ mod tests_select {
     #[test]
     fn test_select_some_fields_where_or() {
         let expected: Vec<&str> = vec![
-            "101, mario@email.com\n",
-            "102, laura@email.com\n",
-            "103, javier@email.com\n",
-            "104, carla@email.com\n",
-            "105, andres@email.com\n",
-            "109, rafael@email.com\n",
+            "mario@email.com, 101\n",
+            "laura@email.com, 102\n",
+            "javier@email.com, 103\n",
+            "carla@email.com, 104\n",
+            "andres@email.com, 105\n",
+            "rafael@email.com, 109\n",
         ];
 
         let output = std::process::Command::new("./target/debug/mini_sql")
@@ -224,12 +245,12 @@ mod tests_select {
     #[test]
     fn test_select_ordering_asc_default() {
         let expected: Vec<&str> = vec![
-            "105, andres@email.com\n",
-            "104, carla@email.com\n",
-            "103, javier@email.com\n",
-            "102, laura@email.com\n",
-            "101, mario@email.com\n",
-            "109, rafael@email.com\n",
+            "andres@email.com, 105\n",
+            "carla@email.com, 104\n",
+            "javier@email.com, 103\n",
+            "laura@email.com, 102\n",
+            "mario@email.com, 101\n",
+            "rafael@email.com, 109\n",
         ];
 
         let output = std::process::Command::new("./target/debug/mini_sql")
@@ -248,12 +269,12 @@ mod tests_select {
     #[test]
     fn test_select_ordering_asc_explicit() {
         let expected: Vec<&str> = vec![
-            "105, andres@email.com\n",
-            "104, carla@email.com\n",
-            "103, javier@email.com\n",
-            "102, laura@email.com\n",
-            "101, mario@email.com\n",
-            "109, rafael@email.com\n",
+            "andres@email.com, 105\n",
+            "carla@email.com, 104\n",
+            "javier@email.com, 103\n",
+            "laura@email.com, 102\n",
+            "mario@email.com, 101\n",
+            "rafael@email.com, 109\n",
         ];
 
         let output = std::process::Command::new("./target/debug/mini_sql")
@@ -272,12 +293,12 @@ mod tests_select {
     #[test]
     fn test_select_ordering_desc() {
         let expected: Vec<&str> = vec![
-            "109, rafael@email.com\n",
-            "101, mario@email.com\n",
-            "102, laura@email.com\n",
-            "103, javier@email.com\n",
-            "104, carla@email.com\n",
-            "105, andres@email.com\n",
+            "rafael@email.com, 109\n",
+            "mario@email.com, 101\n",
+            "laura@email.com, 102\n",
+            "javier@email.com, 103\n",
+            "carla@email.com, 104\n",
+            "andres@email.com, 105\n",
         ];
 
         let output = std::process::Command::new("./target/debug/mini_sql")
@@ -323,12 +344,12 @@ mod tests_select {
     #[test]
     fn test_select_ordering_numeric_desc() {
         let expected: Vec<&str> = vec![
-            "109, rafael@email.com\n",
-            "105, andres@email.com\n",
-            "104, carla@email.com\n",
-            "103, javier@email.com\n",
-            "102, laura@email.com\n",
-            "101, mario@email.com\n",
+            "rafael@email.com, 109\n",
+            "andres@email.com, 105\n",
+            "carla@email.com, 104\n",
+            "javier@email.com, 103\n",
+            "laura@email.com, 102\n",
+            "mario@email.com, 101\n",
         ];
 
         let output = std::process::Command::new("./target/debug/mini_sql")
@@ -347,11 +368,11 @@ mod tests_select {
     #[test]
     fn test_select_example_1() {
         let expected: Vec<&str> = vec![
-            "102, 2, Teléfono\n",
-            "104, 3, Teclado\n",
-            "105, 4, Mouse\n",
-            "107, 6, Altavoces\n",
-            "110, 6, Teléfono\n",
+            "102, Teléfono, 2\n",
+            "104, Teclado, 3\n",
+            "105, Mouse, 4\n",
+            "107, Altavoces, 6\n",
+            "110, Teléfono, 6\n",
         ];
 
         let output = std::process::Command::new("./target/debug/mini_sql")
@@ -450,6 +471,29 @@ mod tests_select {
         assert_eq!(stdout, expected.concat());
         assert!(stderr.is_empty());
     }
+
+    #[test]
+    fn test_select_parenthesis_before_or() {
+        let expected: Vec<&str> = vec![
+            "104, 3, Teclado, 1\n",
+            "104, 3, Teclado, 4\n",
+            "107, 6, Altavoces, 1\n",
+            "107, 6, Altavoces, 4\n",
+            "110, 6, Teléfono, 2\n",
+        ];
+
+        let output = std::process::Command::new("./target/debug/mini_sql")
+            .arg("data/tables")
+            .arg("SELECT * FROM ordenes WHERE ( id_cliente = 3 ) OR id_cliente = 6")
+            .output()
+            .expect("Failed to execute command");
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        assert_eq!(stdout, expected.concat());
+        assert!(stderr.is_empty());
+    }
 }
 
 mod test_update {
@@ -1093,7 +1137,7 @@ mod test_insert {
             "108,sofia,gonzalez,sofia@email.com,5555566778\n",
             "109,rafael,diaz,rafael@email.com,5556677881\n",
             "110,paula,vera,paula@email.com,5557788992\n",
-            "111,pepe,garcia,pepe@email.com,\n",
+            "111,pepe,garcia,pepe@email.com,\\N\n",
         ];
 
         let content = std::fs::read(format!("tests/temp-{}/clientes.csv", clean_thread_id));
@@ -1219,8 +1263,8 @@ mod test_insert {
             "108,sofia,gonzalez,sofia@email.com,5555566778\n",
             "109,rafael,diaz,rafael@email.com,5556677881\n",
             "110,paula,vera,paula@email.com,5557788992\n",
-            "111,pepe,garcia,pepe@email.com,\n",
-            "112,carlos,rodriguez,carlos@email.com,\n",
+            "111,pepe,garcia,pepe@email.com,\\N\n",
+            "112,carlos,rodriguez,carlos@email.com,\\N\n",
         ];
 
         let content = std::fs::read(format!("tests/temp-{}/clientes.csv", clean_thread_id));
@@ -1341,7 +1385,7 @@ mod test_insert {
             "1,carlos,carlos@gmail.com,1122334455,Calle Falsa 123\n",
             "2,ana,ana@gmail.com,1122335566,Calle Real 456\n",
             "3,martin,martin@hotmail.com,1133445566,Avenida Siempre Viva 789\n",
-            ",julian,julian@gmail.com,,\n",
+            "\\N,julian,julian@gmail.com,\\N,\\N\n",
         ];
 
         let content = std::fs::read(format!("tests/temp-{}/personas.csv", clean_thread_id));
@@ -1364,3 +1408,1795 @@ mod test_insert {
         }
     }
 }
+
+mod test_csv_storage {
+    use crate::{delete_file, duplicate_temp_file, run, PERSONAS_DIR};
+
+    #[test]
+    fn test_insert_after_last_row_without_line_break() {
+        let thread_id = std::thread::current().id();
+        let thread_id_str = format!("{:?}", thread_id);
+        let clean_thread_id = thread_id_str.replace("ThreadId(", "").replace(")", "");
+        let dir = format!("tests/temp-{}", clean_thread_id);
+        if std::fs::create_dir(&dir).is_err() {
+            println!("FAIL: Could not create dir\n");
+            assert_eq!(false, true)
+        }
+        let written = std::fs::write(format!("{}/agenda.csv", dir), "id,nombre\n1,ana");
+
+        let insert = run(&dir, "INSERT INTO agenda (id, nombre) VALUES (2, 'bob')");
+        let content = std::fs::read_to_string(format!("{}/agenda.csv", dir));
+
+        if delete_file(&dir).is_err() {
+            println!("FAIL: Could not delete file\n");
+            assert_eq!(false, true)
+        }
+
+        assert!(written.is_ok());
+        assert!(insert.1.is_empty());
+        assert_eq!(content.unwrap_or_default(), "id,nombre\n1,ana\n2,bob\n");
+    }
+
+    #[test]
+    fn test_short_rows_are_completed_with_null() {
+        let thread_id = std::thread::current().id();
+        let thread_id_str = format!("{:?}", thread_id);
+        let clean_thread_id = thread_id_str.replace("ThreadId(", "").replace(")", "");
+        let dir = format!("tests/temp-{}", clean_thread_id);
+        if std::fs::create_dir(&dir).is_err() {
+            println!("FAIL: Could not create dir\n");
+            assert_eq!(false, true)
+        }
+        let written = std::fs::write(
+            format!("{}/agenda.csv", dir),
+            "id,nombre,tel\n1,ana,5\n2,bob\n",
+        );
+
+        let reordered = run(&dir, "SELECT tel, nombre FROM agenda");
+        let filtered = run(&dir, "SELECT * FROM agenda WHERE tel > 1");
+        let null = run(&dir, "SELECT id FROM agenda WHERE tel IS NULL");
+        let update = run(&dir, "UPDATE agenda SET tel = 1 WHERE id = 2");
+        let updated = std::fs::read_to_string(format!("{}/agenda.csv", dir));
+        let written = written.and_then(|_| {
+            std::fs::write(
+                format!("{}/agenda.csv", dir),
+                "id,nombre,tel\n1,ana,5\n2,bob\n3,carla\n",
+            )
+        });
+        let delete = run(&dir, "DELETE FROM agenda WHERE tel IS NULL AND id = 3");
+        let deleted = std::fs::read_to_string(format!("{}/agenda.csv", dir));
+
+        if delete_file(&dir).is_err() {
+            println!("FAIL: Could not delete file\n");
+            assert_eq!(false, true)
+        }
+
+        assert!(written.is_ok());
+        assert_eq!(reordered, ("5, ana\n, bob\n".to_string(), String::new()));
+        assert_eq!(filtered, ("1, ana, 5\n".to_string(), String::new()));
+        assert_eq!(null, ("2\n".to_string(), String::new()));
+        assert!(update.1.is_empty());
+        assert_eq!(
+            updated.unwrap_or_default(),
+            "id,nombre,tel\n1,ana,5\n2,bob,1\n"
+        );
+        assert!(delete.1.is_empty());
+        assert_eq!(
+            deleted.unwrap_or_default(),
+            "id,nombre,tel\n1,ana,5\n2,bob,\\N\n"
+        );
+    }
+
+    #[test]
+    fn test_insert_and_select_quoted_values() {
+        let thread_id = std::thread::current().id();
+        let thread_id_str = format!("{:?}", thread_id);
+        let clean_thread_id = thread_id_str.replace("ThreadId(", "").replace(")", "");
+        let mut ok = duplicate_temp_file(
+            PERSONAS_DIR,
+            "tests",
+            &format!("temp-{}", clean_thread_id),
+            "personas.csv",
+        );
+        match ok {
+            Ok(_) => (),
+            Err(_) => {
+                println!("FAIL: Could not duplicate file\n");
+                assert_eq!(false, true)
+            }
+        }
+
+        let insert = std::process::Command::new("./target/debug/mini_sql")
+        .arg(format!("tests/temp-{}", clean_thread_id))
+        .arg("INSERT INTO personas (id_persona, Nombre, direccion) VALUES (4, 'julian \"el\"', 'Buenos Aires, AR');")
+        .output()
+        .expect("Failed to execute command");
+
+        let select = std::process::Command::new("./target/debug/mini_sql")
+            .arg(format!("tests/temp-{}", clean_thread_id))
+            .arg("SELECT id_persona, telefono FROM personas WHERE direccion = 'Buenos Aires, AR'")
+            .output()
+            .expect("Failed to execute command");
+
+        let expected: Vec<&str> = vec![
+            "id_persona,Nombre,Correo_electrónico,telefono,direccion\n",
+            "1,carlos,carlos@gmail.com,1122334455,Calle Falsa 123\n",
+            "2,ana,ana@gmail.com,1122335566,Calle Real 456\n",
+            "3,martin,martin@hotmail.com,1133445566,Avenida Siempre Viva 789\n",
+            "4,\"julian \"\"el\"\"\",\\N,\\N,\"Buenos Aires, AR\"\n",
+        ];
+
+        let content = std::fs::read(format!("tests/temp-{}/personas.csv", clean_thread_id));
+        ok = delete_file(&format!("tests/temp-{}", clean_thread_id));
+        match ok {
+            Ok(_) => (),
+            Err(_) => {
+                println!("FAIL: Could not delete file\n");
+                assert_eq!(false, true)
+            }
+        }
+
+        match content {
+            Err(_) => assert_eq!(false, true),
+            Ok(content) => {
+                assert!(insert.stderr.is_empty());
+                assert!(select.stderr.is_empty());
+                assert_eq!(String::from_utf8_lossy(&select.stdout), "4, \n");
+                assert_eq!(String::from_utf8_lossy(&content), expected.concat())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_schema {
+    use crate::{delete_file, duplicate_temp_file, PRODUCTOS_DIR, PRODUCTOS_SCHEMA_DIR};
+
+    #[test]
+    fn test_select_typed_where_and_order() {
+        let output = std::process::Command::new("./target/debug/mini_sql")
+            .arg("data/tables")
+            .arg("SELECT nombre, precio FROM productos WHERE alta >= '2023-01-01' ORDER BY precio")
+            .output()
+            .expect("Failed to execute command");
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        let expected_output = "Mouse, 19.9\nAuriculares, 89.5\nMonitor, 300\nLaptop, 1200.50\n";
+
+        assert!(stderr.is_empty());
+        assert_eq!(stdout, expected_output);
+    }
+
+    #[test]
+    fn test_select_invalid_typed_literal() {
+        let output = std::process::Command::new("./target/debug/mini_sql")
+            .arg("data/tables")
+            .arg("SELECT nombre FROM productos WHERE stock > 'muchos'")
+            .output()
+            .expect("Failed to execute command");
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        let expected_error =
+            "[INVALID_SYNTAX]: [invalid value muchos for a field of type INTEGER ]\n";
+
+        assert_eq!(stderr, expected_error);
+        assert!(stdout.is_empty());
+    }
+
+    #[test]
+    fn test_insert_and_update_validate_types() {
+        let thread_id = std::thread::current().id();
+        let thread_id_str = format!("{:?}", thread_id);
+        let clean_thread_id = thread_id_str.replace("ThreadId(", "").replace(")", "");
+        let mut ok = duplicate_temp_file(
+            PRODUCTOS_DIR,
+            "tests",
+            &format!("temp-{}", clean_thread_id),
+            "productos.csv",
+        )
+        .and_then(|_| {
+            std::fs::copy(
+                PRODUCTOS_SCHEMA_DIR,
+                format!("tests/temp-{}/productos.schema", clean_thread_id),
+            )
+            .map(|_| ())
+        });
+        match ok {
+            Ok(_) => (),
+            Err(_) => {
+                println!("FAIL: Could not duplicate file\n");
+                assert_eq!(false, true)
+            }
+        }
+
+        let invalid_insert = std::process::Command::new("./target/debug/mini_sql")
+            .arg(format!("tests/temp-{}", clean_thread_id))
+            .arg("INSERT INTO productos (id, nombre, precio) VALUES (7, 'Cable', 'barato')")
+            .output()
+            .expect("Failed to execute command");
+
+        let insert = std::process::Command::new("./target/debug/mini_sql")
+            .arg(format!("tests/temp-{}", clean_thread_id))
+            .arg("INSERT INTO productos (id, nombre, precio, activo) VALUES (7, 'Cable', 10, TRUE)")
+            .output()
+            .expect("Failed to execute command");
+
+        let invalid_update = std::process::Command::new("./target/debug/mini_sql")
+            .arg(format!("tests/temp-{}", clean_thread_id))
+            .arg("UPDATE productos SET alta = '2023-02-30' WHERE id = 7")
+            .output()
+            .expect("Failed to execute command");
+
+        let expected: Vec<&str> = vec![
+            "id,nombre,precio,stock,activo,alta\n",
+            "1,Laptop,1200.50,10,true,2023-01-15\n",
+            "2,Monitor,300,25,true,2023-03-02\n",
+            "3,Teclado,45.99,100,false,2022-11-20\n",
+            "4,Mouse,19.9,150,true,2024-02-29\n",
+            "5,Impresora,150,0,false,2021-07-08\n",
+            "6,Auriculares,89.5,40,true,2023-12-01\n",
+            "7,Cable,10,\\N,true,\\N\n",
+        ];
+
+        let content = std::fs::read(format!("tests/temp-{}/productos.csv", clean_thread_id));
+        ok = delete_file(&format!("tests/temp-{}", clean_thread_id));
+        match ok {
+            Ok(_) => (),
+            Err(_) => {
+                println!("FAIL: Could not delete file\n");
+                assert_eq!(false, true)
+            }
+        }
+
+        match content {
+            Err(_) => assert_eq!(false, true),
+            Ok(content) => {
+                assert_eq!(
+                    String::from_utf8_lossy(&invalid_insert.stderr),
+                    "[INVALID_COLUMN]: [invalid value [ barato ] for field [ precio ] of type REAL ]\n"
+                );
+                assert!(insert.stderr.is_empty());
+                assert_eq!(
+                    String::from_utf8_lossy(&invalid_update.stderr),
+                    "[INVALID_COLUMN]: [invalid value [ 2023-02-30 ] for field [ alta ] of type DATE ]\n"
+                );
+                assert_eq!(String::from_utf8_lossy(&content), expected.concat())
+            }
+        }
+    }
+
+    #[test]
+    fn test_update_with_expressions() {
+        let thread_id = std::thread::current().id();
+        let thread_id_str = format!("{:?}", thread_id);
+        let clean_thread_id = thread_id_str.replace("ThreadId(", "").replace(")", "");
+        let mut ok = duplicate_temp_file(
+            PRODUCTOS_DIR,
+            "tests",
+            &format!("temp-{}", clean_thread_id),
+            "productos.csv",
+        )
+        .and_then(|_| {
+            std::fs::copy(
+                PRODUCTOS_SCHEMA_DIR,
+                format!("tests/temp-{}/productos.schema", clean_thread_id),
+            )
+            .map(|_| ())
+        });
+        match ok {
+            Ok(_) => (),
+            Err(_) => {
+                println!("FAIL: Could not duplicate file\n");
+                assert_eq!(false, true)
+            }
+        }
+
+        let run = |sentence: &str| {
+            std::process::Command::new("./target/debug/mini_sql")
+                .arg(format!("tests/temp-{}", clean_thread_id))
+                .arg(sentence)
+                .output()
+                .expect("Failed to execute command")
+        };
+        let update = run(
+            "UPDATE productos SET stock = stock - 1, precio = precio * 2, nombre = nombre || ' Pro' WHERE id = 3",
+        );
+        // los valores se calculan sobre el registro anterior al update
+        let swap = run("UPDATE productos SET id = stock, stock = id WHERE id = 5");
+        let invalid_update = run("UPDATE productos SET stock = stock / 3.0 WHERE id = 1");
+
+        let expected: Vec<&str> = vec![
+            "id,nombre,precio,stock,activo,alta\n",
+            "1,Laptop,1200.50,10,true,2023-01-15\n",
+            "2,Monitor,300,25,true,2023-03-02\n",
+            "3,Teclado Pro,91.98,99,false,2022-11-20\n",
+            "4,Mouse,19.9,150,true,2024-02-29\n",
+            "0,Impresora,150,5,false,2021-07-08\n",
+            "6,Auriculares,89.5,40,true,2023-12-01\n",
+        ];
+
+        let content = std::fs::read(format!("tests/temp-{}/productos.csv", clean_thread_id));
+        ok = delete_file(&format!("tests/temp-{}", clean_thread_id));
+        match ok {
+            Ok(_) => (),
+            Err(_) => {
+                println!("FAIL: Could not delete file\n");
+                assert_eq!(false, true)
+            }
+        }
+
+        match content {
+            Err(_) => assert_eq!(false, true),
+            Ok(content) => {
+                assert!(update.stderr.is_empty());
+                assert!(swap.stderr.is_empty());
+                assert_eq!(
+                    String::from_utf8_lossy(&invalid_update.stderr),
+                    "[INVALID_COLUMN]: [invalid value [ 3.3333333 ] for field [ stock ] of type INTEGER ]\n"
+                );
+                assert_eq!(String::from_utf8_lossy(&content), expected.concat())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_create_table {
+    use crate::{delete_file, run};
+
+    #[test]
+    fn test_create_table_and_insert_with_constraints() {
+        let thread_id = std::thread::current().id();
+        let thread_id_str = format!("{:?}", thread_id);
+        let clean_thread_id = thread_id_str.replace("ThreadId(", "").replace(")", "");
+        let dir = format!("tests/temp-{}", clean_thread_id);
+        if std::fs::create_dir(&dir).is_err() {
+            println!("FAIL: Could not create dir\n");
+            assert_eq!(false, true)
+        }
+
+        let create = run(
+            &dir,
+            "CREATE TABLE cuentas (id INTEGER PRIMARY KEY, titular TEXT NOT NULL, saldo REAL DEFAULT 0)",
+        );
+        let repeated = run(&dir, "CREATE TABLE cuentas (id INTEGER)");
+        let if_not_exists = run(&dir, "CREATE TABLE IF NOT EXISTS cuentas (id INTEGER)");
+        let insert = run(
+            &dir,
+            "INSERT INTO cuentas (id, titular) VALUES (1, 'ana'), (2, 'pepe')",
+        );
+        let missing_required = run(&dir, "INSERT INTO cuentas (id) VALUES (3)");
+        let duplicated = run(
+            &dir,
+            "INSERT INTO cuentas (id, titular) VALUES (3, 'juan'), (1, 'carlos')",
+        );
+
+        let content = std::fs::read_to_string(format!("{}/cuentas.csv", dir));
+        let schema = std::fs::read_to_string(format!("{}/cuentas.schema", dir));
+        if delete_file(&dir).is_err() {
+            println!("FAIL: Could not delete file\n");
+            assert_eq!(false, true)
+        }
+
+        assert!(create.1.is_empty());
+        assert_eq!(
+            repeated.1,
+            "[INVALID_TABLE]: [table cuentas already exists ]\n"
+        );
+        assert!(if_not_exists.1.is_empty());
+        assert!(insert.1.is_empty());
+        assert_eq!(
+            missing_required.1,
+            "[INVALID_COLUMN]: [field [ titular ] can not be empty ]\n"
+        );
+        assert_eq!(
+            duplicated.1,
+            "[INVALID_COLUMN]: [duplicate value [ 1 ] for unique field [ id ] ]\n"
+        );
+        assert_eq!(
+            content.unwrap_or_default(),
+            "id,titular,saldo\n1,ana,0\n2,pepe,0\n"
+        );
+        assert_eq!(
+            schema.unwrap_or_default(),
+            "id INTEGER PRIMARY KEY\ntitular TEXT NOT NULL\nsaldo REAL DEFAULT 0\n"
+        );
+    }
+
+    #[test]
+    fn test_unique_compares_values_of_its_type() {
+        let thread_id = std::thread::current().id();
+        let thread_id_str = format!("{:?}", thread_id);
+        let clean_thread_id = thread_id_str.replace("ThreadId(", "").replace(")", "");
+        let dir = format!("tests/temp-{}", clean_thread_id);
+        if std::fs::create_dir(&dir).is_err() {
+            println!("FAIL: Could not create dir\n");
+            assert_eq!(false, true)
+        }
+
+        let create = run(
+            &dir,
+            "CREATE TABLE cuentas (id INTEGER PRIMARY KEY, cbu REAL UNIQUE, titular TEXT)",
+        );
+        // registros escritos a mano, sin normalizar
+        let written = std::fs::write(
+            format!("{}/cuentas.csv", dir),
+            "id,cbu,titular\n01,2.50,ana\n",
+        );
+        let same_id = run(&dir, "INSERT INTO cuentas (id, cbu) VALUES (1, 3)");
+        let same_cbu = run(&dir, "INSERT INTO cuentas (id, cbu) VALUES (2, 2.5)");
+        let different = run(&dir, "INSERT INTO cuentas (id, cbu) VALUES (2, 25)");
+
+        if delete_file(&dir).is_err() {
+            println!("FAIL: Could not delete file\n");
+            assert_eq!(false, true)
+        }
+
+        assert!(create.1.is_empty());
+        assert!(written.is_ok());
+        assert_eq!(
+            same_id.1,
+            "[INVALID_COLUMN]: [duplicate value [ 1 ] for unique field [ id ] ]\n"
+        );
+        assert_eq!(
+            same_cbu.1,
+            "[INVALID_COLUMN]: [duplicate value [ 2.5 ] for unique field [ cbu ] ]\n"
+        );
+        assert!(different.1.is_empty());
+    }
+
+    #[test]
+    fn test_update_can_not_repeat_unique_values() {
+        let thread_id = std::thread::current().id();
+        let thread_id_str = format!("{:?}", thread_id);
+        let clean_thread_id = thread_id_str.replace("ThreadId(", "").replace(")", "");
+        let dir = format!("tests/temp-{}", clean_thread_id);
+        if std::fs::create_dir(&dir).is_err() {
+            println!("FAIL: Could not create dir\n");
+            assert_eq!(false, true)
+        }
+
+        let create = run(
+            &dir,
+            "CREATE TABLE cuentas (id INTEGER PRIMARY KEY, email TEXT UNIQUE, titular TEXT)",
+        );
+        let insert = run(
+            &dir,
+            "INSERT INTO cuentas (id, email, titular) VALUES (1, 'ana@mail.com', 'ana'), (2, NULL, 'pepe'), (3, NULL, 'juan')",
+        );
+        let same_id = run(&dir, "UPDATE cuentas SET id = 1 WHERE titular = 'juan'");
+        let all_same_email = run(&dir, "UPDATE cuentas SET email = 'x@mail.com'");
+        let shifted_ids = run(&dir, "UPDATE cuentas SET id = id + 1");
+
+        let content = std::fs::read_to_string(format!("{}/cuentas.csv", dir));
+        if delete_file(&dir).is_err() {
+            println!("FAIL: Could not delete file\n");
+            assert_eq!(false, true)
+        }
+
+        assert!(create.1.is_empty());
+        assert!(insert.1.is_empty());
+        assert_eq!(
+            same_id.1,
+            "[INVALID_COLUMN]: [duplicate value [ 1 ] for unique field [ id ] on row 3 ]\n"
+        );
+        assert_eq!(
+            all_same_email.1,
+            "[INVALID_COLUMN]: [duplicate value [ x@mail.com ] for unique field [ email ] on row 2 ]\n"
+        );
+        // cada id nuevo es unico aunque coincida con el id anterior de otro registro
+        assert!(shifted_ids.1.is_empty());
+        assert_eq!(
+            content.unwrap_or_default(),
+            "id,email,titular\n2,ana@mail.com,ana\n3,\\N,pepe\n4,\\N,juan\n"
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_drop_and_truncate {
+    use crate::{delete_file, duplicate_temp_file, run, PRODUCTOS_DIR, PRODUCTOS_SCHEMA_DIR};
+
+    #[test]
+    fn test_truncate_then_drop() {
+        let thread_id = std::thread::current().id();
+        let thread_id_str = format!("{:?}", thread_id);
+        let clean_thread_id = thread_id_str.replace("ThreadId(", "").replace(")", "");
+        let dir = format!("tests/temp-{}", clean_thread_id);
+        let ok = duplicate_temp_file(
+            PRODUCTOS_DIR,
+            "tests",
+            &format!("temp-{}", clean_thread_id),
+            "productos.csv",
+        )
+        .and_then(|_| {
+            std::fs::copy(PRODUCTOS_SCHEMA_DIR, format!("{}/productos.schema", dir)).map(|_| ())
+        });
+        if ok.is_err() {
+            println!("FAIL: Could not duplicate file\n");
+            assert_eq!(false, true)
+        }
+
+        let truncate = run(&dir, "TRUNCATE TABLE productos");
+        let truncated = std::fs::read_to_string(format!("{}/productos.csv", dir));
+        let select = run(&dir, "SELECT * FROM productos");
+        let drop = run(&dir, "DROP TABLE productos");
+        let files: Vec<String> = std::fs::read_dir(&dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.file_name().to_string_lossy().to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let drop_again = run(&dir, "DROP TABLE productos");
+        let drop_if_exists = run(&dir, "DROP TABLE IF EXISTS productos");
+
+        if delete_file(&dir).is_err() {
+            println!("FAIL: Could not delete file\n");
+            assert_eq!(false, true)
+        }
+
+        assert!(truncate.1.is_empty());
+        assert_eq!(
+            truncated.unwrap_or_default(),
+            "id,nombre,precio,stock,activo,alta\n"
+        );
+        assert!(select.0.is_empty());
+        assert!(drop.1.is_empty());
+        assert!(files.is_empty());
+        assert_eq!(
+            drop_again.1,
+            format!(
+                "[INVALID_TABLE]: [Unable to open file at {}/productos]\n",
+                dir
+            )
+        );
+        assert!(drop_if_exists.1.is_empty());
+    }
+
+    #[test]
+    fn test_drop_can_not_leave_the_tables_folder() {
+        let thread_id = std::thread::current().id();
+        let thread_id_str = format!("{:?}", thread_id);
+        let clean_thread_id = thread_id_str.replace("ThreadId(", "").replace(")", "");
+        let dir = format!("tests/temp-{}", clean_thread_id);
+        let tables = format!("{}/tablas", dir);
+        let ok = duplicate_temp_file(
+            PRODUCTOS_DIR,
+            "tests",
+            &format!("temp-{}", clean_thread_id),
+            "productos.csv",
+        )
+        .and_then(|_| std::fs::create_dir(&tables));
+        if ok.is_err() {
+            println!("FAIL: Could not duplicate file\n");
+            assert_eq!(false, true)
+        }
+
+        let drop = run(&tables, "DROP TABLE \"../productos\"");
+        let kept = std::path::Path::new(&format!("{}/productos.csv", dir)).exists();
+
+        if delete_file(&dir).is_err() {
+            println!("FAIL: Could not delete file\n");
+            assert_eq!(false, true)
+        }
+
+        assert_eq!(
+            drop.1,
+            "[INVALID_TABLE]: [table name [ ../productos ] can not contain path separators ]\n"
+        );
+        assert!(kept);
+    }
+}
+
+#[cfg(test)]
+mod test_alter_table {
+    use crate::{delete_file, duplicate_temp_file, run, PRODUCTOS_DIR, PRODUCTOS_SCHEMA_DIR};
+
+    #[test]
+    fn test_alter_columns_and_rename_table() {
+        let thread_id = std::thread::current().id();
+        let thread_id_str = format!("{:?}", thread_id);
+        let clean_thread_id = thread_id_str.replace("ThreadId(", "").replace(")", "");
+        let dir = format!("tests/temp-{}", clean_thread_id);
+        let ok = duplicate_temp_file(
+            PRODUCTOS_DIR,
+            "tests",
+            &format!("temp-{}", clean_thread_id),
+            "productos.csv",
+        )
+        .and_then(|_| {
+            std::fs::copy(PRODUCTOS_SCHEMA_DIR, format!("{}/productos.schema", dir)).map(|_| ())
+        });
+        if ok.is_err() {
+            println!("FAIL: Could not duplicate file\n");
+            assert_eq!(false, true)
+        }
+
+        let add = run(
+            &dir,
+            "ALTER TABLE productos ADD COLUMN descuento REAL DEFAULT 0.5",
+        );
+        let drop = run(&dir, "ALTER TABLE productos DROP COLUMN alta");
+        let rename_column = run(
+            &dir,
+            "ALTER TABLE productos RENAME COLUMN stock TO unidades",
+        );
+        let repeated = run(&dir, "ALTER TABLE productos ADD COLUMN precio");
+        let untyped_default = run(&dir, "ALTER TABLE productos ADD COLUMN origen DEFAULT 'AR'");
+        let rename = run(&dir, "ALTER TABLE productos RENAME TO articulos");
+        let select = run(
+            &dir,
+            "SELECT nombre, descuento FROM articulos WHERE unidades > 50 ORDER BY unidades DESC",
+        );
+
+        let content = std::fs::read_to_string(format!("{}/articulos.csv", dir));
+        let schema = std::fs::read_to_string(format!("{}/articulos.schema", dir));
+        if delete_file(&dir).is_err() {
+            println!("FAIL: Could not delete file\n");
+            assert_eq!(false, true)
+        }
+
+        let expected: Vec<&str> = vec![
+            "id,nombre,precio,unidades,activo,descuento\n",
+            "1,Laptop,1200.50,10,true,0.5\n",
+            "2,Monitor,300,25,true,0.5\n",
+            "3,Teclado,45.99,100,false,0.5\n",
+            "4,Mouse,19.9,150,true,0.5\n",
+            "5,Impresora,150,0,false,0.5\n",
+            "6,Auriculares,89.5,40,true,0.5\n",
+        ];
+
+        assert!(add.1.is_empty());
+        assert!(drop.1.is_empty());
+        assert!(rename_column.1.is_empty());
+        assert_eq!(
+            repeated.1,
+            "[INVALID_COLUMN]: [field [ precio ] already exists ]\n"
+        );
+        assert_eq!(
+            untyped_default.1,
+            "[INVALID_SYNTAX]: [field [ origen ] needs a type to have a DEFAULT value ]\n"
+        );
+        assert!(rename.1.is_empty());
+        assert_eq!(select.0, "Mouse, 0.5\nTeclado, 0.5\n");
+        assert_eq!(content.unwrap_or_default(), expected.concat());
+        assert_eq!(
+            schema.unwrap_or_default(),
+            "id INTEGER\nnombre TEXT\nprecio REAL\nunidades INTEGER\nactivo BOOLEAN\ndescuento REAL DEFAULT 0.5\n"
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_join {
+    use crate::{run, TABLES_DIR};
+
+    #[test]
+    fn test_inner_join_with_aliases() {
+        let (stdout, stderr) = run(
+            TABLES_DIR,
+            "SELECT c.nombre, o.producto FROM clientes2 c JOIN ordenes o ON c.id = o.id_cliente WHERE o.cantidad > 1 ORDER BY o.producto",
+        );
+
+        let expected_output =
+            "Laura, Altavoces\nMaría, Mouse\nCarlos, Teclado\nAna, Teléfono\nLaura, Teléfono\n";
+
+        assert!(stderr.is_empty());
+        assert_eq!(stdout, expected_output);
+    }
+
+    #[test]
+    fn test_left_join_keeps_unmatched() {
+        let (stdout, stderr) = run(
+            TABLES_DIR,
+            "SELECT c.nombre, o.id FROM clientes2 AS c LEFT JOIN ordenes AS o ON c.id = o.id_cliente AND o.cantidad > 1",
+        );
+
+        let expected_output =
+            "Juan, \nAna, 102\nCarlos, 104\nMaría, 105\nJosé, \nLaura, 107\nLaura, 110\n";
+
+        assert!(stderr.is_empty());
+        assert_eq!(stdout, expected_output);
+    }
+
+    #[test]
+    fn test_cross_and_comma_join() {
+        let expected_output = "carlos, 1\ncarlos, 2\nana, 1\nana, 2\nmartin, 1\nmartin, 2\n";
+
+        let (stdout, stderr) = run(
+            TABLES_DIR,
+            "SELECT p.Nombre, c.id FROM personas p CROSS JOIN clientes2 c WHERE c.id <= 2",
+        );
+        assert!(stderr.is_empty());
+        assert_eq!(stdout, expected_output);
+
+        let (stdout, stderr) = run(
+            TABLES_DIR,
+            "SELECT personas.Nombre, clientes2.id FROM personas, clientes2 WHERE id <= 2",
+        );
+        assert!(stderr.is_empty());
+        assert_eq!(stdout, expected_output);
+    }
+
+    #[test]
+    fn test_join_ambiguous_field() {
+        let (stdout, stderr) = run(TABLES_DIR, "SELECT nombre FROM clientes2, clientes");
+
+        let expected_error =
+            "[INVALID_COLUMN]: [field [ nombre ] is on more than one table, qualify it as table.nombre ]\n";
+
+        assert_eq!(stderr, expected_error);
+        assert!(stdout.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod test_aggregates {
+    use crate::{run, TABLES_DIR};
+
+    #[test]
+    fn test_count_orders_of_client() {
+        let (stdout, stderr) = run(
+            TABLES_DIR,
+            "SELECT COUNT(*) FROM ordenes WHERE id_cliente = 6",
+        );
+        assert!(stderr.is_empty());
+        assert_eq!(stdout, "3\n");
+    }
+
+    #[test]
+    fn test_all_aggregates() {
+        let (stdout, stderr) = run(
+            TABLES_DIR,
+            "SELECT COUNT(*), COUNT(DISTINCT producto), SUM(cantidad), AVG(cantidad), MIN(producto), MAX(cantidad) FROM ordenes",
+        );
+        assert!(stderr.is_empty());
+        assert_eq!(stdout, "12, 8, 21, 1.75, Altavoces, 4\n");
+    }
+
+    #[test]
+    fn test_typed_aggregates_over_join() {
+        let (stdout, stderr) = run(
+            TABLES_DIR,
+            "SELECT SUM(p.precio), MAX(p.alta), COUNT(o.id) FROM productos p LEFT JOIN ordenes o ON p.nombre = o.producto WHERE p.activo = true",
+        );
+        assert!(stderr.is_empty());
+        assert_eq!(stdout, "2810.4, 2024-02-29, 5\n");
+    }
+
+    #[test]
+    fn test_aggregate_mixed_with_field() {
+        let (stdout, stderr) = run(TABLES_DIR, "SELECT producto, COUNT(*) FROM ordenes");
+        assert_eq!(
+            stderr,
+            "[INVALID_SYNTAX]: [field producto must appear on GROUP BY or be used inside an aggregate function ]\n"
+        );
+        assert!(stdout.is_empty());
+    }
+
+    #[test]
+    fn test_group_by_with_having() {
+        let (stdout, stderr) = run(
+            TABLES_DIR,
+            "SELECT id_cliente, SUM(cantidad) FROM ordenes GROUP BY id_cliente HAVING SUM(cantidad) > 3",
+        );
+        assert!(stderr.is_empty());
+        assert_eq!(stdout, "3, 5\n6, 7\n");
+    }
+
+    #[test]
+    fn test_group_by_joined_tables() {
+        let (stdout, stderr) = run(
+            TABLES_DIR,
+            "SELECT c.nombre, COUNT(o.id) FROM clientes2 c LEFT JOIN ordenes o ON c.id = o.id_cliente GROUP BY c.nombre HAVING COUNT(o.id) >= 3",
+        );
+        assert!(stderr.is_empty());
+        assert_eq!(stdout, "Laura, 3\n");
+    }
+
+    #[test]
+    fn test_group_by_typed_field() {
+        let (stdout, stderr) = run(
+            TABLES_DIR,
+            "SELECT activo, COUNT(*), MIN(alta) FROM productos GROUP BY activo ORDER BY activo",
+        );
+        assert!(stderr.is_empty());
+        assert_eq!(stdout, "false, 2, 2021-07-08\ntrue, 4, 2023-01-15\n");
+    }
+
+    #[test]
+    fn test_having_field_not_grouped() {
+        let (stdout, stderr) = run(
+            TABLES_DIR,
+            "SELECT id_cliente FROM ordenes GROUP BY id_cliente HAVING cantidad > 1",
+        );
+        assert_eq!(
+            stderr,
+            "[INVALID_SYNTAX]: [field cantidad must appear on GROUP BY or be used inside an aggregate function ]\n"
+        );
+        assert!(stdout.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod test_limit {
+    use crate::{run, TABLES_DIR};
+
+    #[test]
+    fn test_limit_without_order() {
+        let (stdout, stderr) = run(
+            TABLES_DIR,
+            "SELECT id, producto FROM ordenes LIMIT 2 OFFSET 1",
+        );
+        assert!(stderr.is_empty());
+        assert_eq!(stdout, "103, Monitor\n102, Teléfono\n");
+    }
+
+    #[test]
+    fn test_limit_with_order() {
+        let (stdout, stderr) = run(
+            TABLES_DIR,
+            "SELECT nombre, precio FROM productos ORDER BY precio DESC LIMIT 2",
+        );
+        assert!(stderr.is_empty());
+        assert_eq!(stdout, "Laptop, 1200.50\nMonitor, 300\n");
+    }
+
+    #[test]
+    fn test_fetch_first_with_offset() {
+        let (stdout, stderr) = run(
+            TABLES_DIR,
+            "SELECT nombre FROM productos ORDER BY precio OFFSET 1 ROWS FETCH NEXT 2 ROWS ONLY",
+        );
+        assert!(stderr.is_empty());
+        assert_eq!(stdout, "Teclado\nAuriculares\n");
+    }
+
+    #[test]
+    fn test_limit_on_groups() {
+        let (stdout, stderr) = run(
+            TABLES_DIR,
+            "SELECT id_cliente, COUNT(*) FROM ordenes GROUP BY id_cliente ORDER BY id_cliente LIMIT 2 OFFSET 1",
+        );
+        assert!(stderr.is_empty());
+        assert_eq!(stdout, "2, 1\n3, 2\n");
+    }
+
+    #[test]
+    fn test_limit_zero() {
+        let (stdout, stderr) = run(TABLES_DIR, "SELECT * FROM ordenes LIMIT 0");
+        assert!(stderr.is_empty());
+        assert!(stdout.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod test_order_by {
+    use crate::{run, TABLES_DIR};
+
+    #[test]
+    fn test_order_by_several_keys() {
+        let (stdout, stderr) = run(
+            TABLES_DIR,
+            "SELECT id_cliente, producto, cantidad FROM ordenes WHERE id_cliente >= 5 ORDER BY id_cliente DESC, cantidad, producto DESC",
+        );
+        assert!(stderr.is_empty());
+        assert_eq!(
+            stdout,
+            "6, Altavoces, 1\n6, Teléfono, 2\n6, Altavoces, 4\n5, Laptop, 1\n5, Impresora, 1\n"
+        );
+    }
+
+    #[test]
+    fn test_order_by_alias_and_position() {
+        let (stdout, stderr) = run(
+            TABLES_DIR,
+            "SELECT id_cliente, SUM(cantidad) AS total FROM ordenes GROUP BY id_cliente ORDER BY total DESC, 1 LIMIT 4",
+        );
+        assert!(stderr.is_empty());
+        assert_eq!(stdout, "6, 7\n3, 5\n4, 3\n1, 2\n");
+    }
+
+    #[test]
+    fn test_order_by_is_stable() {
+        let (stdout, stderr) = run(
+            TABLES_DIR,
+            "SELECT id, id_cliente FROM ordenes ORDER BY id_cliente LIMIT 4",
+        );
+        assert!(stderr.is_empty());
+        assert_eq!(stdout, "101, 1\n103, 1\n102, 2\n104, 3\n");
+    }
+
+    #[test]
+    fn test_order_by_invalid_position() {
+        let (stdout, stderr) = run(TABLES_DIR, "SELECT id FROM ordenes ORDER BY 2");
+        assert_eq!(
+            stderr,
+            "[INVALID_SYNTAX]: [ORDER BY position 2 is not on the selected fields ]\n"
+        );
+        assert!(stdout.is_empty());
+    }
+
+    #[test]
+    fn test_order_by_phone_numbers() {
+        let thread_id = std::thread::current().id();
+        let thread_id_str = format!("{:?}", thread_id);
+        let clean_thread_id = thread_id_str.replace("ThreadId(", "").replace(")", "");
+        let dir = format!("tests/temp-{}", clean_thread_id);
+        if crate::duplicate_temp_file(
+            crate::PERSONAS_DIR,
+            "tests",
+            &format!("temp-{}", clean_thread_id),
+            "personas.csv",
+        )
+        .is_err()
+        {
+            println!("FAIL: Could not duplicate file\n");
+            assert_eq!(false, true)
+        }
+
+        let run_on = |sentence: &str| {
+            std::process::Command::new("./target/debug/mini_sql")
+                .arg(&dir)
+                .arg(sentence)
+                .output()
+                .expect("Failed to execute command")
+        };
+        let insert = run_on(
+            "INSERT INTO personas (id_persona, Nombre, telefono) VALUES (4, 'emergencias', '911'), (5, 'sin datos', NULL), (6, 'oficina', 'interno 12')",
+        );
+        let select = run_on("SELECT id_persona, telefono FROM personas ORDER BY telefono DESC");
+        let _ = crate::delete_file(&dir);
+
+        assert!(insert.stderr.is_empty());
+        assert_eq!(
+            String::from_utf8_lossy(&select.stdout),
+            "6, interno 12\n3, 1133445566\n2, 1122335566\n1, 1122334455\n4, 911\n5, \n"
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_distinct {
+    use crate::{delete_file, run, TABLES_DIR};
+
+    #[test]
+    fn test_distinct_agrees_with_group_by() {
+        let thread_id = std::thread::current().id();
+        let thread_id_str = format!("{:?}", thread_id);
+        let clean_thread_id = thread_id_str.replace("ThreadId(", "").replace(")", "");
+        let dir = format!("tests/temp-{}", clean_thread_id);
+        if std::fs::create_dir(&dir).is_err() {
+            println!("FAIL: Could not create dir\n");
+            assert_eq!(false, true)
+        }
+        let written = std::fs::write(format!("{}/medidas.csv", dir), "x\n1\n01\n1.0\n2\n")
+            .and_then(|_| std::fs::write(format!("{}/medidas.schema", dir), "x REAL\n"));
+
+        let distinct = run(&dir, "SELECT DISTINCT x FROM medidas");
+        let count = run(&dir, "SELECT COUNT(DISTINCT x) FROM medidas");
+        let grouped = run(&dir, "SELECT x, COUNT(*) FROM medidas GROUP BY x");
+
+        if delete_file(&dir).is_err() {
+            println!("FAIL: Could not delete file\n");
+            assert_eq!(false, true)
+        }
+
+        assert!(written.is_ok());
+        assert_eq!(distinct, ("1\n2\n".to_string(), String::new()));
+        assert_eq!(count, ("2\n".to_string(), String::new()));
+        assert_eq!(grouped, ("1, 3\n2, 1\n".to_string(), String::new()));
+    }
+
+    #[test]
+    fn test_select_distinct() {
+        let (stdout, stderr) = run(
+            TABLES_DIR,
+            "SELECT DISTINCT producto FROM ordenes WHERE id_cliente > 3 ORDER BY producto DESC",
+        );
+        assert!(stderr.is_empty());
+        assert_eq!(
+            stdout,
+            "Teléfono\nMouse\nLaptop\nImpresora\nAuriculares\nAltavoces\n"
+        );
+    }
+
+    #[test]
+    fn test_select_distinct_with_limit() {
+        let (stdout, stderr) = run(
+            TABLES_DIR,
+            "SELECT DISTINCT id_cliente FROM ordenes LIMIT 3",
+        );
+        assert!(stderr.is_empty());
+        assert_eq!(stdout, "1\n2\n3\n");
+    }
+
+    #[test]
+    fn test_distinct_on_keeps_first_after_order() {
+        let (stdout, stderr) = run(
+            TABLES_DIR,
+            "SELECT DISTINCT ON (id_cliente) id_cliente, producto, cantidad FROM ordenes ORDER BY id_cliente, cantidad DESC",
+        );
+        assert!(stderr.is_empty());
+        assert_eq!(
+            stdout,
+            "1, Laptop, 1\n2, Teléfono, 2\n3, Teclado, 4\n4, Mouse, 2\n5, Impresora, 1\n6, Altavoces, 4\n"
+        );
+    }
+
+    #[test]
+    fn test_distinct_on_must_lead_order() {
+        let (stdout, stderr) = run(
+            TABLES_DIR,
+            "SELECT DISTINCT ON (producto) producto FROM ordenes ORDER BY id",
+        );
+        assert_eq!(
+            stderr,
+            "[INVALID_SYNTAX]: [DISTINCT ON keys must match the first ORDER BY keys ]\n"
+        );
+        assert!(stdout.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod test_projection {
+    use crate::{run, TABLES_DIR};
+
+    #[test]
+    fn test_fields_in_requested_order() {
+        let (stdout, stderr) = run(
+            TABLES_DIR,
+            "SELECT telefono, id_cliente, nombre FROM clientes WHERE id_cliente < 103",
+        );
+        assert!(stderr.is_empty());
+        assert_eq!(stdout, "5551234567, 101, mario\n5559876543, 102, laura\n");
+    }
+
+    #[test]
+    fn test_repeated_fields_and_alias() {
+        let (stdout, stderr) = run(
+            TABLES_DIR,
+            "SELECT nombre AS n, id_cliente, nombre FROM clientes WHERE id_cliente < 103 ORDER BY n DESC",
+        );
+        assert!(stderr.is_empty());
+        assert_eq!(stdout, "mario, 101, mario\nlaura, 102, laura\n");
+    }
+
+    #[test]
+    fn test_wildcard_with_other_fields() {
+        let (stdout, stderr) = run(
+            TABLES_DIR,
+            "SELECT *, nombre FROM clientes WHERE id_cliente = 101",
+        );
+        assert!(stderr.is_empty());
+        assert_eq!(
+            stdout,
+            "101, mario, hernandez, mario@email.com, 5551234567, mario\n"
+        );
+
+        let (stdout, stderr) = run(
+            TABLES_DIR,
+            "SELECT apellido, * FROM clientes WHERE id_cliente = 101",
+        );
+        assert!(stderr.is_empty());
+        assert_eq!(
+            stdout,
+            "hernandez, 101, mario, hernandez, mario@email.com, 5551234567\n"
+        );
+    }
+
+    #[test]
+    fn test_aggregates_in_requested_order() {
+        let (stdout, stderr) = run(
+            TABLES_DIR,
+            "SELECT COUNT(*), id_cliente FROM ordenes WHERE id_cliente < 3 GROUP BY id_cliente",
+        );
+        assert!(stderr.is_empty());
+        assert_eq!(stdout, "2, 1\n1, 2\n");
+    }
+}
+
+#[cfg(test)]
+mod test_expressions {
+    use crate::{run, TABLES_DIR};
+
+    #[test]
+    fn test_select_arithmetic_with_alias() {
+        let (stdout, stderr) = run(
+            TABLES_DIR,
+            "SELECT id, cantidad * 10 AS total, -cantidad FROM ordenes WHERE id_cliente = 4 ORDER BY total DESC",
+        );
+        assert!(stderr.is_empty());
+        assert_eq!(stdout, "105, 20, -2\n108, 10, -1\n");
+    }
+
+    #[test]
+    fn test_select_concatenation() {
+        let (stdout, stderr) = run(
+            TABLES_DIR,
+            "SELECT nombre || ' ' || apellido AS completo FROM clientes WHERE id_cliente < 103",
+        );
+        assert!(stderr.is_empty());
+        assert_eq!(stdout, "mario hernandez\nlaura ortega\n");
+    }
+
+    #[test]
+    fn test_expression_on_where() {
+        let (stdout, stderr) = run(TABLES_DIR, "SELECT id FROM ordenes WHERE cantidad * 2 > 6");
+        assert!(stderr.is_empty());
+        assert_eq!(stdout, "104\n107\n");
+    }
+
+    #[test]
+    fn test_expression_over_aggregates() {
+        let (stdout, stderr) = run(
+            TABLES_DIR,
+            "SELECT id_cliente, SUM(cantidad) * 10 AS total FROM ordenes GROUP BY id_cliente HAVING COUNT(*) >= 2 ORDER BY total DESC",
+        );
+        assert!(stderr.is_empty());
+        assert_eq!(stdout, "6, 70\n3, 50\n4, 30\n1, 20\n5, 20\n");
+    }
+
+    #[test]
+    fn test_division_by_zero() {
+        let (stdout, stderr) = run(TABLES_DIR, "SELECT cantidad / 0 FROM ordenes");
+        assert_eq!(stderr, "[INVALID_SYNTAX]: [division by zero on 1 / 0 ]\n");
+        assert!(stdout.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod test_like {
+    use crate::{delete_file, duplicate_temp_file, run, CLIENTES_DIR};
+
+    #[test]
+    fn test_select_like() {
+        let (stdout, stderr) = run(
+            "data/tables",
+            "SELECT nombre FROM clientes WHERE nombre LIKE '%r%o' OR apellido LIKE 'd_a%'",
+        );
+        assert!(stderr.is_empty());
+        assert_eq!(stdout, "mario\njavier\nfernando\nrafael\n");
+    }
+
+    #[test]
+    fn test_select_ilike_and_not_like() {
+        let (stdout, stderr) = run(
+            "data/tables",
+            "SELECT producto FROM ordenes WHERE producto ILIKE 'M%' AND producto NOT LIKE '%r'",
+        );
+        assert!(stderr.is_empty());
+        assert_eq!(stdout, "Mouse\n");
+    }
+
+    #[test]
+    fn test_update_and_delete_with_like() {
+        let thread_id = std::thread::current().id();
+        let thread_id_str = format!("{:?}", thread_id);
+        let clean_thread_id = thread_id_str.replace("ThreadId(", "").replace(")", "");
+        let route = format!("tests/temp-{}", clean_thread_id);
+        let mut ok = duplicate_temp_file(
+            CLIENTES_DIR,
+            "tests",
+            &format!("temp-{}", clean_thread_id),
+            "clientes.csv",
+        );
+        match ok {
+            Ok(_) => (),
+            Err(_) => {
+                println!("FAIL: Could not duplicate file\n");
+                assert_eq!(false, true)
+            }
+        }
+
+        let update = run(
+            &route,
+            "UPDATE clientes SET email = nombre || '@gmail.com' WHERE apellido ILIKE 'D%'",
+        );
+        let delete = run(
+            &route,
+            "DELETE FROM clientes WHERE email NOT LIKE '%@gmail.com'",
+        );
+
+        let expected: Vec<&str> = vec![
+            "id_cliente,nombre,apellido,email,telefono\n",
+            "103,javier,diaz,javier@gmail.com,5551122334\n",
+            "109,rafael,diaz,rafael@gmail.com,5556677881\n",
+        ];
+
+        let content = std::fs::read(format!("{}/clientes.csv", route));
+        ok = delete_file(&route);
+        match ok {
+            Ok(_) => (),
+            Err(_) => {
+                println!("FAIL: Could not delete file\n");
+                assert_eq!(false, true)
+            }
+        }
+
+        match content {
+            Err(_) => assert_eq!(false, true),
+            Ok(content) => {
+                assert!(update.1.is_empty());
+                assert!(delete.1.is_empty());
+                assert_eq!(String::from_utf8_lossy(&content), expected.concat())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_in_and_between {
+    use crate::{run, TABLES_DIR};
+
+    #[test]
+    fn test_select_in_and_not_in() {
+        let (stdout, stderr) = run(
+            TABLES_DIR,
+            "SELECT id, producto FROM ordenes WHERE id_cliente IN (1, 6) AND producto NOT IN ('Laptop', 'Altavoces')",
+        );
+        assert!(stderr.is_empty());
+        assert_eq!(stdout, "103, Monitor\n110, Teléfono\n");
+    }
+
+    #[test]
+    fn test_select_in_typed_field() {
+        let (stdout, stderr) = run(
+            TABLES_DIR,
+            "SELECT nombre FROM productos WHERE precio IN (1200.5, 300.0, 19.90)",
+        );
+        assert!(stderr.is_empty());
+        assert_eq!(stdout, "Laptop\nMonitor\nMouse\n");
+    }
+
+    #[test]
+    fn test_select_between_and_boolean_and() {
+        let (stdout, stderr) = run(
+            TABLES_DIR,
+            "SELECT nombre FROM productos WHERE alta BETWEEN '2023-01-01' AND '2023-12-31' AND activo = true",
+        );
+        assert!(stderr.is_empty());
+        assert_eq!(stdout, "Laptop\nMonitor\nAuriculares\n");
+
+        let (stdout, stderr) = run(
+            TABLES_DIR,
+            "SELECT nombre FROM productos WHERE precio NOT BETWEEN 50 AND 500",
+        );
+        assert!(stderr.is_empty());
+        assert_eq!(stdout, "Laptop\nTeclado\nMouse\n");
+    }
+
+    #[test]
+    fn test_between_without_and() {
+        let (stdout, stderr) = run(
+            TABLES_DIR,
+            "SELECT id FROM ordenes WHERE id BETWEEN 103 105",
+        );
+        assert_eq!(
+            stderr,
+            "[INVALID_SYNTAX]: [expected AND at line 1, column 45 ]\n"
+        );
+        assert!(stdout.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod test_null {
+    use crate::{delete_file, duplicate_temp_file, run, CLIENTES_DIR};
+
+    #[test]
+    fn test_empty_text_is_not_null() {
+        let thread_id = std::thread::current().id();
+        let thread_id_str = format!("{:?}", thread_id);
+        let clean_thread_id = thread_id_str.replace("ThreadId(", "").replace(")", "");
+        let route = format!("tests/temp-{}", clean_thread_id);
+        if duplicate_temp_file(
+            CLIENTES_DIR,
+            "tests",
+            &format!("temp-{}", clean_thread_id),
+            "clientes.csv",
+        )
+        .is_err()
+        {
+            println!("FAIL: Could not duplicate file\n");
+            assert_eq!(false, true)
+        }
+
+        let insert = run(
+            &route,
+            "INSERT INTO clientes (id_cliente, nombre, apellido, email) VALUES (111, 'pepe', '', NULL)",
+        );
+        let empty = run(
+            &route,
+            "SELECT id_cliente FROM clientes WHERE apellido = ''",
+        );
+        let is_null = run(
+            &route,
+            "SELECT id_cliente FROM clientes WHERE apellido IS NULL OR email IS NOT NULL AND id_cliente = 111",
+        );
+        let count = run(
+            &route,
+            "SELECT COUNT(apellido), COUNT(email) FROM clientes WHERE id_cliente = 111",
+        );
+        let insert_marker = run(
+            &route,
+            "INSERT INTO clientes (id_cliente, nombre) VALUES (112, '\\N')",
+        );
+        let update_marker = run(
+            &route,
+            "UPDATE clientes SET apellido = '\\N' WHERE id_cliente = 111",
+        );
+        let marker = run(
+            &route,
+            "SELECT COUNT(*) FROM clientes WHERE id_cliente = 112 OR apellido IS NULL",
+        );
+        if delete_file(&route).is_err() {
+            println!("FAIL: Could not delete file\n");
+            assert_eq!(false, true)
+        }
+
+        assert!(insert.1.is_empty());
+        assert_eq!(empty, ("111\n".to_string(), String::new()));
+        assert_eq!(is_null, (String::new(), String::new()));
+        assert_eq!(count, ("1, 0\n".to_string(), String::new()));
+        assert_eq!(
+            insert_marker.1,
+            "[INVALID_COLUMN]: [the text [ \\N ] for field [ nombre ] is how NULL is stored, write NULL instead ]\n"
+        );
+        assert_eq!(
+            update_marker.1,
+            "[INVALID_COLUMN]: [the text [ \\N ] for field [ apellido ] is how NULL is stored, write NULL instead ]\n"
+        );
+        assert_eq!(marker, ("0\n".to_string(), String::new()));
+    }
+
+    #[test]
+    fn test_null_on_missing_fields() {
+        let thread_id = std::thread::current().id();
+        let thread_id_str = format!("{:?}", thread_id);
+        let clean_thread_id = thread_id_str.replace("ThreadId(", "").replace(")", "");
+        let route = format!("tests/temp-{}", clean_thread_id);
+        let ok = duplicate_temp_file(
+            CLIENTES_DIR,
+            "tests",
+            &format!("temp-{}", clean_thread_id),
+            "clientes.csv",
+        );
+        match ok {
+            Ok(_) => (),
+            Err(_) => {
+                println!("FAIL: Could not duplicate file\n");
+                assert_eq!(false, true)
+            }
+        }
+
+        let insert = run(
+            &route,
+            "INSERT INTO clientes (id_cliente, nombre, apellido) VALUES (111, 'pepe', 'garcia')",
+        );
+        let greater = run(
+            &route,
+            "SELECT id_cliente FROM clientes WHERE id_cliente > 110 AND telefono > 0",
+        );
+        let is_null = run(
+            &route,
+            "SELECT id_cliente, telefono, email FROM clientes WHERE telefono IS NULL",
+        );
+        let not_or = run(
+            &route,
+            "SELECT id_cliente FROM clientes WHERE NOT (id_cliente < 111 OR telefono > 0)",
+        );
+        let count = run(&route, "SELECT COUNT(telefono), COUNT(*) FROM clientes");
+        let order = run(
+            &route,
+            "SELECT id_cliente FROM clientes WHERE id_cliente > 108 ORDER BY telefono DESC NULLS LAST",
+        );
+        let update = run(
+            &route,
+            "UPDATE clientes SET telefono = NULL, email = email || '.ar' WHERE id_cliente >= 110",
+        );
+        let is_not_null = run(
+            &route,
+            "SELECT id_cliente FROM clientes WHERE telefono IS NOT NULL AND id_cliente > 108",
+        );
+
+        let content = std::fs::read_to_string(format!("{}/clientes.csv", route));
+        if delete_file(&route).is_err() {
+            println!("FAIL: Could not delete file\n");
+            assert_eq!(false, true)
+        }
+
+        assert!(insert.1.is_empty());
+        assert_eq!(greater, (String::new(), String::new()));
+        assert_eq!(is_null, ("111, , \n".to_string(), String::new()));
+        assert_eq!(not_or, (String::new(), String::new()));
+        assert_eq!(count, ("10, 11\n".to_string(), String::new()));
+        assert_eq!(order, ("110\n109\n111\n".to_string(), String::new()));
+        assert!(update.1.is_empty());
+        assert_eq!(is_not_null, ("109\n".to_string(), String::new()));
+        assert!(content
+            .unwrap_or_default()
+            .ends_with("110,paula,vera,paula@email.com.ar,\\N\n111,pepe,garcia,\\N,\\N\n"));
+    }
+
+    #[test]
+    fn test_null_on_required_field() {
+        let thread_id = std::thread::current().id();
+        let thread_id_str = format!("{:?}", thread_id);
+        let clean_thread_id = thread_id_str.replace("ThreadId(", "").replace(")", "");
+        let dir = format!("tests/temp-{}", clean_thread_id);
+        if std::fs::create_dir(&dir).is_err() {
+            println!("FAIL: Could not create dir\n");
+            assert_eq!(false, true)
+        }
+
+        let create = run(
+            &dir,
+            "CREATE TABLE cuentas (id INTEGER PRIMARY KEY, titular TEXT NOT NULL, saldo REAL DEFAULT NULL)",
+        );
+        let insert_null = run(&dir, "INSERT INTO cuentas (id, titular) VALUES (1, NULL)");
+        let insert = run(&dir, "INSERT INTO cuentas (id, titular) VALUES (1, 'ana')");
+        let insert_empty = run(&dir, "INSERT INTO cuentas (id, titular) VALUES (2, '')");
+        let delete_empty = run(&dir, "DELETE FROM cuentas WHERE titular = ''");
+        let update_null = run(&dir, "UPDATE cuentas SET titular = NULL");
+        let update_computed = run(&dir, "UPDATE cuentas SET id = saldo + 1");
+        let sum = run(
+            &dir,
+            "SELECT SUM(saldo), MAX(saldo), COUNT(saldo) FROM cuentas",
+        );
+
+        let content = std::fs::read_to_string(format!("{}/cuentas.csv", dir));
+        if delete_file(&dir).is_err() {
+            println!("FAIL: Could not delete file\n");
+            assert_eq!(false, true)
+        }
+
+        assert!(create.1.is_empty());
+        assert_eq!(
+            insert_null.1,
+            "[INVALID_COLUMN]: [field [ titular ] can not be empty ]\n"
+        );
+        assert!(insert.1.is_empty());
+        assert!(insert_empty.1.is_empty());
+        assert!(delete_empty.1.is_empty());
+        assert_eq!(
+            update_null.1,
+            "[INVALID_COLUMN]: [field [ titular ] can not be empty ]\n"
+        );
+        assert_eq!(
+            update_computed.1,
+            "[INVALID_COLUMN]: [field [ id ] can not be empty ]\n"
+        );
+        assert_eq!(sum, (", , 0\n".to_string(), String::new()));
+        assert_eq!(content.unwrap_or_default(), "id,titular,saldo\n1,ana,\\N\n");
+    }
+}
+
+#[cfg(test)]
+mod test_numeric_types {
+    use crate::{delete_file, run};
+
+    #[test]
+    fn test_large_integers_compare_numerically() {
+        let (stdout, stderr) = run(
+            "data/tables",
+            "SELECT id_cliente FROM clientes WHERE telefono > 999 AND telefono < 5553000000",
+        );
+        assert!(stderr.is_empty());
+        assert_eq!(stdout, "101\n103\n105\n");
+
+        let (stdout, stderr) = run(
+            "data/tables",
+            "SELECT id_cliente FROM clientes WHERE telefono IN (5551234567, 5559876543.0)",
+        );
+        assert!(stderr.is_empty());
+        assert_eq!(stdout, "101\n102\n");
+    }
+
+    #[test]
+    fn test_literal_promoted_against_integer_field() {
+        let (stdout, stderr) = run(
+            "data/tables",
+            "SELECT nombre FROM productos WHERE stock > 2.5 AND stock < 3000000000 AND stock <= 25",
+        );
+        assert!(stderr.is_empty());
+        assert_eq!(stdout, "Laptop\nMonitor\n");
+    }
+
+    #[test]
+    fn test_decimal_field() {
+        let thread_id = std::thread::current().id();
+        let thread_id_str = format!("{:?}", thread_id);
+        let clean_thread_id = thread_id_str.replace("ThreadId(", "").replace(")", "");
+        let dir = format!("tests/temp-{}", clean_thread_id);
+        if std::fs::create_dir(&dir).is_err() {
+            println!("FAIL: Could not create dir\n");
+            assert_eq!(false, true)
+        }
+
+        let create = run(&dir, "CREATE TABLE pagos (id BIGINT, monto DECIMAL)");
+        let insert = run(
+            &dir,
+            "INSERT INTO pagos (id, monto) VALUES (1, 10.10), (2, 20.20), (3, 0.1)",
+        );
+        let overflow = run(
+            &dir,
+            "INSERT INTO pagos (id, monto) VALUES (9223372036854775808, 1)",
+        );
+        let aggregates = run(&dir, "SELECT SUM(monto), AVG(monto), MAX(monto) FROM pagos");
+        let exact = run(
+            &dir,
+            "SELECT id, monto + 0.2, monto * 3 FROM pagos WHERE monto + 0.2 = 0.3 OR monto = 20.2",
+        );
+        let update = run(&dir, "UPDATE pagos SET monto = monto / 3 WHERE id = 1");
+        let arithmetic_overflow = run(
+            &dir,
+            "SELECT monto * 99999999999999999999999999999999999 FROM pagos",
+        );
+
+        let content = std::fs::read_to_string(format!("{}/pagos.csv", dir));
+        if delete_file(&dir).is_err() {
+            println!("FAIL: Could not delete file\n");
+            assert_eq!(false, true)
+        }
+
+        assert!(create.1.is_empty());
+        assert!(insert.1.is_empty());
+        assert_eq!(
+            overflow.1,
+            "[INVALID_COLUMN]: [value [ 9223372036854775808 ] overflows field [ id ] of type BIGINT ]\n"
+        );
+        assert_eq!(
+            aggregates,
+            ("30.40, 10.13333333, 20.20\n".to_string(), String::new())
+        );
+        assert_eq!(
+            exact,
+            ("2, 20.40, 60.60\n3, 0.3, 0.3\n".to_string(), String::new())
+        );
+        assert!(update.1.is_empty());
+        assert_eq!(
+            arithmetic_overflow.1,
+            "[INVALID_SYNTAX]: [numeric overflow on 3.36666667 * 99999999999999999999999999999999999 ]\n"
+        );
+        assert_eq!(
+            content.unwrap_or_default(),
+            "id,monto\n1,3.36666667\n2,20.20\n3,0.1\n"
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_coercion {
+    use crate::{delete_file, run_with_options};
+
+    #[test]
+    fn test_mixed_values_compared_as_text_or_strict() {
+        let thread_id = std::thread::current().id();
+        let thread_id_str = format!("{:?}", thread_id);
+        let clean_thread_id = thread_id_str.replace("ThreadId(", "").replace(")", "");
+        let dir = format!("tests/temp-{}", clean_thread_id);
+        if std::fs::create_dir(&dir).is_err() {
+            println!("FAIL: Could not create dir\n");
+            assert_eq!(false, true)
+        }
+        let written = std::fs::write(
+            format!("{}/codigos.csv", dir),
+            "id,codigo\n1,10\n2,abc\n3,7\n",
+        );
+
+        let coerced = run_with_options(&[], &dir, "SELECT id FROM codigos WHERE codigo > 8");
+        let strict = run_with_options(
+            &["--strict"],
+            &dir,
+            "SELECT id FROM codigos WHERE codigo > 8",
+        );
+        let strict_numbers =
+            run_with_options(&["--strict"], &dir, "SELECT id FROM codigos WHERE id > 1");
+        let strict_delete =
+            run_with_options(&["--strict"], &dir, "DELETE FROM codigos WHERE codigo = 7");
+        let content = std::fs::read_to_string(format!("{}/codigos.csv", dir));
+        let unknown = run_with_options(&["--verbose"], &dir, "SELECT id FROM codigos");
+
+        if delete_file(&dir).is_err() {
+            println!("FAIL: Could not delete file\n");
+            assert_eq!(false, true)
+        }
+
+        assert!(written.is_ok());
+        assert_eq!(coerced, ("1\n2\n".to_string(), String::new()));
+        // los registros anteriores ya se imprimieron
+        assert_eq!(
+            strict,
+            (
+                "1\n".to_string(),
+                "[INVALID_SYNTAX]: [invalid type comparison, abc and 8 on row 2 ]\n".to_string()
+            )
+        );
+        assert_eq!(strict_numbers, ("2\n3\n".to_string(), String::new()));
+        assert_eq!(
+            strict_delete.1,
+            "[INVALID_SYNTAX]: [invalid type comparison, abc and 7 on row 2 ]\n"
+        );
+        assert_eq!(content.unwrap_or_default(), "id,codigo\n1,10\n2,abc\n3,7\n");
+        assert_eq!(unknown.1, "[INVALID_SYNTAX]: [Unknown option --verbose.]\n");
+    }
+
+    #[test]
+    fn test_invalid_stored_value_on_order_and_aggregates() {
+        let thread_id = std::thread::current().id();
+        let thread_id_str = format!("{:?}", thread_id);
+        let clean_thread_id = thread_id_str.replace("ThreadId(", "").replace(")", "");
+        let dir = format!("tests/temp-{}", clean_thread_id);
+        if std::fs::create_dir(&dir).is_err() {
+            println!("FAIL: Could not create dir\n");
+            assert_eq!(false, true)
+        }
+        let written = std::fs::write(
+            format!("{}/codigos.csv", dir),
+            "id,codigo\n1,10\n2,abc\n3,7\n",
+        )
+        .and_then(|_| {
+            std::fs::write(
+                format!("{}/codigos.schema", dir),
+                "id INTEGER\ncodigo INTEGER\n",
+            )
+        });
+
+        let sentence = "SELECT id FROM codigos ORDER BY codigo";
+        let coerced = run_with_options(&[], &dir, sentence);
+        let strict = run_with_options(&["--strict"], &dir, sentence);
+        let aggregates = "SELECT COUNT(codigo), MIN(codigo), MAX(codigo), SUM(codigo) FROM codigos";
+        let coerced_aggregates = run_with_options(&[], &dir, aggregates);
+        let strict_aggregates = run_with_options(&["--strict"], &dir, aggregates);
+
+        if delete_file(&dir).is_err() {
+            println!("FAIL: Could not delete file\n");
+            assert_eq!(false, true)
+        }
+
+        assert!(written.is_ok());
+        assert_eq!(coerced, ("3\n1\n2\n".to_string(), String::new()));
+        assert_eq!(
+            strict.1,
+            "[INVALID_SYNTAX]: [unable to order by abc, it is not a valid INTEGER ]\n"
+        );
+        assert_eq!(
+            coerced_aggregates,
+            ("3, 7, abc, 17\n".to_string(), String::new())
+        );
+        assert_eq!(
+            strict_aggregates.1,
+            "[INVALID_SYNTAX]: [invalid value abc for a field of type INTEGER on COUNT(codigo) on row 2 ]\n"
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_dates {
+    use crate::{delete_file, run};
+
+    #[test]
+    fn test_date_time_and_timestamp_fields() {
+        let thread_id = std::thread::current().id();
+        let thread_id_str = format!("{:?}", thread_id);
+        let clean_thread_id = thread_id_str.replace("ThreadId(", "").replace(")", "");
+        let dir = format!("tests/temp-{}", clean_thread_id);
+        if std::fs::create_dir(&dir).is_err() {
+            println!("FAIL: Could not create dir\n");
+            assert_eq!(false, true)
+        }
+
+        let create = run(
+            &dir,
+            "CREATE TABLE eventos (id INTEGER, dia DATE, hora TIME, creado TIMESTAMP)",
+        );
+        let insert = run(
+            &dir,
+            "INSERT INTO eventos (id, dia, hora, creado) VALUES \
+             (1, '2024-01-31', '09:30', '2024-01-31 09:30:00'), \
+             (2, '2023-12-15', '18:00:00', '2023-12-15T18:00:00'), \
+             (3, '2024-02-29', '23:45', '2024-02-29 23:45:10')",
+        );
+        let invalid = run(
+            &dir,
+            "INSERT INTO eventos (id, dia) VALUES (4, '2023-02-29')",
+        );
+        let compared = run(
+            &dir,
+            "SELECT id FROM eventos WHERE creado >= '2024-01-01' AND dia < NOW() AND hora > '12:00'",
+        );
+        let functions = run(
+            &dir,
+            "SELECT id, DATE_ADD(dia, INTERVAL 1 MONTH), EXTRACT(YEAR FROM creado), \
+             DATE_TRUNC('month', creado) FROM eventos ORDER BY EXTRACT(MONTH FROM dia) DESC",
+        );
+        let ordered = run(&dir, "SELECT id FROM eventos ORDER BY hora DESC");
+        let update = run(
+            &dir,
+            "UPDATE eventos SET dia = DATE_ADD(dia, INTERVAL -1 DAY), \
+             creado = DATE_TRUNC('hour', creado) WHERE EXTRACT(DAY FROM dia) = 29",
+        );
+        let content = std::fs::read_to_string(format!("{}/eventos.csv", dir));
+
+        if delete_file(&dir).is_err() {
+            println!("FAIL: Could not delete file\n");
+            assert_eq!(false, true)
+        }
+
+        assert!(create.1.is_empty());
+        assert!(insert.1.is_empty());
+        assert_eq!(
+            invalid.1,
+            "[INVALID_COLUMN]: [invalid value [ 2023-02-29 ] for field [ dia ] of type DATE ]\n"
+        );
+        assert_eq!(compared, ("3\n".to_string(), String::new()));
+        assert_eq!(
+            functions,
+            (
+                "2, 2024-01-15, 2023, 2023-12-01T00:00:00\n\
+                 3, 2024-03-29, 2024, 2024-02-01T00:00:00\n\
+                 1, 2024-02-29, 2024, 2024-01-01T00:00:00\n"
+                    .to_string(),
+                String::new()
+            )
+        );
+        assert_eq!(ordered, ("3\n2\n1\n".to_string(), String::new()));
+        assert!(update.1.is_empty());
+        assert_eq!(
+            content.unwrap_or_default(),
+            "id,dia,hora,creado\n\
+             1,2024-01-31,09:30:00,2024-01-31T09:30:00\n\
+             2,2023-12-15,18:00:00,2023-12-15T18:00:00\n\
+             3,2024-02-28,23:45:00,2024-02-29T23:00:00\n"
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_keywords_as_names {
+    use crate::{delete_file, run};
+
+    #[test]
+    fn test_fields_named_like_keywords() {
+        let thread_id = std::thread::current().id();
+        let thread_id_str = format!("{:?}", thread_id);
+        let clean_thread_id = thread_id_str.replace("ThreadId(", "").replace(")", "");
+        let dir = format!("tests/temp-{}", clean_thread_id);
+        if std::fs::create_dir(&dir).is_err() {
+            println!("FAIL: Could not create dir\n");
+            assert_eq!(false, true)
+        }
+        let written = std::fs::write(
+            format!("{}/turnos.csv", dir),
+            "first,last,key\nana,perez,1\npepe,garcia,2\n",
+        );
+
+        let insert = run(
+            &dir,
+            "INSERT INTO turnos (first, last, key) VALUES ('juan', 'lopez', 3)",
+        );
+        let select = run(
+            &dir,
+            "SELECT last, first FROM turnos WHERE key > 1 ORDER BY first DESC",
+        );
+
+        if delete_file(&dir).is_err() {
+            println!("FAIL: Could not delete file\n");
+            assert_eq!(false, true)
+        }
+
+        assert!(written.is_ok());
+        assert!(insert.1.is_empty());
+        assert_eq!(
+            select,
+            ("garcia, pepe\nlopez, juan\n".to_string(), String::new())
+        );
+    }
+}